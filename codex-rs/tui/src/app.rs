@@ -1634,6 +1634,9 @@ impl App {
             AppEvent::StatuslineGitPreviewUpdated(preview) => {
                 self.chat_widget.set_statusline_git_preview(preview);
             }
+            AppEvent::StatuslineJjPreviewUpdated(preview) => {
+                self.chat_widget.set_statusline_jj_preview(preview);
+            }
             AppEvent::ConnectorsLoaded { result, is_final } => {
                 self.chat_widget.on_connectors_loaded(result, is_final);
             }
@@ -22,6 +22,7 @@ use crate::bottom_pane::ApprovalRequest;
 use crate::bottom_pane::StatusLineItem;
 use crate::history_cell::HistoryCell;
 use crate::statusline::GitPreviewData;
+use crate::statusline::JjPreviewData;
 
 use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
@@ -100,6 +101,9 @@ pub(crate) enum AppEvent {
     /// Result of refreshing statusline Git info
     StatuslineGitPreviewUpdated(GitPreviewData),
 
+    /// Result of refreshing statusline Jujutsu info
+    StatuslineJjPreviewUpdated(JjPreviewData),
+
     /// Result of prefetching connectors.
     ConnectorsLoaded {
         result: Result<ConnectorsSnapshot, String>,
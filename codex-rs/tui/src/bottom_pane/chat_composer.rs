@@ -95,9 +95,12 @@ use crate::key_hint::KeyBinding;
 use crate::key_hint::has_ctrl_or_alt;
 use crate::statusline::CxLineConfig;
 use crate::statusline::GitPreviewData;
+use crate::statusline::JjPreviewData;
 use crate::statusline::StatusLineContext;
 use crate::statusline::StatusLineWidget;
 use crate::statusline::build_statusline;
+use crate::statusline::capability::TerminalCapabilities;
+use crate::statusline::style::ColorSupport;
 use crate::ui_consts::FOOTER_INDENT_COLS;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -316,13 +319,24 @@ pub(crate) struct ChatComposer {
     windows_degraded_sandbox_active: bool,
     // CxLine 状态栏相关数据
     statusline_config: CxLineConfig,
+    /// 加载配置时的错误，用户保存新配置后清除
+    statusline_config_error: Option<String>,
     statusline_model: String,
     statusline_reasoning_effort: Option<ReasoningEffort>,
     statusline_cwd: PathBuf,
     statusline_hourly_rate_limit_percent: Option<f64>,
     statusline_weekly_rate_limit_percent: Option<f64>,
     statusline_weekly_rate_limit_resets_at: Option<String>,
+    statusline_weekly_rate_limit_resets_at_timestamp: Option<i64>,
     statusline_git_preview: Option<GitPreviewData>,
+    /// 由后台轮询更新，渲染时不执行 jj 命令
+    statusline_jj_preview: Option<JjPreviewData>,
+    /// 本会话编辑过的文件数
+    statusline_edited_files: Option<usize>,
+    /// 创建时检测的终端颜色级别，渲染时按此降级
+    statusline_color_support: ColorSupport,
+    /// 是否有 segment 提供副标题，数据或配置变化时更新，布局时直接读取
+    statusline_has_subtitle: bool,
     // Official status line
     status_line_value: Option<Line<'static>>,
     status_line_enabled: bool,
@@ -381,6 +395,7 @@ impl ChatComposer {
         config: ChatComposerConfig,
     ) -> Self {
         let use_shift_enter_hint = enhanced_keys_supported;
+        let statusline = CxLineConfig::load_checked();
 
         let mut this = Self {
             textarea: TextArea::new(),
@@ -424,19 +439,26 @@ impl ChatComposer {
             personality_command_enabled: false,
             windows_degraded_sandbox_active: false,
             // CxLine 状态栏初始化
-            statusline_config: CxLineConfig::load(),
+            statusline_config: statusline.config,
+            statusline_config_error: statusline.last_error,
             statusline_model: String::new(),
             statusline_reasoning_effort: None,
             statusline_cwd: PathBuf::new(),
             statusline_hourly_rate_limit_percent: None,
             statusline_weekly_rate_limit_percent: None,
             statusline_weekly_rate_limit_resets_at: None,
+            statusline_weekly_rate_limit_resets_at_timestamp: None,
             statusline_git_preview: Some(GitPreviewData::empty()),
+            statusline_jj_preview: Some(JjPreviewData::empty()),
+            statusline_edited_files: None,
+            statusline_color_support: TerminalCapabilities::detect().color_support,
+            statusline_has_subtitle: false,
             status_line_value: None,
             status_line_enabled: false,
         };
         // Apply configuration via the setter to keep side-effects centralized.
         this.set_disable_paste_burst(disable_paste_burst);
+        this.refresh_statusline_subtitle();
         this
     }
 
@@ -531,6 +553,7 @@ impl ChatComposer {
         hourly_rate_limit_percent: Option<f64>,
         weekly_rate_limit_percent: Option<f64>,
         weekly_rate_limit_resets_at: Option<String>,
+        weekly_rate_limit_resets_at_timestamp: Option<i64>,
     ) {
         self.statusline_model = model.to_string();
         self.statusline_reasoning_effort = reasoning_effort;
@@ -538,6 +561,9 @@ impl ChatComposer {
         self.statusline_hourly_rate_limit_percent = hourly_rate_limit_percent;
         self.statusline_weekly_rate_limit_percent = weekly_rate_limit_percent;
         self.statusline_weekly_rate_limit_resets_at = weekly_rate_limit_resets_at;
+        self.statusline_weekly_rate_limit_resets_at_timestamp =
+            weekly_rate_limit_resets_at_timestamp;
+        self.refresh_statusline_subtitle();
     }
 
     /// 获取当前状态栏配置
@@ -548,6 +574,8 @@ impl ChatComposer {
     /// 设置状态栏配置
     pub fn set_statusline_config(&mut self, config: CxLineConfig) {
         self.statusline_config = config;
+        self.statusline_config_error = None;
+        self.refresh_statusline_subtitle();
     }
 
     /// 设置状态栏 Git 预览数据
@@ -556,6 +584,63 @@ impl ChatComposer {
             return;
         }
         self.statusline_git_preview = Some(preview);
+        self.refresh_statusline_subtitle();
+    }
+
+    /// 设置状态栏 Jj 预览数据
+    pub fn set_statusline_jj_preview(&mut self, preview: JjPreviewData) {
+        if self.statusline_jj_preview.as_ref() == Some(&preview) {
+            return;
+        }
+        self.statusline_jj_preview = Some(preview);
+        self.refresh_statusline_subtitle();
+    }
+
+    /// 设置本会话编辑过的文件数
+    pub fn set_statusline_edited_files(&mut self, count: usize) {
+        self.statusline_edited_files = Some(count);
+        self.refresh_statusline_subtitle();
+    }
+
+    /// 状态栏渲染上下文（布局与渲染共用）
+    fn statusline_context(&self) -> StatusLineContext<'_> {
+        let mut ctx = StatusLineContext::new(&self.statusline_model, &self.statusline_cwd)
+            .with_reasoning_effort(self.statusline_reasoning_effort)
+            .with_config_error(self.statusline_config_error.clone())
+            .with_context(self.context_window_used_tokens, self.context_window_size)
+            .with_session_edited_files(self.statusline_edited_files)
+            .with_rate_limit(
+                self.statusline_hourly_rate_limit_percent,
+                self.statusline_weekly_rate_limit_percent,
+                self.statusline_weekly_rate_limit_resets_at.clone(),
+            )
+            .with_rate_limit_reset_timestamp(self.statusline_weekly_rate_limit_resets_at_timestamp);
+        // 传入完整的预览数据（含冲突数、远端分叉等）
+        ctx.git_preview = self.statusline_git_preview.clone();
+        ctx.jj_preview = self.statusline_jj_preview.clone();
+        ctx
+    }
+
+    /// 重新判断是否显示副标题；构建状态栏可能执行 git 命令，只在数据或配置变化时调用
+    fn refresh_statusline_subtitle(&mut self) {
+        let has_subtitle = self.statusline_config.enabled && {
+            let ctx = self.statusline_context();
+            let renderer = build_statusline(&self.statusline_config, &ctx);
+            // 左右分组排布时不显示副标题
+            !renderer.has_right_aligned() && renderer.render_subtitle_line().is_some()
+        };
+        self.statusline_has_subtitle = has_subtitle;
+    }
+
+    /// 状态栏高度：禁用时为 0；有 segment 提供副标题时为 2，副标题占第二行；否则为 1
+    fn statusline_height(&self) -> u16 {
+        if !self.statusline_config.enabled {
+            0
+        } else if self.statusline_has_subtitle {
+            2
+        } else {
+            1
+        }
     }
 
     fn layout_areas(&self, area: Rect) -> [Rect; 4] {
@@ -566,8 +651,7 @@ impl ChatComposer {
         let footer_spacing = Self::footer_spacing(footer_hint_height);
         let footer_total_height = footer_hint_height + footer_spacing;
 
-        // 状态栏高度（启用时为 1，禁用时为 0）
-        let statusline_height = if self.statusline_config.enabled { 1 } else { 0 };
+        let statusline_height = self.statusline_height();
 
         let popup_constraint = match &self.active_popup {
             ActivePopup::Command(popup) => {
@@ -3248,6 +3332,7 @@ impl ChatComposer {
         }
         self.context_window_used_tokens = used_tokens;
         self.context_window_size = window_size;
+        self.refresh_statusline_subtitle();
     }
 
     pub(crate) fn set_esc_backtrack_hint(&mut self, show: bool) {
@@ -3348,8 +3433,7 @@ impl Renderable for ChatComposer {
             .unwrap_or_else(|| footer_height(&footer_props));
         let footer_spacing = Self::footer_spacing(footer_hint_height);
         let footer_total_height = footer_hint_height + footer_spacing;
-        // 状态栏高度（启用时为 1，禁用时为 0）
-        let statusline_height = if self.statusline_config.enabled { 1 } else { 0 };
+        let statusline_height = self.statusline_height();
         const COLS_WITH_MARGIN: u16 = LIVE_PREFIX_COLS + 1;
         self.textarea
             .desired_height(width.saturating_sub(COLS_WITH_MARGIN))
@@ -3619,24 +3703,9 @@ impl ChatComposer {
             && statusline_rect.height > 0
             && statusline_rect.y < area.y + area.height
         {
-            let mut ctx = StatusLineContext::new(&self.statusline_model, &self.statusline_cwd)
-                .with_reasoning_effort(self.statusline_reasoning_effort)
-                .with_context(self.context_window_used_tokens, self.context_window_size)
-                .with_rate_limit(
-                    self.statusline_hourly_rate_limit_percent,
-                    self.statusline_weekly_rate_limit_percent,
-                    self.statusline_weekly_rate_limit_resets_at.clone(),
-                );
-            if let Some(preview) = &self.statusline_git_preview {
-                ctx = ctx.with_git_preview(
-                    &preview.branch,
-                    &preview.status,
-                    preview.ahead,
-                    preview.behind,
-                );
-            }
-            let renderer = build_statusline(&self.statusline_config, &ctx);
-            let statusline_widget = StatusLineWidget::from_renderer(&renderer);
+            let ctx = self.statusline_context();
+            let renderer = build_statusline(&self.statusline_config, &ctx)
+                .with_color_support(self.statusline_color_support);
             // 添加左边距，与输入框 ❯ 提示符对齐
             let aligned_rect = Rect::new(
                 statusline_rect.x + LIVE_PREFIX_COLS,
@@ -3644,6 +3713,14 @@ impl ChatComposer {
                 statusline_rect.width.saturating_sub(LIVE_PREFIX_COLS),
                 statusline_rect.height,
             );
+            // 有靠右的 segment 或配置了溢出提示符时按可用宽度排布，放不下时先换用紧凑形式再丢弃
+            let statusline_widget = if renderer.has_right_aligned()
+                || self.statusline_config.overflow_indicator.is_some()
+            {
+                StatusLineWidget::new(renderer.render_line_for_width(aligned_rect.width))
+            } else {
+                StatusLineWidget::from_renderer(&renderer.compacted_for_width(aligned_rect.width))
+            };
             statusline_widget.render_ref(aligned_rect, buf);
         }
 
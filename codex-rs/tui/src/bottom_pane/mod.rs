@@ -683,6 +683,18 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// 设置状态栏 Jj 预览数据
+    pub(crate) fn set_statusline_jj_preview(&mut self, preview: crate::statusline::JjPreviewData) {
+        self.composer.set_statusline_jj_preview(preview);
+        self.request_redraw();
+    }
+
+    /// 设置本会话编辑过的文件数
+    pub(crate) fn set_statusline_edited_files(&mut self, count: usize) {
+        self.composer.set_statusline_edited_files(count);
+        self.request_redraw();
+    }
+
     /// 设置状态栏数据
     pub(crate) fn set_statusline_data(
         &mut self,
@@ -692,6 +704,7 @@ impl BottomPane {
         hourly_rate_limit_percent: Option<f64>,
         weekly_rate_limit_percent: Option<f64>,
         weekly_rate_limit_resets_at: Option<String>,
+        weekly_rate_limit_resets_at_timestamp: Option<i64>,
     ) {
         self.composer.set_statusline_data(
             model,
@@ -700,6 +713,7 @@ impl BottomPane {
             hourly_rate_limit_percent,
             weekly_rate_limit_percent,
             weekly_rate_limit_resets_at,
+            weekly_rate_limit_resets_at_timestamp,
         );
         self.request_redraw();
     }
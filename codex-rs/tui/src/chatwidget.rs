@@ -210,7 +210,11 @@ use crate::render::renderable::RenderableItem;
 use crate::slash_command::SlashCommand;
 use crate::status::RateLimitSnapshotDisplay;
 use crate::statusline::GitPreviewData;
+use crate::statusline::JjPreviewData;
+use crate::statusline::SegmentId;
 use crate::statusline::collect_git_preview;
+use crate::statusline::collect_jj_preview;
+use crate::statusline::reset_git_base;
 use crate::text_formatting::truncate_text;
 use crate::tui::FrameRequester;
 mod interrupts;
@@ -320,7 +324,7 @@ fn is_standard_tool_call(parsed_cmd: &[ParsedCommand]) -> bool {
 const RATE_LIMIT_WARNING_THRESHOLDS: [f64; 3] = [75.0, 90.0, 95.0];
 const NUDGE_MODEL_SLUG: &str = "gpt-5.1-codex-mini";
 const RATE_LIMIT_SWITCH_PROMPT_THRESHOLD: f64 = 90.0;
-const STATUSLINE_GIT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const STATUSLINE_GIT_MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(Default)]
 struct RateLimitWarningState {
@@ -516,6 +520,8 @@ pub(crate) struct ChatWidget {
     rate_limit_switch_prompt: RateLimitSwitchPromptState,
     rate_limit_poller: Option<JoinHandle<()>>,
     statusline_git_poller: Option<JoinHandle<()>>,
+    /// 本会话中成功应用补丁修改过的文件（供状态栏 Edits segment 计数）
+    statusline_edited_files: HashSet<PathBuf>,
     adaptive_chunking: AdaptiveChunkingPolicy,
     // Stream lifecycle controller
     stream_controller: Option<StreamController>,
@@ -1026,6 +1032,10 @@ impl ChatWidget {
         self.thread_name = event.thread_name.clone();
         self.forked_from = event.forked_from_id;
         self.current_rollout_path = event.rollout_path.clone();
+        if self.current_cwd.as_ref() != Some(&event.cwd) {
+            // cwd 切换后丢弃旧的 git 缓存结果
+            crate::statusline::segments::invalidate_git_cache(None);
+        }
         self.current_cwd = Some(event.cwd.clone());
         let initial_messages = event.initial_messages.clone();
         let forked_from_id = event.forked_from_id;
@@ -1494,21 +1504,23 @@ impl ChatWidget {
             .rate_limit_snapshots_by_limit_id
             .get("codex")
             .or_else(|| self.rate_limit_snapshots_by_limit_id.values().next());
-        let (hourly_percent, weekly_percent, weekly_resets_at) = if let Some(snapshot) = snapshot {
-            let hourly = snapshot.primary.as_ref().map(|p| p.used_percent);
-            let weekly = snapshot.secondary.as_ref().map(|s| s.used_percent);
-            // Format reset time as "M-D-H" for StatusLine
-            let resets_at = snapshot
-                .secondary
-                .as_ref()
-                .and_then(|s| s.resets_at_timestamp)
-                .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
-                .map(|dt: DateTime<Utc>| dt.with_timezone(&Local))
-                .map(|dt| dt.format("%-m-%-d-%-H").to_string());
-            (hourly, weekly, resets_at)
-        } else {
-            (None, None, None)
-        };
+        let (hourly_percent, weekly_percent, weekly_resets_at_timestamp) =
+            if let Some(snapshot) = snapshot {
+                let hourly = snapshot.primary.as_ref().map(|p| p.used_percent);
+                let weekly = snapshot.secondary.as_ref().map(|s| s.used_percent);
+                let resets_at_timestamp = snapshot
+                    .secondary
+                    .as_ref()
+                    .and_then(|s| s.resets_at_timestamp);
+                (hourly, weekly, resets_at_timestamp)
+            } else {
+                (None, None, None)
+            };
+        // Format reset time as "M-D-H" for StatusLine; the raw timestamp is passed alongside
+        let weekly_resets_at = weekly_resets_at_timestamp
+            .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
+            .map(|dt: DateTime<Utc>| dt.with_timezone(&Local))
+            .map(|dt| dt.format("%-m-%-d-%-H").to_string());
 
         // Get current model and reasoning effort from effective collaboration mode
         let reasoning_effort = self.effective_reasoning_effort();
@@ -1521,6 +1533,7 @@ impl ChatWidget {
             hourly_percent,
             weekly_percent,
             weekly_resets_at,
+            weekly_resets_at_timestamp,
         );
     }
 
@@ -1529,37 +1542,75 @@ impl ChatWidget {
             return;
         }
 
-        let app_event_tx = self.app_event_tx.clone();
-        let cwd = self.config.cwd.clone();
+        let statusline_config = self.bottom_pane.get_statusline_config();
+        // interval 不接受 0，限制最小轮询间隔
+        let poll_interval = |id| {
+            statusline_config
+                .command_cache_ttl(id)
+                .max(STATUSLINE_GIT_MIN_POLL_INTERVAL)
+        };
+        let git_interval = poll_interval(SegmentId::Git);
+        let jj_interval = poll_interval(SegmentId::Jj);
+        let git_config = statusline_config.get_segment_config(SegmentId::Git).clone();
+        let jj_config = statusline_config.get_segment_config(SegmentId::Jj).clone();
 
         let Ok(handle) = tokio::runtime::Handle::try_current() else {
             return;
         };
 
-        let poller = Some(handle.spawn(async move {
-            let mut interval = tokio::time::interval(STATUSLINE_GIT_POLL_INTERVAL);
-            let mut last_preview: Option<GitPreviewData> = None;
+        let git_cwd = self.config.cwd.clone();
+        let git = poll_statusline_preview(
+            git_interval,
+            move || {
+                collect_git_preview(&git_cwd, &git_config).unwrap_or_else(GitPreviewData::empty)
+            },
+            self.app_event_tx.clone(),
+            AppEvent::StatuslineGitPreviewUpdated,
+        );
+        // jj segment 未启用时不执行 jj 命令
+        let jj_cwd = self.config.cwd.clone();
+        let jj_enabled = jj_config.enabled;
+        let jj = poll_statusline_preview(
+            jj_interval,
+            move || collect_jj_preview(&jj_cwd, &jj_config).unwrap_or_else(JjPreviewData::empty),
+            self.app_event_tx.clone(),
+            AppEvent::StatuslineJjPreviewUpdated,
+        );
 
-            loop {
-                interval.tick().await;
-                let cwd_clone = cwd.clone();
-                let preview = tokio::task::spawn_blocking(move || collect_git_preview(&cwd_clone))
-                    .await
-                    .ok()
-                    .flatten()
-                    .unwrap_or_else(GitPreviewData::empty);
-
-                if last_preview.as_ref() == Some(&preview) {
-                    continue;
-                }
-                app_event_tx.send(AppEvent::StatuslineGitPreviewUpdated(preview.clone()));
-                last_preview = Some(preview);
+        let poller = Some(handle.spawn(async move {
+            if jj_enabled {
+                tokio::join!(git, jj);
+            } else {
+                git.await;
             }
         }));
 
         self.statusline_git_poller = poller;
     }
 
+    /// 将当前 HEAD 记录为 git segment 的 base 提交，下一次轮询即显示新的 ahead/behind
+    fn reset_statusline_git_base(&mut self) {
+        let statusline_config = self.bottom_pane.get_statusline_config();
+        let git_config = statusline_config.get_segment_config(SegmentId::Git);
+        if !git_config.option_bool("show_base").unwrap_or(false) {
+            self.add_info_message(
+                "Git base tracking is off.".to_string(),
+                Some(
+                    "Enable the show_base option of the git segment to use /reset-base."
+                        .to_string(),
+                ),
+            );
+            return;
+        }
+        match reset_git_base(&self.config.cwd, git_config) {
+            Ok(sha) => {
+                let short = sha.get(..7).unwrap_or(&sha);
+                self.add_info_message(format!("Git base reset to {short}"), None);
+            }
+            Err(err) => self.add_error_message(format!("Failed to reset git base: {err}")),
+        }
+    }
+
     fn stop_statusline_git_poller(&mut self) {
         if let Some(handle) = self.statusline_git_poller.take() {
             handle.abort();
@@ -2456,6 +2507,11 @@ impl ChatWidget {
         // Otherwise, add a failure block.
         if !event.success {
             self.add_to_history(history_cell::new_patch_apply_failure(event.stderr));
+        } else {
+            self.statusline_edited_files
+                .extend(event.changes.into_keys());
+            self.bottom_pane
+                .set_statusline_edited_files(self.statusline_edited_files.len());
         }
         // Mark that actual work was done (patch applied)
         self.had_work_activity = true;
@@ -2711,6 +2767,7 @@ impl ChatWidget {
             rate_limit_switch_prompt: RateLimitSwitchPromptState::default(),
             rate_limit_poller: None,
             statusline_git_poller: None,
+            statusline_edited_files: HashSet::new(),
             adaptive_chunking: AdaptiveChunkingPolicy::default(),
             stream_controller: None,
             plan_stream_controller: None,
@@ -2939,6 +2996,7 @@ impl ChatWidget {
             status_line_branch_lookup_complete: false,
             external_editor_state: ExternalEditorState::Closed,
             statusline_git_poller: None,
+            statusline_edited_files: HashSet::new(),
             translation_config: crate::translation::TranslationConfig::load(),
         };
 
@@ -3050,6 +3108,7 @@ impl ChatWidget {
             rate_limit_switch_prompt: RateLimitSwitchPromptState::default(),
             rate_limit_poller: None,
             statusline_git_poller: None,
+            statusline_edited_files: HashSet::new(),
             adaptive_chunking: AdaptiveChunkingPolicy::default(),
             stream_controller: None,
             plan_stream_controller: None,
@@ -3521,6 +3580,9 @@ impl ChatWidget {
             SlashCommand::Statusline => {
                 self.open_status_line_setup();
             }
+            SlashCommand::ResetBase => {
+                self.reset_statusline_git_base();
+            }
             SlashCommand::Ps => {
                 self.add_ps_output();
             }
@@ -6053,12 +6115,21 @@ impl ChatWidget {
         config: crate::statusline::config::CxLineConfig,
     ) {
         self.bottom_pane.set_statusline_config(config);
+        // 轮询任务持有配置快照，重启以应用新的 segment 选项和轮询间隔
+        if self.statusline_git_poller.is_some() {
+            self.stop_statusline_git_poller();
+            self.start_statusline_git_poller();
+        }
     }
 
     pub(crate) fn set_statusline_git_preview(&mut self, preview: GitPreviewData) {
         self.bottom_pane.set_statusline_git_preview(preview);
     }
 
+    pub(crate) fn set_statusline_jj_preview(&mut self, preview: JjPreviewData) {
+        self.bottom_pane.set_statusline_jj_preview(preview);
+    }
+
     /// Get the current translation config.
     pub(crate) fn get_translation_config(&self) -> crate::translation::TranslationConfig {
         self.translation_config.clone()
@@ -7266,6 +7337,33 @@ fn extract_first_bold(s: &str) -> Option<String> {
     None
 }
 
+/// 按 interval 在阻塞线程中收集状态栏数据，结果变化时发送给 App
+async fn poll_statusline_preview<T>(
+    interval: Duration,
+    collect: impl Fn() -> T + Clone + Send + 'static,
+    app_event_tx: AppEventSender,
+    to_event: fn(T) -> AppEvent,
+) where
+    T: Clone + PartialEq + Send + 'static,
+{
+    let mut interval = tokio::time::interval(interval);
+    let mut last_preview: Option<T> = None;
+
+    loop {
+        interval.tick().await;
+        let collect = collect.clone();
+        let Ok(preview) = tokio::task::spawn_blocking(collect).await else {
+            continue;
+        };
+
+        if last_preview.as_ref() == Some(&preview) {
+            continue;
+        }
+        app_event_tx.send(to_event(preview.clone()));
+        last_preview = Some(preview);
+    }
+}
+
 async fn fetch_rate_limits(base_url: String, auth: CodexAuth) -> Vec<RateLimitSnapshot> {
     match BackendClient::from_auth(base_url, &auth) {
         Ok(client) => match client.get_rate_limits_many().await {
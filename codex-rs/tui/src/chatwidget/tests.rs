@@ -1148,6 +1148,7 @@ async fn make_chatwidget_manual(
         status_line_branch_lookup_complete: false,
         external_editor_state: ExternalEditorState::Closed,
         statusline_git_poller: None,
+        statusline_edited_files: HashSet::new(),
         translation_config: crate::translation::TranslationConfig::default(),
     };
     widget.set_model(&resolved_model);
@@ -31,9 +31,13 @@ use crate::statusline::IconSelector;
 use crate::statusline::NameInputDialog;
 use crate::statusline::SeparatorEditor;
 use crate::statusline::StatusLineContext;
+use crate::statusline::capability::TerminalCapabilities;
+use crate::statusline::color_picker::push_recent;
 use crate::statusline::config::CxLineConfig;
 use crate::statusline::segment::SegmentId;
 use crate::statusline::style::AnsiColor;
+use crate::statusline::style::ColorSupport;
+use crate::statusline::style::PowerlineGlyph;
 use crate::statusline::style::StyleMode;
 use crate::statusline::themes::THEME_NAMES;
 use crate::tui;
@@ -76,35 +80,49 @@ pub(crate) struct CxlineOverlay {
     status_message: Option<String>,
     // 对话框组件
     color_picker: ColorPicker,
+    /// 本次编辑中最近选择的颜色，供颜色选择器快速复用
+    recent_colors: Vec<AnsiColor>,
     icon_selector: IconSelector,
     separator_editor: SeparatorEditor,
     name_input_dialog: NameInputDialog,
+    /// 撤销历史（重置前的配置快照）
+    undo_stack: Vec<CxLineConfig>,
+    /// 打开时检测的终端颜色级别，预览按此降级
+    color_support: ColorSupport,
 }
 
+/// 撤销历史的最大长度
+const MAX_UNDO: usize = 20;
+
 impl CxlineOverlay {
     pub fn new(config: CxLineConfig) -> Self {
         let original_theme = config.theme.clone();
         let original_config = config.clone();
+        // 先按配置中的顺序，未列出的 segment 排在后面以便仍可编辑
+        let mut segment_order = config.order.clone();
+        segment_order.extend(
+            SegmentId::ALL
+                .into_iter()
+                .filter(|id| !config.order.contains(id)),
+        );
+        let color_picker = ColorPicker::default().with_rgb_layout(config.rgb_input_layout);
         Self {
             config,
             original_config,
             original_theme,
-            segment_order: vec![
-                SegmentId::Model,
-                SegmentId::Directory,
-                SegmentId::Git,
-                SegmentId::Context,
-                SegmentId::Usage,
-            ],
+            segment_order,
             selected_segment: 0,
             selected_panel: Panel::SegmentList,
             selected_field: FieldSelection::Enabled,
             is_done: false,
             status_message: None,
-            color_picker: ColorPicker::default(),
+            color_picker,
+            recent_colors: Vec::new(),
             icon_selector: IconSelector::default(),
             separator_editor: SeparatorEditor::default(),
             name_input_dialog: NameInputDialog::default(),
+            undo_stack: Vec::new(),
+            color_support: TerminalCapabilities::detect().color_support,
         }
     }
 
@@ -193,9 +211,11 @@ impl CxlineOverlay {
             KeyCode::Right | KeyCode::Char('l') => self.adjust_current(1),
             KeyCode::Char('p') | KeyCode::Char('P') => self.cycle_theme(),
             KeyCode::Char('r') | KeyCode::Char('R') => self.reset_theme(),
+            KeyCode::Char('u') | KeyCode::Char('U') => self.undo(),
             KeyCode::Char('w') | KeyCode::Char('W') => self.write_to_current_theme(),
             KeyCode::Char('s') | KeyCode::Char('S') => self.save_config(),
             KeyCode::Char('e') | KeyCode::Char('E') => self.open_separator_editor(),
+            KeyCode::Char('d') | KeyCode::Char('D') => self.downgrade_to_256(),
             KeyCode::Char('1') => self.switch_to_theme(0),
             KeyCode::Char('2') => self.switch_to_theme(1),
             KeyCode::Char('3') => self.switch_to_theme(2),
@@ -217,6 +237,7 @@ impl CxlineOverlay {
             }
             KeyCode::Enter => {
                 if let Some(color) = self.color_picker.get_selected_color() {
+                    push_recent(&mut self.recent_colors, color);
                     self.apply_color(color);
                 }
                 self.color_picker.close();
@@ -224,6 +245,9 @@ impl CxlineOverlay {
             KeyCode::Tab => {
                 self.color_picker.cycle_mode();
             }
+            KeyCode::Char('v') => {
+                self.color_picker.cycle_simulation();
+            }
             KeyCode::Up | KeyCode::Char('k') => {
                 self.color_picker.move_vertical(-1);
             }
@@ -284,9 +308,15 @@ impl CxlineOverlay {
                     self.icon_selector.toggle_style();
                 }
                 KeyCode::Up | KeyCode::Char('k') => {
-                    self.icon_selector.move_selection(-1);
+                    self.icon_selector.move_selection_vertical(-1);
                 }
                 KeyCode::Down | KeyCode::Char('j') => {
+                    self.icon_selector.move_selection_vertical(1);
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    self.icon_selector.move_selection(-1);
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
                     self.icon_selector.move_selection(1);
                 }
                 KeyCode::Char('c') | KeyCode::Char('C') => {
@@ -305,6 +335,10 @@ impl CxlineOverlay {
             }
             KeyCode::Enter => {
                 let separator = self.separator_editor.get_separator();
+                // Powerline 形状的预设同时决定 Powerline 模式的过渡字符
+                if let Some(glyph) = PowerlineGlyph::from_filled(&separator) {
+                    self.config.powerline_separator = glyph;
+                }
                 self.config.separator = separator;
                 self.status_message = Some("Separator updated".to_string());
                 self.separator_editor.close();
@@ -412,7 +446,7 @@ impl CxlineOverlay {
             StyleMode::Plain => {
                 segment_config.icon.plain = icon;
             }
-            StyleMode::NerdFont | StyleMode::Powerline => {
+            StyleMode::NerdFont | StyleMode::Powerline | StyleMode::PowerlineSeparated => {
                 segment_config.icon.nerd_font = icon;
             }
         }
@@ -445,6 +479,12 @@ impl CxlineOverlay {
             SegmentId::Git => "Git",
             SegmentId::Context => "Context Window",
             SegmentId::Usage => "Usage",
+            SegmentId::Budget => "Budget",
+            SegmentId::Jj => "Jujutsu",
+            SegmentId::Edits => "Edited Files",
+            SegmentId::Timezone => "Timezone",
+            SegmentId::Host => "Host",
+            SegmentId::Warning => "Warning",
         }
     }
 
@@ -501,6 +541,7 @@ impl CxlineOverlay {
         if self.selected_panel == Panel::SegmentList && self.selected_segment > 0 {
             self.segment_order
                 .swap(self.selected_segment, self.selected_segment - 1);
+            self.sync_config_order();
             self.selected_segment -= 1;
             self.status_message = Some("Segment moved up".to_string());
         }
@@ -512,16 +553,60 @@ impl CxlineOverlay {
         {
             self.segment_order
                 .swap(self.selected_segment, self.selected_segment + 1);
+            self.sync_config_order();
             self.selected_segment += 1;
             self.status_message = Some("Segment moved down".to_string());
         }
     }
 
+    /// 按列表中的新顺序重排 config.order，不把未列出的 segment 加进去
+    fn sync_config_order(&mut self) {
+        let order = &self.config.order;
+        self.config.order = self
+            .segment_order
+            .iter()
+            .copied()
+            .filter(|id| order.contains(id))
+            .collect();
+    }
+
+    /// 丢弃所有修改，恢复为进入时主题的原始状态（可撤销）
     fn reset_theme(&mut self) {
-        self.config.apply_theme(&self.original_theme);
+        self.push_undo();
+        self.config.theme = self.original_theme.clone();
+        self.config.reset_to_theme();
         self.status_message = Some(format!("Reset to: {}", self.original_theme));
     }
 
+    fn push_undo(&mut self) {
+        if self.undo_stack.len() == MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(self.config.clone());
+    }
+
+    /// 将 RGB 颜色改写为 256 色（可撤销），适用于不支持真彩色的终端
+    fn downgrade_to_256(&mut self) {
+        self.push_undo();
+        let changed = self.config.downgrade_to_256();
+        if changed == 0 {
+            self.undo_stack.pop();
+        }
+        self.status_message = Some(format!("Converted {changed} RGB colors to 256 colors"));
+    }
+
+    fn undo(&mut self) {
+        match self.undo_stack.pop() {
+            Some(config) => {
+                self.config = config;
+                self.status_message = Some("Undone".to_string());
+            }
+            None => {
+                self.status_message = Some("Nothing to undo".to_string());
+            }
+        }
+    }
+
     fn toggle_current(&mut self) {
         match self.selected_panel {
             Panel::SegmentList => {
@@ -568,17 +653,20 @@ impl CxlineOverlay {
             FieldSelection::IconColor => {
                 let current_color = self.config.get_segment_config(id).colors.icon;
                 self.color_picker
-                    .open(ColorTarget::IconColor, current_color);
+                    .open(ColorTarget::IconColor, current_color, &self.recent_colors);
             }
             FieldSelection::TextColor => {
                 let current_color = self.config.get_segment_config(id).colors.text;
                 self.color_picker
-                    .open(ColorTarget::TextColor, current_color);
+                    .open(ColorTarget::TextColor, current_color, &self.recent_colors);
             }
             FieldSelection::BackgroundColor => {
                 let current_color = self.config.get_segment_config(id).colors.background;
-                self.color_picker
-                    .open(ColorTarget::BackgroundColor, current_color);
+                self.color_picker.open(
+                    ColorTarget::BackgroundColor,
+                    current_color,
+                    &self.recent_colors,
+                );
             }
             FieldSelection::TextStyle => {
                 let segment_config = self.config.get_segment_config_mut(id);
@@ -711,39 +799,40 @@ impl CxlineOverlay {
 
     fn render_preview(&self, area: Rect, buf: &mut Buffer) {
         use crate::statusline::renderer::StatusLineRenderer;
-        use crate::statusline::segment::Segment;
-        use crate::statusline::segments::*;
+        use crate::statusline::segments::collect_segment;
         use codex_protocol::openai_models::ReasoningEffort;
 
         let ctx =
             StatusLineContext::new("gpt-5.2-codex", std::path::Path::new("/home/user/Cxline"))
+                .with_cwd_check(false)
                 .with_reasoning_effort(Some(ReasoningEffort::Medium))
                 .with_context(Some(50000), Some(128000))
                 .with_rate_limit(Some(25.0), Some(15.0), Some("1-28-14".to_string()))
-                .with_git_preview("main", "✓", 0, 0);
+                .with_token_budget(Some(12_500), Some(100_000))
+                .with_session_edited_files(Some(3))
+                .with_git_preview("main", "✓", 0, 0)
+                .with_jj_preview("kxqzmtow", "✓", "");
 
-        // 按 segment_order 顺序构建预览
+        // 按配置的 order 构建预览
         let mut renderer = StatusLineRenderer::new(&self.config);
-        for &segment_id in &self.segment_order {
+        for &segment_id in &self.config.order {
             let segment_config = self.config.get_segment_config(segment_id);
             if !segment_config.enabled {
                 continue;
             }
 
-            let data = match segment_id {
-                SegmentId::Model => ModelSegment.collect(&ctx),
-                SegmentId::Directory => DirectorySegment.collect(&ctx),
-                SegmentId::Git => GitSegment.collect(&ctx),
-                SegmentId::Context => ContextSegment.collect(&ctx),
-                SegmentId::Usage => UsageSegment.collect(&ctx),
-            };
+            let data = collect_segment(&self.config, segment_config, &ctx);
 
-            if let Some(data) = data {
+            if let Some(data) = data
+                && !segment_config.should_hide(&data)
+            {
                 renderer.add_segment(segment_id, data);
             }
         }
 
-        let line = renderer.render_line();
+        let line = renderer
+            .with_color_support(self.color_support)
+            .render_line();
 
         let block = Block::default().borders(Borders::ALL).title("Preview");
         let inner = block.inner(area);
@@ -954,7 +1043,9 @@ impl CxlineOverlay {
             ("[1-9]", "Theme"),
             ("[P]", "Cycle Theme"),
             ("[R]", "Reset Theme"),
+            ("[U]", "Undo"),
             ("[E]", "Edit Separator"),
+            ("[D]", "To 256 Colors"),
             ("[W]", "Write Theme"),
             ("[Ctrl+S]", "Save Theme"),
             ("[S]", "Save Config"),
@@ -37,6 +37,7 @@ pub enum SlashCommand {
     Status,
     DebugConfig,
     Statusline,
+    ResetBase,
     Mcp,
     Apps,
     Logout,
@@ -75,6 +76,7 @@ impl SlashCommand {
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::DebugConfig => "show config layers and requirement sources for debugging",
             SlashCommand::Statusline => "configure which items appear in the status line",
+            SlashCommand::ResetBase => "record HEAD as the status line git base",
             SlashCommand::Ps => "list background terminals",
             SlashCommand::Clean => "stop all background terminals",
             SlashCommand::MemoryDrop => "DO NOT USE",
@@ -140,6 +142,7 @@ impl SlashCommand {
             | SlashCommand::Skills
             | SlashCommand::Status
             | SlashCommand::DebugConfig
+            | SlashCommand::ResetBase
             | SlashCommand::Ps
             | SlashCommand::Clean
             | SlashCommand::Mcp
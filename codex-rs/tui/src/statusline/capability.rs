@@ -0,0 +1,142 @@
+// 终端能力检测
+// 用于首次运行时按终端能力挑选默认主题，以及渲染时的颜色降级
+
+use super::style::ColorSupport;
+
+/// 终端能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TerminalCapabilities {
+    /// 是否可显示 Nerd Font 图标
+    pub nerd_font: bool,
+    /// 支持的颜色级别，渲染时据此降级超出的颜色
+    pub color_support: ColorSupport,
+}
+
+impl TerminalCapabilities {
+    /// 检测当前终端能力
+    pub fn detect() -> Self {
+        let lookup = |key: &str| std::env::var(key).ok();
+        let color_support = color_support_override(lookup).unwrap_or_else(|| {
+            match supports_color::on_cached(supports_color::Stream::Stdout) {
+                Some(level) if level.has_16m => ColorSupport::TrueColor,
+                Some(level) if level.has_256 => ColorSupport::Ansi256,
+                _ => ColorSupport::Ansi16,
+            }
+        });
+
+        Self {
+            nerd_font: detect_nerd_font(lookup),
+            color_support,
+        }
+    }
+
+    /// 首次运行时使用的主题
+    /// Nerd Font + 真彩色 → Powerline；仅 Nerd Font → cometix；否则 minimal
+    pub fn first_run_theme(&self) -> &'static str {
+        match (
+            self.nerd_font,
+            self.color_support == ColorSupport::TrueColor,
+        ) {
+            (true, true) => "powerline-dark",
+            (true, false) => "cometix",
+            (false, _) => "minimal",
+        }
+    }
+}
+
+/// 读取 `CXLINE_COLOR_DEPTH`（truecolor/24bit/256/16）手动指定的颜色级别，未设置或无法识别时返回 None
+pub fn color_support_override(lookup: impl Fn(&str) -> Option<String>) -> Option<ColorSupport> {
+    match lookup("CXLINE_COLOR_DEPTH")?
+        .trim()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "truecolor" | "24bit" => Some(ColorSupport::TrueColor),
+        "256" => Some(ColorSupport::Ansi256),
+        "16" => Some(ColorSupport::Ansi16),
+        _ => None,
+    }
+}
+
+/// 判断终端是否可显示 Nerd Font 图标
+/// 字体无法直接探测：优先读取 `CXLINE_NERD_FONT`（1/true/yes 或 0/false/no），
+/// 否则按内置 Nerd Font 符号的终端推断
+pub fn detect_nerd_font(lookup: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(value) = lookup("CXLINE_NERD_FONT") {
+        return matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes"
+        );
+    }
+
+    matches!(
+        lookup("TERM_PROGRAM").as_deref(),
+        Some("WezTerm" | "ghostty")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_detect_nerd_font() {
+        assert!(detect_nerd_font(env(&[("CXLINE_NERD_FONT", "1")])));
+        assert!(!detect_nerd_font(env(&[
+            ("CXLINE_NERD_FONT", "false"),
+            ("TERM_PROGRAM", "WezTerm"),
+        ])));
+        assert!(detect_nerd_font(env(&[("TERM_PROGRAM", "ghostty")])));
+        assert!(!detect_nerd_font(env(&[(
+            "TERM_PROGRAM",
+            "Apple_Terminal"
+        )])));
+        assert!(!detect_nerd_font(env(&[])));
+    }
+
+    #[test]
+    fn test_color_support_override() {
+        assert_eq!(color_support_override(env(&[])), None);
+        assert_eq!(
+            color_support_override(env(&[("CXLINE_COLOR_DEPTH", "16")])),
+            Some(ColorSupport::Ansi16)
+        );
+        assert_eq!(
+            color_support_override(env(&[("CXLINE_COLOR_DEPTH", "256")])),
+            Some(ColorSupport::Ansi256)
+        );
+        assert_eq!(
+            color_support_override(env(&[("CXLINE_COLOR_DEPTH", " TrueColor ")])),
+            Some(ColorSupport::TrueColor)
+        );
+        assert_eq!(
+            color_support_override(env(&[("CXLINE_COLOR_DEPTH", "lots")])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_first_run_theme() {
+        let caps = |nerd_font, truecolor| TerminalCapabilities {
+            nerd_font,
+            color_support: if truecolor {
+                ColorSupport::TrueColor
+            } else {
+                ColorSupport::Ansi256
+            },
+        };
+        assert_eq!(caps(true, true).first_run_theme(), "powerline-dark");
+        assert_eq!(caps(true, false).first_run_theme(), "cometix");
+        assert_eq!(caps(false, true).first_run_theme(), "minimal");
+        assert_eq!(caps(false, false).first_run_theme(), "minimal");
+    }
+}
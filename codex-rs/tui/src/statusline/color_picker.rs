@@ -11,17 +11,23 @@ use ratatui::widgets::Borders;
 use ratatui::widgets::Clear;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::Widget;
+use serde::Deserialize;
+use serde::Serialize;
 
 use super::style::AnsiColor;
 
+/// 最近使用颜色的最大数量
+pub const MAX_RECENT_COLORS: usize = 8;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ColorPickerMode {
     Basic16,
     Extended256,
     RgbInput,
+    HslInput,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RgbField {
     Red,
     Green,
@@ -29,6 +35,32 @@ pub enum RgbField {
     Hex,
 }
 
+/// RGB 模式下输入框的顺序和可见性
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RgbInputLayout {
+    /// Hex 输入框排在 R/G/B 之前
+    pub hex_first: bool,
+    /// 只显示 Hex 输入框
+    pub hex_only: bool,
+}
+
+impl RgbInputLayout {
+    /// 按导航顺序列出可见的输入框
+    pub fn fields(&self) -> Vec<RgbField> {
+        const RGB: [RgbField; 3] = [RgbField::Red, RgbField::Green, RgbField::Blue];
+        if self.hex_only {
+            vec![RgbField::Hex]
+        } else if self.hex_first {
+            std::iter::once(RgbField::Hex).chain(RGB).collect()
+        } else {
+            RGB.into_iter()
+                .chain(std::iter::once(RgbField::Hex))
+                .collect()
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RgbInput {
     pub r: String,
@@ -50,6 +82,76 @@ impl Default for RgbInput {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HslField {
+    Hue,
+    Saturation,
+    Lightness,
+}
+
+impl HslField {
+    /// 输入框允许的最大值
+    fn max(self) -> u16 {
+        match self {
+            Self::Hue => 360,
+            Self::Saturation | Self::Lightness => 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HslInput {
+    pub h: String,
+    pub s: String,
+    pub l: String,
+    pub editing_field: HslField,
+}
+
+impl Default for HslInput {
+    fn default() -> Self {
+        Self {
+            h: String::new(),
+            s: String::new(),
+            l: String::new(),
+            editing_field: HslField::Hue,
+        }
+    }
+}
+
+/// 色觉缺陷类型，用于预览颜色在色弱用户眼中的效果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CvdKind {
+    /// 绿色弱/绿色盲
+    Deuteranopia,
+    /// 红色弱/红色盲
+    Protanopia,
+}
+
+impl CvdKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Deuteranopia => "Deuteranopia",
+            Self::Protanopia => "Protanopia",
+        }
+    }
+
+    /// Machado et al. (2009) 严重程度 1.0 的模拟矩阵，作用于线性 RGB
+    fn matrix(self) -> [[f64; 3]; 3] {
+        match self {
+            Self::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            Self::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ColorTarget {
     IconColor,
@@ -64,6 +166,12 @@ pub struct ColorPicker {
     pub selected_basic: usize,
     pub selected_extended: usize,
     pub rgb_input: RgbInput,
+    pub rgb_layout: RgbInputLayout,
+    pub hsl_input: HslInput,
+    /// 最近使用的颜色（由调用方持有，打开时传入），在 Basic/Extended 模式下按 1-8 选择
+    pub recent: Vec<AnsiColor>,
+    /// 在预览中额外显示色觉缺陷模拟效果
+    pub simulate: Option<CvdKind>,
     pub current_color: Option<AnsiColor>,
     pub target_field: ColorTarget,
     pub cached_basic_cols: usize,
@@ -78,6 +186,10 @@ impl Default for ColorPicker {
             selected_basic: 0,
             selected_extended: 0,
             rgb_input: RgbInput::default(),
+            rgb_layout: RgbInputLayout::default(),
+            hsl_input: HslInput::default(),
+            recent: Vec::new(),
+            simulate: None,
             current_color: None,
             target_field: ColorTarget::IconColor,
             cached_basic_cols: 8,
@@ -87,14 +199,54 @@ impl Default for ColorPicker {
 }
 
 impl ColorPicker {
-    pub fn open(&mut self, target: ColorTarget, current: Option<AnsiColor>) {
+    pub fn open(&mut self, target: ColorTarget, current: Option<AnsiColor>, recent: &[AnsiColor]) {
         self.is_open = true;
         self.target_field = target;
         self.mode = ColorPickerMode::Basic16;
         self.selected_basic = 0;
         self.selected_extended = 0;
-        self.rgb_input = RgbInput::default();
+        self.rgb_input = RgbInput {
+            editing_field: self.first_rgb_field(),
+            ..RgbInput::default()
+        };
+        self.hsl_input = HslInput::default();
+        self.recent = recent.to_vec();
         self.current_color = current;
+
+        // 光标定位到当前颜色；256 色中的前 16 个等同于基础色，其余使用扩展色网格
+        match current {
+            Some(AnsiColor::Color16 { c16: index } | AnsiColor::Color256 { c256: index })
+                if index < 16 =>
+            {
+                self.selected_basic = usize::from(index);
+            }
+            Some(AnsiColor::Color16 { c16: index } | AnsiColor::Color256 { c256: index }) => {
+                self.mode = ColorPickerMode::Extended256;
+                self.selected_extended = usize::from(index);
+            }
+            Some(AnsiColor::Rgb { r, g, b }) => {
+                self.mode = ColorPickerMode::RgbInput;
+                self.rgb_input.r = r.to_string();
+                self.rgb_input.g = g.to_string();
+                self.rgb_input.b = b.to_string();
+                self.rgb_input.hex = format!("{r:02X}{g:02X}{b:02X}");
+            }
+            None => {}
+        }
+    }
+
+    pub fn with_rgb_layout(mut self, layout: RgbInputLayout) -> Self {
+        self.rgb_layout = layout;
+        self.rgb_input.editing_field = self.first_rgb_field();
+        self
+    }
+
+    fn first_rgb_field(&self) -> RgbField {
+        self.rgb_layout
+            .fields()
+            .first()
+            .copied()
+            .unwrap_or(RgbField::Red)
     }
 
     pub fn close(&mut self) {
@@ -105,7 +257,17 @@ impl ColorPicker {
         self.mode = match self.mode {
             ColorPickerMode::Basic16 => ColorPickerMode::Extended256,
             ColorPickerMode::Extended256 => ColorPickerMode::RgbInput,
-            ColorPickerMode::RgbInput => ColorPickerMode::Basic16,
+            ColorPickerMode::RgbInput => ColorPickerMode::HslInput,
+            ColorPickerMode::HslInput => ColorPickerMode::Basic16,
+        };
+    }
+
+    /// 切换色觉缺陷模拟：关闭 → Deuteranopia → Protanopia → 关闭
+    pub fn cycle_simulation(&mut self) {
+        self.simulate = match self.simulate {
+            None => Some(CvdKind::Deuteranopia),
+            Some(CvdKind::Deuteranopia) => Some(CvdKind::Protanopia),
+            Some(CvdKind::Protanopia) => None,
         };
     }
 
@@ -136,16 +298,33 @@ impl ColorPicker {
                 self.current_color = Some(AnsiColor::c256(self.selected_extended as u8));
             }
             ColorPickerMode::RgbInput => {
-                self.rgb_input.editing_field = match (&self.rgb_input.editing_field, delta > 0) {
-                    (RgbField::Red, true) => RgbField::Green,
-                    (RgbField::Green, true) => RgbField::Blue,
-                    (RgbField::Blue, true) => RgbField::Hex,
-                    (RgbField::Hex, true) => RgbField::Red,
-                    (RgbField::Red, false) => RgbField::Hex,
-                    (RgbField::Green, false) => RgbField::Red,
-                    (RgbField::Blue, false) => RgbField::Green,
-                    (RgbField::Hex, false) => RgbField::Blue,
+                // 按配置的顺序循环，跳过隐藏的输入框
+                let fields = self.rgb_layout.fields();
+                let len = fields.len();
+                let current = fields
+                    .iter()
+                    .position(|field| *field == self.rgb_input.editing_field)
+                    .unwrap_or(0);
+                let next = if delta > 0 {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                self.rgb_input.editing_field = fields[next];
+            }
+            ColorPickerMode::HslInput => {
+                const FIELDS: [HslField; 3] =
+                    [HslField::Hue, HslField::Saturation, HslField::Lightness];
+                let current = FIELDS
+                    .iter()
+                    .position(|field| *field == self.hsl_input.editing_field)
+                    .unwrap_or(0);
+                let next = if delta > 0 {
+                    (current + 1) % FIELDS.len()
+                } else {
+                    (current + FIELDS.len() - 1) % FIELDS.len()
                 };
+                self.hsl_input.editing_field = FIELDS[next];
             }
         }
     }
@@ -196,31 +375,37 @@ impl ColorPicker {
                 self.selected_extended = new_selection;
                 self.current_color = Some(AnsiColor::c256(self.selected_extended as u8));
             }
-            ColorPickerMode::RgbInput => {}
+            ColorPickerMode::RgbInput | ColorPickerMode::HslInput => {}
         }
     }
 
     pub fn input_char(&mut self, c: char) {
+        if matches!(
+            self.mode,
+            ColorPickerMode::Basic16 | ColorPickerMode::Extended256
+        ) {
+            self.select_recent(c);
+            return;
+        }
+        if self.mode == ColorPickerMode::HslInput {
+            let field = self.hsl_input.editing_field;
+            let value = match field {
+                HslField::Hue => &mut self.hsl_input.h,
+                HslField::Saturation => &mut self.hsl_input.s,
+                HslField::Lightness => &mut self.hsl_input.l,
+            };
+            push_bounded_digit(value, c, field.max());
+            self.update_hsl_color();
+            return;
+        }
         if self.mode != ColorPickerMode::RgbInput {
             return;
         }
 
         match self.rgb_input.editing_field {
-            RgbField::Red => {
-                if self.rgb_input.r.len() < 3 && c.is_ascii_digit() {
-                    self.rgb_input.r.push(c);
-                }
-            }
-            RgbField::Green => {
-                if self.rgb_input.g.len() < 3 && c.is_ascii_digit() {
-                    self.rgb_input.g.push(c);
-                }
-            }
-            RgbField::Blue => {
-                if self.rgb_input.b.len() < 3 && c.is_ascii_digit() {
-                    self.rgb_input.b.push(c);
-                }
-            }
+            RgbField::Red => push_bounded_digit(&mut self.rgb_input.r, c, 255),
+            RgbField::Green => push_bounded_digit(&mut self.rgb_input.g, c, 255),
+            RgbField::Blue => push_bounded_digit(&mut self.rgb_input.b, c, 255),
             RgbField::Hex => {
                 if self.rgb_input.hex.len() < 6 && c.is_ascii_hexdigit() {
                     self.rgb_input.hex.push(c.to_ascii_uppercase());
@@ -231,6 +416,15 @@ impl ColorPicker {
     }
 
     pub fn backspace(&mut self) {
+        if self.mode == ColorPickerMode::HslInput {
+            match self.hsl_input.editing_field {
+                HslField::Hue => self.hsl_input.h.pop(),
+                HslField::Saturation => self.hsl_input.s.pop(),
+                HslField::Lightness => self.hsl_input.l.pop(),
+            };
+            self.update_hsl_color();
+            return;
+        }
         if self.mode != ColorPickerMode::RgbInput {
             return;
         }
@@ -252,25 +446,60 @@ impl ColorPicker {
         self.update_rgb_color();
     }
 
+    /// 优先使用正在编辑的一组输入框，其次是另一组可见的输入框
+    /// 正在编辑的一组完整有效时回填另一组，使 Hex 与 R/G/B 保持一致；
+    /// 未输完的 Hex（1-5 位）不会覆盖 R/G/B
     fn update_rgb_color(&mut self) {
-        if !self.rgb_input.hex.is_empty()
-            && self.rgb_input.hex.len() == 6
-            && let (Ok(r), Ok(g), Ok(b)) = (
-                u8::from_str_radix(&self.rgb_input.hex[0..2], 16),
-                u8::from_str_radix(&self.rgb_input.hex[2..4], 16),
-                u8::from_str_radix(&self.rgb_input.hex[4..6], 16),
-            )
-        {
-            self.current_color = Some(AnsiColor::rgb(r, g, b));
-            return;
-        }
-
-        if let (Ok(r), Ok(g), Ok(b)) = (
+        let hex = AnsiColor::from_hex(&self.rgb_input.hex);
+        let rgb = if self.rgb_layout.hex_only {
+            None
+        } else if let (Ok(r), Ok(g), Ok(b)) = (
             self.rgb_input.r.parse::<u8>(),
             self.rgb_input.g.parse::<u8>(),
             self.rgb_input.b.parse::<u8>(),
         ) {
-            self.current_color = Some(AnsiColor::rgb(r, g, b));
+            Some(AnsiColor::rgb(r, g, b))
+        } else {
+            None
+        };
+
+        let editing_hex = self.rgb_input.editing_field == RgbField::Hex;
+        if editing_hex && let Some(AnsiColor::Rgb { r, g, b }) = hex {
+            self.rgb_input.r = r.to_string();
+            self.rgb_input.g = g.to_string();
+            self.rgb_input.b = b.to_string();
+        } else if !editing_hex && let Some(AnsiColor::Rgb { r, g, b }) = rgb {
+            self.rgb_input.hex = format!("{r:02X}{g:02X}{b:02X}");
+        }
+
+        let color = if editing_hex {
+            hex.or(rgb)
+        } else {
+            rgb.or(hex)
+        };
+        if color.is_some() {
+            self.current_color = color;
+        }
+    }
+
+    /// 按数字键 1-8 选择最近使用的颜色
+    fn select_recent(&mut self, c: char) {
+        let Some(index) = c.to_digit(10).and_then(|digit| digit.checked_sub(1)) else {
+            return;
+        };
+        if let Some(color) = self.recent.get(index as usize) {
+            self.current_color = Some(*color);
+        }
+    }
+
+    /// H/S/L 三个输入框都有效时更新颜色
+    fn update_hsl_color(&mut self) {
+        if let (Ok(h), Ok(s), Ok(l)) = (
+            self.hsl_input.h.parse::<u16>(),
+            self.hsl_input.s.parse::<u8>(),
+            self.hsl_input.l.parse::<u8>(),
+        ) {
+            self.current_color = Some(hsl_to_rgb(h, s, l));
         }
     }
 
@@ -290,7 +519,15 @@ impl ColorPicker {
         let inner = popup_block.inner(popup_area);
         popup_block.render(popup_area, buf);
 
-        let [mode_area, content_area, preview_area, help_area] = Layout::vertical([
+        let recent_height = if self.recent.is_empty() { 0 } else { 3 };
+        let [
+            recent_area,
+            mode_area,
+            content_area,
+            preview_area,
+            help_area,
+        ] = Layout::vertical([
+            Constraint::Length(recent_height),
             Constraint::Length(3),
             Constraint::Min(8),
             Constraint::Length(3),
@@ -298,11 +535,16 @@ impl ColorPicker {
         ])
         .areas(inner);
 
+        if !self.recent.is_empty() {
+            self.render_recent(recent_area, buf);
+        }
+
         // Mode selector
         let mode_text = match self.mode {
-            ColorPickerMode::Basic16 => "[•] Basic (16)  [ ] Extended (256)  [ ] RGB",
-            ColorPickerMode::Extended256 => "[ ] Basic (16)  [•] Extended (256)  [ ] RGB",
-            ColorPickerMode::RgbInput => "[ ] Basic (16)  [ ] Extended (256)  [•] RGB",
+            ColorPickerMode::Basic16 => "[•] Basic (16)  [ ] Extended (256)  [ ] RGB  [ ] HSL",
+            ColorPickerMode::Extended256 => "[ ] Basic (16)  [•] Extended (256)  [ ] RGB  [ ] HSL",
+            ColorPickerMode::RgbInput => "[ ] Basic (16)  [ ] Extended (256)  [•] RGB  [ ] HSL",
+            ColorPickerMode::HslInput => "[ ] Basic (16)  [ ] Extended (256)  [ ] RGB  [•] HSL",
         };
         Paragraph::new(mode_text)
             .block(Block::default().borders(Borders::ALL).title("Mode"))
@@ -313,17 +555,50 @@ impl ColorPicker {
             ColorPickerMode::Basic16 => self.render_basic_colors(content_area, buf),
             ColorPickerMode::Extended256 => self.render_extended_colors(content_area, buf),
             ColorPickerMode::RgbInput => self.render_rgb_input(content_area, buf),
+            ColorPickerMode::HslInput => self.render_hsl_input(content_area, buf),
         }
 
         // Preview
         self.render_preview(preview_area, buf);
 
         // Help
-        Paragraph::new("[Enter] Select  [Esc] Cancel  [Tab] Cycle Mode")
+        Paragraph::new("[Enter] Select  [Esc] Cancel  [Tab] Cycle Mode  [v] CVD Preview")
             .block(Block::default().borders(Borders::ALL))
             .render(help_area, buf);
     }
 
+    fn render_recent(&self, area: Rect, buf: &mut Buffer) {
+        let title = if matches!(
+            self.mode,
+            ColorPickerMode::Basic16 | ColorPickerMode::Extended256
+        ) {
+            "Recent [1-8]"
+        } else {
+            "Recent"
+        };
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        for (i, color) in self.recent.iter().enumerate() {
+            let x = inner.x + (i * 6) as u16;
+            if x + 5 > inner.x + inner.width {
+                break;
+            }
+            let text = if self.current_color == Some(*color) {
+                format!("[{}██]", i + 1)
+            } else {
+                format!(" {}██ ", i + 1)
+            };
+            buf.set_string(
+                x,
+                inner.y,
+                text,
+                Style::default().fg(color.to_ratatui_color()),
+            );
+        }
+    }
+
     fn render_basic_colors(&mut self, area: Rect, buf: &mut Buffer) {
         let block = Block::default()
             .borders(Borders::ALL)
@@ -473,8 +748,6 @@ impl ColorPicker {
             ),
         );
 
-        buf.set_string(inner.x, inner.y, &rgb_text, Style::default());
-
         let hex_text = format!(
             "Hex: #{}",
             format_field(
@@ -484,8 +757,53 @@ impl ColorPicker {
             ),
         );
 
-        if inner.height > 2 {
-            buf.set_string(inner.x, inner.y + 2, &hex_text, Style::default());
+        let lines = if self.rgb_layout.hex_only {
+            vec![hex_text]
+        } else if self.rgb_layout.hex_first {
+            vec![hex_text, rgb_text]
+        } else {
+            vec![rgb_text, hex_text]
+        };
+        for (i, line) in lines.iter().enumerate() {
+            let offset = (i * 2) as u16;
+            if offset < inner.height {
+                buf.set_string(inner.x, inner.y + offset, line, Style::default());
+            }
+        }
+    }
+
+    fn render_hsl_input(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default().borders(Borders::ALL).title("HSL Input");
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let format_field = |field: HslField, value: &str| -> String {
+            if field == self.hsl_input.editing_field {
+                format!("> {value} <")
+            } else {
+                value.to_string()
+            }
+        };
+
+        let lines = [
+            format!(
+                "H[{}]  S[{}]  L[{}]",
+                format_field(HslField::Hue, &self.hsl_input.h),
+                format_field(HslField::Saturation, &self.hsl_input.s),
+                format_field(HslField::Lightness, &self.hsl_input.l),
+            ),
+            "H 0-360  S 0-100  L 0-100".to_string(),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            let offset = (i * 2) as u16;
+            if offset < inner.height {
+                let style = if i == 0 {
+                    Style::default()
+                } else {
+                    Style::default().fg(Color::Gray)
+                };
+                buf.set_string(inner.x, inner.y + offset, line, style);
+            }
         }
     }
 
@@ -507,6 +825,22 @@ impl ColorPicker {
             .map(|c| c.to_ratatui_color())
             .unwrap_or(Color::White);
 
+        // 开启模拟时在右侧并排显示模拟后的色块
+        let area = match (self.simulate, self.current_color) {
+            (Some(kind), Some(current)) => {
+                let [normal, simulated] =
+                    Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                        .areas(area);
+                let simulated_color = simulate_cvd(current, Some(kind));
+                Paragraph::new("████")
+                    .style(Style::default().fg(simulated_color.to_ratatui_color()))
+                    .block(Block::default().borders(Borders::ALL).title(kind.label()))
+                    .render(simulated, buf);
+                normal
+            }
+            _ => area,
+        };
+
         Paragraph::new(preview_text)
             .style(Style::default().fg(color))
             .block(Block::default().borders(Borders::ALL).title("Preview"))
@@ -558,6 +892,78 @@ pub fn ansi16_to_color(ansi: u8) -> Color {
     }
 }
 
+/// 将选中的颜色放到最近使用列表的最前面；已存在时移动而不重复，超出上限时丢弃最旧的
+pub fn push_recent(recent: &mut Vec<AnsiColor>, color: AnsiColor) {
+    recent.retain(|existing| *existing != color);
+    recent.insert(0, color);
+    recent.truncate(MAX_RECENT_COLORS);
+}
+
+/// 模拟颜色在指定色觉缺陷下的效果，kind 为 None 时原样返回
+/// 16/256 色先换算为 RGB，结果总是 RGB
+pub fn simulate_cvd(color: AnsiColor, kind: Option<CvdKind>) -> AnsiColor {
+    let Some(kind) = kind else {
+        return color;
+    };
+
+    let to_linear = |v: u8| {
+        let v = f64::from(v) / 255.0;
+        if v <= 0.040_45 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let to_srgb = |v: f64| {
+        let v = v.clamp(0.0, 1.0);
+        let v = if v <= 0.003_130_8 {
+            v * 12.92
+        } else {
+            1.055 * v.powf(1.0 / 2.4) - 0.055
+        };
+        (v * 255.0).round() as u8
+    };
+
+    let (r, g, b) = color.to_rgb();
+    let input = [to_linear(r), to_linear(g), to_linear(b)];
+    let [r, g, b] = kind
+        .matrix()
+        .map(|row| to_srgb(row[0] * input[0] + row[1] * input[1] + row[2] * input[2]));
+    AnsiColor::rgb(r, g, b)
+}
+
+/// 向数字输入框追加一位，超过 max 的输入直接拒绝
+fn push_bounded_digit(field: &mut String, c: char, max: u16) {
+    if !c.is_ascii_digit() || field.len() >= 3 {
+        return;
+    }
+    let candidate = format!("{field}{c}");
+    if candidate.parse::<u16>().is_ok_and(|value| value <= max) {
+        *field = candidate;
+    }
+}
+
+/// HSL 转 RGB：h 为 0-360 度，s 和 l 为 0-100 百分比
+pub fn hsl_to_rgb(h: u16, s: u8, l: u8) -> AnsiColor {
+    let h = f64::from(h % 360) / 60.0;
+    let s = f64::from(s.min(100)) / 100.0;
+    let l = f64::from(l.min(100)) / 100.0;
+
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = l - chroma / 2.0;
+    let channel = |value: f64| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    AnsiColor::rgb(channel(r), channel(g), channel(b))
+}
+
 pub fn get_color_name(ansi: u8) -> &'static str {
     match ansi {
         0 => "Black",
@@ -579,3 +985,298 @@ pub fn get_color_name(ansi: u8) -> &'static str {
         _ => "Unknown",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statusline::style::ansi16;
+    use pretty_assertions::assert_eq;
+
+    fn rgb_picker(layout: RgbInputLayout) -> ColorPicker {
+        let mut picker = ColorPicker::default().with_rgb_layout(layout);
+        picker.open(ColorTarget::TextColor, None, &[]);
+        picker.mode = ColorPickerMode::RgbInput;
+        picker
+    }
+
+    #[test]
+    fn test_default_navigation_order() {
+        let mut picker = rgb_picker(RgbInputLayout::default());
+        let mut visited = vec![picker.rgb_input.editing_field];
+        for _ in 0..4 {
+            picker.move_horizontal(1);
+            visited.push(picker.rgb_input.editing_field);
+        }
+        assert_eq!(
+            visited,
+            vec![
+                RgbField::Red,
+                RgbField::Green,
+                RgbField::Blue,
+                RgbField::Hex,
+                RgbField::Red
+            ]
+        );
+        picker.move_horizontal(-1);
+        assert_eq!(picker.rgb_input.editing_field, RgbField::Hex);
+    }
+
+    #[test]
+    fn test_hex_first_navigation() {
+        let mut picker = rgb_picker(RgbInputLayout {
+            hex_first: true,
+            hex_only: false,
+        });
+        assert_eq!(picker.rgb_input.editing_field, RgbField::Hex);
+        picker.move_horizontal(1);
+        assert_eq!(picker.rgb_input.editing_field, RgbField::Red);
+        picker.move_horizontal(-1);
+        picker.move_horizontal(-1);
+        assert_eq!(picker.rgb_input.editing_field, RgbField::Blue);
+    }
+
+    #[test]
+    fn test_hex_only_skips_rgb_fields() {
+        let mut picker = rgb_picker(RgbInputLayout {
+            hex_first: false,
+            hex_only: true,
+        });
+        assert_eq!(picker.rgb_input.editing_field, RgbField::Hex);
+        picker.move_horizontal(1);
+        assert_eq!(picker.rgb_input.editing_field, RgbField::Hex);
+        picker.move_horizontal(-1);
+        assert_eq!(picker.rgb_input.editing_field, RgbField::Hex);
+
+        for c in "ff8700".chars() {
+            picker.input_char(c);
+        }
+        assert_eq!(picker.rgb_input.hex, "FF8700");
+        assert_eq!(
+            picker.get_selected_color(),
+            Some(AnsiColor::rgb(255, 135, 0))
+        );
+    }
+
+    #[test]
+    fn test_editing_field_takes_priority() {
+        let mut picker = rgb_picker(RgbInputLayout::default());
+        picker.rgb_input.hex = "0000FF".to_string();
+        for c in "255".chars() {
+            picker.input_char(c);
+        }
+        picker.move_horizontal(1);
+        picker.input_char('0');
+        picker.move_horizontal(1);
+        picker.input_char('0');
+        assert_eq!(picker.get_selected_color(), Some(AnsiColor::rgb(255, 0, 0)));
+
+        // 输完 R/G/B 后 Hex 已同步，切到 Hex 编辑后 Hex 优先
+        picker.move_horizontal(1);
+        assert_eq!(picker.rgb_input.hex, "FF0000");
+        picker.backspace();
+        picker.input_char('F');
+        assert_eq!(
+            picker.get_selected_color(),
+            Some(AnsiColor::rgb(255, 0, 15))
+        );
+    }
+
+    #[test]
+    fn test_push_recent_dedupes_and_caps() {
+        let mut recent = Vec::new();
+        for i in 0..10 {
+            push_recent(&mut recent, AnsiColor::c256(i));
+        }
+        assert_eq!(recent.len(), MAX_RECENT_COLORS);
+        assert_eq!(recent.first(), Some(&AnsiColor::c256(9)));
+        assert_eq!(recent.last(), Some(&AnsiColor::c256(2)));
+
+        // 重复选择移到最前，不新增
+        push_recent(&mut recent, AnsiColor::c256(5));
+        assert_eq!(recent.len(), MAX_RECENT_COLORS);
+        assert_eq!(recent.first(), Some(&AnsiColor::c256(5)));
+        assert_eq!(
+            recent
+                .iter()
+                .filter(|color| **color == AnsiColor::c256(5))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_open_positions_cursor_on_current_color() {
+        let mut picker = ColorPicker::default();
+        picker.open(ColorTarget::TextColor, Some(AnsiColor::c16(9)), &[]);
+        assert_eq!(picker.mode, ColorPickerMode::Basic16);
+        assert_eq!(picker.selected_basic, 9);
+
+        picker.open(ColorTarget::TextColor, Some(AnsiColor::c256(208)), &[]);
+        assert_eq!(picker.mode, ColorPickerMode::Extended256);
+        assert_eq!(picker.selected_extended, 208);
+
+        picker.open(ColorTarget::TextColor, Some(AnsiColor::c256(3)), &[]);
+        assert_eq!(picker.mode, ColorPickerMode::Basic16);
+        assert_eq!(picker.selected_basic, 3);
+
+        picker.open(
+            ColorTarget::TextColor,
+            Some(AnsiColor::rgb(255, 135, 0)),
+            &[],
+        );
+        assert_eq!(picker.mode, ColorPickerMode::RgbInput);
+        assert_eq!(picker.rgb_input.hex, "FF8700");
+        assert_eq!(
+            (
+                picker.rgb_input.r.as_str(),
+                picker.rgb_input.g.as_str(),
+                picker.rgb_input.b.as_str()
+            ),
+            ("255", "135", "0")
+        );
+        assert_eq!(
+            picker.get_selected_color(),
+            Some(AnsiColor::rgb(255, 135, 0))
+        );
+
+        picker.open(ColorTarget::TextColor, None, &[]);
+        assert_eq!(picker.mode, ColorPickerMode::Basic16);
+        assert_eq!(picker.selected_basic, 0);
+    }
+
+    #[test]
+    fn test_select_recent_color() {
+        let recent = [AnsiColor::rgb(1, 2, 3), AnsiColor::c16(4)];
+        let mut picker = ColorPicker::default();
+        picker.open(ColorTarget::TextColor, None, &recent);
+        picker.input_char('2');
+        assert_eq!(picker.get_selected_color(), Some(AnsiColor::c16(4)));
+        picker.input_char('8');
+        assert_eq!(picker.get_selected_color(), Some(AnsiColor::c16(4)));
+        picker.input_char('1');
+        assert_eq!(picker.get_selected_color(), Some(AnsiColor::rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_simulate_cvd() {
+        let red = AnsiColor::rgb(255, 0, 0);
+        assert_eq!(simulate_cvd(red, None), red);
+        assert_eq!(simulate_cvd(ansi16::RED, None), ansi16::RED);
+
+        let simulated = simulate_cvd(red, Some(CvdKind::Deuteranopia));
+        assert_ne!(simulated, red);
+        let (r, g, _) = simulated.to_rgb();
+        assert!(r < 255 && g > 0, "{simulated:?}");
+
+        // 灰色不受影响，16 色先换算为 RGB
+        let gray = AnsiColor::rgb(128, 128, 128);
+        assert_eq!(simulate_cvd(gray, Some(CvdKind::Protanopia)), gray);
+        assert_eq!(
+            simulate_cvd(ansi16::WHITE, Some(CvdKind::Deuteranopia)),
+            AnsiColor::rgb(229, 229, 229)
+        );
+
+        let mut picker = ColorPicker::default();
+        picker.cycle_simulation();
+        assert_eq!(picker.simulate, Some(CvdKind::Deuteranopia));
+        picker.cycle_simulation();
+        picker.cycle_simulation();
+        assert_eq!(picker.simulate, None);
+    }
+
+    #[test]
+    fn test_hsl_to_rgb() {
+        assert_eq!(hsl_to_rgb(0, 100, 50), AnsiColor::rgb(255, 0, 0));
+        assert_eq!(hsl_to_rgb(120, 100, 25), AnsiColor::rgb(0, 128, 0));
+        assert_eq!(hsl_to_rgb(240, 100, 50), AnsiColor::rgb(0, 0, 255));
+        assert_eq!(hsl_to_rgb(360, 100, 50), AnsiColor::rgb(255, 0, 0));
+        for hue in [0, 90, 200, 359] {
+            assert_eq!(hsl_to_rgb(hue, 0, 100), AnsiColor::rgb(255, 255, 255));
+            assert_eq!(hsl_to_rgb(hue, 0, 0), AnsiColor::rgb(0, 0, 0));
+        }
+    }
+
+    #[test]
+    fn test_hsl_mode_input() {
+        let mut picker = ColorPicker::default();
+        picker.open(ColorTarget::TextColor, None, &[]);
+        for _ in 0..3 {
+            picker.cycle_mode();
+        }
+        assert_eq!(picker.mode, ColorPickerMode::HslInput);
+
+        // 超出范围的数字被拒绝
+        for c in "3700".chars() {
+            picker.input_char(c);
+        }
+        assert_eq!(picker.hsl_input.h, "37");
+        picker.backspace();
+        picker.backspace();
+        picker.input_char('0');
+        picker.move_horizontal(1);
+        for c in "100".chars() {
+            picker.input_char(c);
+        }
+        picker.move_horizontal(1);
+        assert_eq!(picker.get_selected_color(), None);
+        for c in "50".chars() {
+            picker.input_char(c);
+        }
+        assert_eq!(picker.get_selected_color(), Some(AnsiColor::rgb(255, 0, 0)));
+
+        picker.cycle_mode();
+        assert_eq!(picker.mode, ColorPickerMode::Basic16);
+    }
+
+    #[test]
+    fn test_hex_and_rgb_fields_stay_in_sync() {
+        let mut picker = rgb_picker(RgbInputLayout::default());
+        for c in "300".chars() {
+            picker.input_char(c);
+        }
+        // 超过 255 的数字被拒绝，不会回绕
+        assert_eq!(picker.rgb_input.r, "30");
+        picker.backspace();
+        picker.backspace();
+        for c in "18".chars() {
+            picker.input_char(c);
+        }
+        picker.move_horizontal(1);
+        picker.input_char('5');
+        picker.move_horizontal(1);
+        // 只填了部分 R/G/B 时 Hex 保持不变
+        assert_eq!(picker.rgb_input.hex, "");
+        picker.input_char('9');
+        assert_eq!(picker.rgb_input.hex, "120509");
+
+        // 未输完的 Hex 不覆盖 R/G/B
+        picker.move_horizontal(1);
+        picker.rgb_input.hex.clear();
+        for c in "00ff".chars() {
+            picker.input_char(c);
+        }
+        assert_eq!(
+            (
+                picker.rgb_input.r.as_str(),
+                picker.rgb_input.g.as_str(),
+                picker.rgb_input.b.as_str()
+            ),
+            ("18", "5", "9")
+        );
+        for c in "80".chars() {
+            picker.input_char(c);
+        }
+        assert_eq!(
+            (
+                picker.rgb_input.r.as_str(),
+                picker.rgb_input.g.as_str(),
+                picker.rgb_input.b.as_str()
+            ),
+            ("0", "255", "128")
+        );
+        assert_eq!(
+            picker.get_selected_color(),
+            Some(AnsiColor::rgb(0, 255, 128))
+        );
+    }
+}
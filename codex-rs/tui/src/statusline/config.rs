@@ -1,17 +1,54 @@
 // 状态栏配置
 // 配置文件位置：~/.codex/cxline/config.toml
 
+use super::capability::TerminalCapabilities;
+use super::color_picker::RgbInputLayout;
+use super::env::expand_env;
+use super::segment::SegmentData;
 use super::segment::SegmentId;
+use super::style::AnsiColor;
 use super::style::ColorConfig;
 use super::style::IconConfig;
+use super::style::PowerlineGlyph;
 use super::style::StyleMode;
 use super::style::TextStyleConfig;
 use super::themes::ThemePresets;
 use serde::Deserialize;
 use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// 配置操作错误
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// 补丁顶层不是 JSON 对象
+    #[error("配置补丁必须是 JSON 对象")]
+    PatchNotObject,
+    /// 合并后的配置无法解析（字段类型不匹配等）
+    #[error("配置补丁无效: {0}")]
+    InvalidPatch(#[source] serde_json::Error),
+    /// Powerline 模式下分隔符为空
+    #[error("style = {0:?} 时 separator 不能为空")]
+    EmptySeparator(StyleMode),
+    /// 颜色选项无法解析
+    #[error("segments.{segment}.options.{key} 不是有效的颜色: {value}")]
+    InvalidColorOption {
+        segment: &'static str,
+        key: String,
+        value: String,
+    },
+    /// order 中同一个 segment id 出现了多次
+    #[error("order 中 segment id {0:?} 出现了多次")]
+    DuplicateSegment(&'static str),
+    /// 该 segment 不认识的选项（通常是拼写错误）
+    #[error("segments.{segment}.options.{key} 不是已知选项")]
+    UnknownOption { segment: &'static str, key: String },
+}
 
 /// 状态栏配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,15 +69,103 @@ pub struct CxLineConfig {
     #[serde(default = "default_separator")]
     pub separator: String,
 
+    /// 外部命令结果（git 等）的缓存时长，单位毫秒
+    /// segment 可通过 `cache_ms` 选项单独覆盖
+    #[serde(default = "default_command_cache_ms")]
+    pub command_cache_ms: u64,
+
+    /// 非 Powerline 模式下用圆角端点包裹有背景色的 segment
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bubble: bool,
+
+    /// 非 Powerline 模式下在首尾也显示分隔符
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub outer_separators: bool,
+
+    /// 非 Powerline 模式下分隔符的颜色，未设置时使用终端默认前景色
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator_color: Option<AnsiColor>,
+
+    /// 非 Powerline 模式下分隔符是否变暗（高对比度主题可关闭）
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub separator_dim: bool,
+
+    /// Powerline 箭头两侧的间距调整，用于适配字体差异
+    /// 正数在箭头两侧各插入 N 个空格；负数为尽力而为：-1 去掉箭头前的右边距，
+    /// -2 再去掉箭头后的左边距（终端无法真正重叠字符）
+    #[serde(default)]
+    pub powerline_arrow_padding: i8,
+
+    /// Powerline 模式下 segment 之间过渡字符的形状
+    #[serde(default)]
+    pub powerline_separator: PowerlineGlyph,
+
+    /// Powerline 模式下在最后一个 segment 后绘制收尾箭头
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub powerline_trailing_cap: bool,
+
+    /// Powerline 模式下没有次要内容的 pill 省略右边距，使 pill 更紧凑
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tight_pills: bool,
+
+    /// 宽度不足导致 segment 被丢弃时，在丢弃处显示的提示符（如 "…"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overflow_indicator: Option<String>,
+
+    /// 颜色选择器 RGB 模式下输入框的布局（Hex 在前或只显示 Hex）
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub rgb_input_layout: RgbInputLayout,
+
     /// 各 segment 配置
     #[serde(default)]
     pub segments: SegmentsConfig,
+
+    /// segment 的显示顺序，未列出的 segment 不显示
+    #[serde(default = "default_order", skip_serializing_if = "is_default_order")]
+    pub order: Vec<SegmentId>,
+
+    /// 当前版本不认识的顶层键（如新版本写入的字段），原样保留以便保存时不丢失
+    #[serde(flatten, default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, toml::Value>,
+}
+
+/// 外部命令结果的默认缓存时长（毫秒），与原来 git 轮询的 1 秒间隔一致
+pub const DEFAULT_COMMAND_CACHE_MS: u64 = 1000;
+
+fn default_command_cache_ms() -> u64 {
+    DEFAULT_COMMAND_CACHE_MS
 }
 
 fn default_true() -> bool {
     true
 }
 
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde 的 skip_serializing_if 需要引用参数
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+/// 等于类型默认值时不写入配置文件
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+fn default_secondary_separator() -> String {
+    " ".to_string()
+}
+
+fn is_default_secondary_separator(separator: &str) -> bool {
+    separator == " "
+}
+
+fn default_order() -> Vec<SegmentId> {
+    SegmentId::ALL.to_vec()
+}
+
+fn is_default_order(order: &[SegmentId]) -> bool {
+    order == SegmentId::ALL
+}
+
 fn default_theme() -> String {
     "cometix".to_string()
 }
@@ -49,23 +174,121 @@ fn default_separator() -> String {
     " │ ".to_string()
 }
 
-/// 各 segment 的配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SegmentsConfig {
-    #[serde(default = "SegmentItemConfig::default_model")]
-    pub model: SegmentItemConfig,
+/// 加载结果：失败时 config 为默认配置，last_error 记录原因，供 WarningSegment 在状态栏提示
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: CxLineConfig,
+    pub last_error: Option<String>,
+}
+
+impl LoadedConfig {
+    fn ok(config: CxLineConfig) -> Self {
+        Self {
+            config,
+            last_error: None,
+        }
+    }
 
-    #[serde(default = "SegmentItemConfig::default_directory")]
-    pub directory: SegmentItemConfig,
+    /// 解析 TOML 配置内容
+    pub fn from_toml(content: &str) -> Self {
+        match toml::from_str::<CxLineConfig>(content) {
+            Ok(config) => {
+                // 配置仍然可用，逐条记录问题便于排查
+                if let Err(errors) = config.validate() {
+                    for error in errors {
+                        tracing::warn!("cxline 配置问题: {error}");
+                    }
+                }
+                Self::ok(config)
+            }
+            Err(e) => {
+                tracing::warn!("解析 cxline 配置失败: {}, 使用默认配置", e);
+                Self {
+                    config: CxLineConfig::fallback(),
+                    last_error: Some(format!("解析配置失败: {e}")),
+                }
+            }
+        }
+    }
+}
 
-    #[serde(default = "SegmentItemConfig::default_git")]
-    pub git: SegmentItemConfig,
+/// 叠加到基础配置上的局部配置，未设置的字段保留基础配置的值
+/// 用于"基础主题 + 小幅点缀"的组合，例如只改 git 的颜色
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PartialCxLineConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<StyleMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_cache_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bubble: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outer_separators: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator_color: Option<AnsiColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator_dim: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub powerline_arrow_padding: Option<i8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub powerline_separator: Option<PowerlineGlyph>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub powerline_trailing_cap: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tight_pills: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overflow_indicator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rgb_input_layout: Option<RgbInputLayout>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Vec<SegmentId>>,
+    /// 只需列出要修改的 segment
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub segments: HashMap<SegmentId, PartialSegmentItemConfig>,
+}
 
-    #[serde(default = "SegmentItemConfig::default_context")]
-    pub context: SegmentItemConfig,
+/// 单个 segment 的局部配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PartialSegmentItemConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<IconConfig>,
+    /// 逐项覆盖：只替换设置了的颜色
+    #[serde(default)]
+    pub colors: ColorConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub styles: Option<TextStyleConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_separator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_hide_when: Option<AutoHideRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_colors: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<SegmentAlignment>,
+    /// 按键合并到基础 options
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub options: HashMap<String, serde_json::Value>,
+}
 
-    #[serde(default = "SegmentItemConfig::default_usage")]
-    pub usage: SegmentItemConfig,
+/// 各 segment 的配置，同一 id 可以出现多次（例如两个指向不同路径的 directory）
+///
+/// id 不重复时序列化为旧的 `[segments.<id>]` 表结构，保持与旧版本兼容；
+/// 有重复时序列化为 `[[segments]]` 数组。两种结构都可以读取，缺少的 segment 使用默认主题的配置
+#[derive(Debug, Clone)]
+pub struct SegmentsConfig {
+    items: Vec<SegmentItemConfig>,
+    /// 当前版本不认识的 segment（如新版本写入的），按 (id, 原始配置) 保留以便保存时写回
+    unknown: Vec<(String, toml::Value)>,
 }
 
 impl Default for SegmentsConfig {
@@ -75,6 +298,207 @@ impl Default for SegmentsConfig {
     }
 }
 
+/// 配置中没有对应 id 的 segment 时返回的占位配置（视为禁用）
+static MISSING_SEGMENT: LazyLock<SegmentItemConfig> = LazyLock::new(|| SegmentItemConfig {
+    enabled: false,
+    ..SegmentItemConfig::new(
+        SegmentId::default(),
+        IconConfig::default(),
+        ColorConfig::default(),
+        TextStyleConfig::default(),
+    )
+});
+
+impl SegmentsConfig {
+    pub fn new(items: Vec<SegmentItemConfig>) -> Self {
+        Self {
+            items,
+            unknown: Vec::new(),
+        }
+    }
+
+    /// 补齐缺少的 segment（使用默认主题的配置），用于读取旧配置或手写的部分配置
+    fn with_missing_defaults(mut self) -> Self {
+        let missing: Vec<SegmentId> = SegmentId::ALL
+            .into_iter()
+            .filter(|id| !self.items.iter().any(|item| item.id == *id))
+            .collect();
+        let needs_prefix = self
+            .items
+            .iter()
+            .any(|item| item.secondary_prefix.is_none());
+        if !missing.is_empty() || needs_prefix {
+            let defaults = ThemePresets::get_default().segments;
+            // 旧配置没有 secondary_prefix 键，沿用预设的默认前缀
+            for item in self.items.iter_mut() {
+                if item.secondary_prefix.is_none() {
+                    item.secondary_prefix = defaults.get(item.id).secondary_prefix.clone();
+                }
+            }
+            for id in missing {
+                self.items.push(defaults.get(id).clone());
+            }
+        }
+        self
+    }
+
+    /// 获取指定 segment 的配置，同一 id 有多个时返回第一个
+    pub fn get(&self, id: SegmentId) -> &SegmentItemConfig {
+        self.items
+            .iter()
+            .find(|item| item.id == id)
+            .unwrap_or(&MISSING_SEGMENT)
+    }
+
+    /// 获取指定 segment 的可变配置，同一 id 有多个时返回第一个，没有时追加一个禁用的配置
+    pub fn get_mut(&mut self, id: SegmentId) -> &mut SegmentItemConfig {
+        let index = match self.items.iter().position(|item| item.id == id) {
+            Some(index) => index,
+            None => {
+                self.items.push(SegmentItemConfig {
+                    id,
+                    ..MISSING_SEGMENT.clone()
+                });
+                self.items.len() - 1
+            }
+        };
+        &mut self.items[index]
+    }
+
+    /// 指定 id 的所有配置，按配置中的先后顺序
+    pub fn instances(&self, id: SegmentId) -> impl Iterator<Item = &SegmentItemConfig> {
+        self.items.iter().filter(move |item| item.id == id)
+    }
+
+    /// 追加一个 segment 配置（允许与已有的 id 重复）
+    pub fn push(&mut self, item: SegmentItemConfig) {
+        self.items.push(item);
+    }
+
+    /// 遍历所有 segment 的配置
+    pub fn iter(&self) -> impl Iterator<Item = &SegmentItemConfig> {
+        self.items.iter()
+    }
+
+    /// 遍历所有 segment 的可变配置
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut SegmentItemConfig> {
+        self.items.iter_mut()
+    }
+
+    fn has_duplicates(&self) -> bool {
+        self.items
+            .iter()
+            .enumerate()
+            .any(|(index, item)| self.items[..index].iter().any(|prev| prev.id == item.id))
+    }
+}
+
+impl Serialize for SegmentsConfig {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        use serde::ser::SerializeSeq;
+
+        if self.has_duplicates() {
+            let mut seq = serializer.serialize_seq(Some(self.items.len() + self.unknown.len()))?;
+            for item in &self.items {
+                seq.serialize_element(item)?;
+            }
+            for (id, value) in &self.unknown {
+                let mut value = value.clone();
+                if let toml::Value::Table(table) = &mut value {
+                    table.insert("id".to_string(), toml::Value::String(id.clone()));
+                }
+                seq.serialize_element(&value)?;
+            }
+            return seq.end();
+        }
+        let mut map = serializer.serialize_map(Some(self.items.len() + self.unknown.len()))?;
+        for item in &self.items {
+            map.serialize_entry(item.id.as_str(), item)?;
+        }
+        for (id, value) in &self.unknown {
+            map.serialize_entry(id, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SegmentsConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SegmentsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for SegmentsVisitor {
+            type Value = SegmentsConfig;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a table keyed by segment id or an array of segments")
+            }
+
+            // 数组结构：id 不认识的条目原样保留
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                use serde::de::Error;
+
+                let mut segments = SegmentsConfig::new(Vec::new());
+                while let Some(value) = seq.next_element::<serde_json::Value>()? {
+                    let unknown_id = value
+                        .get("id")
+                        .and_then(serde_json::Value::as_str)
+                        .filter(|key| segment_id_from_str(key).is_none())
+                        .map(str::to_string);
+                    match unknown_id {
+                        Some(id) => {
+                            let mut value =
+                                toml::Value::try_from(value).map_err(A::Error::custom)?;
+                            if let toml::Value::Table(table) = &mut value {
+                                table.remove("id");
+                            }
+                            segments.unknown.push((id, value));
+                        }
+                        None => segments
+                            .items
+                            .push(serde_json::from_value(value).map_err(A::Error::custom)?),
+                    }
+                }
+                Ok(segments)
+            }
+
+            // 旧的表结构：键即 segment id，新版本才有的 segment 原样保留
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut segments = SegmentsConfig::new(Vec::new());
+                while let Some(key) = map.next_key::<String>()? {
+                    match segment_id_from_str(&key) {
+                        Some(id) => {
+                            let mut item: SegmentItemConfig = map.next_value()?;
+                            item.id = id;
+                            segments.items.push(item);
+                        }
+                        None => segments.unknown.push((key, map.next_value()?)),
+                    }
+                }
+                segments
+                    .items
+                    .sort_by_key(|item| SegmentId::ALL.iter().position(|id| *id == item.id));
+                Ok(segments)
+            }
+        }
+
+        deserializer
+            .deserialize_any(SegmentsVisitor)
+            .map(SegmentsConfig::with_missing_defaults)
+    }
+}
+
+fn segment_id_from_str(key: &str) -> Option<SegmentId> {
+    SegmentId::ALL.into_iter().find(|id| id.as_str() == key)
+}
+
 /// 单个 segment 的配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SegmentItemConfig {
@@ -98,53 +522,341 @@ pub struct SegmentItemConfig {
     #[serde(default)]
     pub styles: TextStyleConfig,
 
+    /// 主要内容与次要内容之间的连接符
+    #[serde(
+        default = "default_secondary_separator",
+        skip_serializing_if = "is_default_secondary_separator"
+    )]
+    pub secondary_separator: String,
+
+    /// 次要内容前的前缀符号（如 "· "），默认值由主题预设提供
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_prefix: Option<String>,
+
+    /// 数据不值得显示时自动隐藏
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_hide_when: Option<AutoHideRule>,
+
+    /// 锁定颜色：切换主题时保留当前 segment 的颜色
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lock_colors: bool,
+
+    /// 在状态栏中靠左还是靠右显示（靠右需配合 `render_line_for_width`）
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub alignment: SegmentAlignment,
+
     /// 自定义选项
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub options: HashMap<String, serde_json::Value>,
+
+    /// 当前版本不认识的键，原样保留以便保存时不丢失
+    #[serde(flatten, default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, toml::Value>,
+}
+
+/// segment 的水平对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentAlignment {
+    #[default]
+    Left,
+    Right,
+}
+
+/// 自动隐藏规则，根据 segment 收集到的数据判断是否"无趣"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoHideRule {
+    /// Git 工作区干净且与上游同步
+    GitClean,
+    /// 上下文使用率低于给定百分比
+    ContextBelow(f64),
+    /// 5h 限额使用率低于给定百分比
+    UsageBelow(f64),
+}
+
+impl AutoHideRule {
+    pub fn should_hide(&self, data: &SegmentData) -> bool {
+        let metadata_f64 = |key: &str| {
+            data.metadata
+                .get(key)
+                .and_then(|value| value.parse::<f64>().ok())
+        };
+
+        match self {
+            Self::GitClean => {
+                // 预览数据为状态符号，实时读取为枚举名
+                matches!(
+                    data.metadata.get("status").map(String::as_str),
+                    Some("✓" | "Clean")
+                ) && metadata_f64("ahead") == Some(0.0)
+                    && metadata_f64("behind") == Some(0.0)
+            }
+            Self::ContextBelow(threshold) => {
+                metadata_f64("percent").is_some_and(|percent| percent < *threshold)
+            }
+            Self::UsageBelow(threshold) => {
+                metadata_f64("hourly_percent").is_some_and(|percent| percent < *threshold)
+            }
+        }
+    }
 }
 
 impl SegmentItemConfig {
-    pub fn default_model() -> Self {
-        ThemePresets::get_default().segments.model
+    pub fn new(
+        id: SegmentId,
+        icon: IconConfig,
+        colors: ColorConfig,
+        styles: TextStyleConfig,
+    ) -> Self {
+        Self {
+            id,
+            enabled: true,
+            icon,
+            colors,
+            styles,
+            secondary_separator: default_secondary_separator(),
+            secondary_prefix: None,
+            auto_hide_when: None,
+            lock_colors: false,
+            alignment: SegmentAlignment::Left,
+            options: HashMap::new(),
+            extra: HashMap::new(),
+        }
     }
 
-    pub fn default_directory() -> Self {
-        ThemePresets::get_default().segments.directory
+    /// 将局部配置叠加到当前 segment
+    fn apply_partial(&mut self, partial: &PartialSegmentItemConfig) {
+        self.enabled = partial.enabled.unwrap_or(self.enabled);
+        self.lock_colors = partial.lock_colors.unwrap_or(self.lock_colors);
+        self.alignment = partial.alignment.unwrap_or(self.alignment);
+        self.auto_hide_when = partial.auto_hide_when.or(self.auto_hide_when);
+        if let Some(icon) = &partial.icon {
+            self.icon = icon.clone();
+        }
+        if let Some(styles) = &partial.styles {
+            self.styles = styles.clone();
+        }
+        if let Some(separator) = &partial.secondary_separator {
+            self.secondary_separator = separator.clone();
+        }
+        if let Some(prefix) = &partial.secondary_prefix {
+            self.secondary_prefix = Some(prefix.clone());
+        }
+
+        let colors = &partial.colors;
+        self.colors.icon = colors.icon.or(self.colors.icon);
+        self.colors.text = colors.text.or(self.colors.text);
+        self.colors.background = colors.background.or(self.colors.background);
+        self.colors.badge = colors.badge.or(self.colors.badge);
+
+        for (key, value) in &partial.options {
+            self.options.insert(key.clone(), value.clone());
+        }
+    }
+
+    /// 默认主题中指定 segment 的配置
+    pub fn default_for(id: SegmentId) -> Self {
+        ThemePresets::get_default().segments.get(id).clone()
+    }
+
+    /// 次要内容前缀，未设置时为空；其中的 `$VAR` 按环境变量展开
+    pub fn secondary_prefix(&self) -> Cow<'_, str> {
+        let prefix = match &self.secondary_prefix {
+            Some(prefix) => prefix,
+            None => "",
+        };
+        if prefix.contains('$') {
+            Cow::Owned(expand_env(prefix))
+        } else {
+            Cow::Borrowed(prefix)
+        }
+    }
+
+    /// 是否按 auto_hide_when 规则隐藏该数据
+    pub fn should_hide(&self, data: &SegmentData) -> bool {
+        self.auto_hide_when
+            .is_some_and(|rule| rule.should_hide(data))
+    }
+
+    /// 读取布尔类型的自定义选项
+    pub fn option_bool(&self, key: &str) -> Option<bool> {
+        self.options.get(key).and_then(serde_json::Value::as_bool)
     }
 
-    pub fn default_git() -> Self {
-        ThemePresets::get_default().segments.git
+    /// 读取字符串类型的自定义选项
+    pub fn option_str(&self, key: &str) -> Option<&str> {
+        self.options.get(key).and_then(serde_json::Value::as_str)
     }
 
-    pub fn default_context() -> Self {
-        ThemePresets::get_default().segments.context
+    /// 将自定义选项反序列化为指定类型（如 `TruncatePosition`），类型不匹配时返回 None
+    pub fn option<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.options
+            .get(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
     }
 
-    pub fn default_usage() -> Self {
-        ThemePresets::get_default().segments.usage
+    /// 读取颜色选项：与 `AnsiColor` 相同的 JSON（`{"c16": 2}`、`{"c256": 214}`、
+    /// `{"r": 255, "g": 136, "b": 0}`），或 `AnsiColor::try_from` 接受的字符串（"red"、"#ff8800"）
+    /// 未设置或无法解析时返回 None（无效值由 `validate` 在加载时报告，渲染时不重复记录）
+    pub fn option_color(&self, key: &str) -> Option<AnsiColor> {
+        parse_color_value(self.options.get(key)?)
     }
 }
 
+/// 颜色选项的 JSON 值：AnsiColor 的 JSON 形式或颜色字符串
+fn parse_color_value(value: &serde_json::Value) -> Option<AnsiColor> {
+    match value.as_str() {
+        Some(text) => AnsiColor::try_from(text).ok(),
+        None => serde_json::from_value(value.clone()).ok(),
+    }
+}
+
+/// 各 segment 读取的 options 键，新增选项时需同步更新
+fn known_option_keys(id: SegmentId) -> &'static [&'static str] {
+    match id {
+        SegmentId::Model => &[
+            "show_when_empty",
+            "empty_placeholder",
+            "model_id_subtitle",
+            "show_effort",
+        ],
+        SegmentId::Directory => &[
+            "depth",
+            "components",
+            "join",
+            "home_tilde",
+            "hide_if_missing",
+            "truncate_position",
+            "path",
+        ],
+        SegmentId::Git => &[
+            "show_commit_count",
+            "commit_count_base",
+            "branch_color_rules",
+            "show_branch",
+            "max_branch_len",
+            "truncate_position",
+            "show_stash",
+            "show_untracked",
+            "remotes",
+            "colorize_icon_by_status",
+            "colorblind_safe",
+            "clean_color",
+            "dirty_color",
+            "conflict_color",
+            "show_identity",
+            "expected_identities",
+            "show_base",
+            "group_indicators",
+            "indicator_brackets",
+        ],
+        SegmentId::Context => &[
+            "dynamic_icon",
+            "number_style",
+            "group_separator",
+            "display_remaining",
+            "min_tokens",
+            "display",
+            "bar_width",
+            "warn_at",
+            "crit_at",
+        ],
+        SegmentId::Usage => &["color_by_level", "display_remaining", "reset_format"],
+        SegmentId::Budget => &["warn_fraction"],
+        SegmentId::Jj => &["description_width", "truncate_position"],
+        SegmentId::Edits | SegmentId::Warning => &[],
+        SegmentId::Timezone => &["show_zone_name"],
+        SegmentId::Host => &["ssh_only"],
+    }
+}
+
+/// 所有 segment 通用的 options 键（见 [`CxLineConfig::command_cache_ttl`]）
+const COMMON_OPTION_KEYS: &[&str] = &["cache_ms"];
+
+/// 覆盖配置文件路径的环境变量
+pub const CONFIG_PATH_ENV: &str = "CODEX_CXLINE_CONFIG";
+
+/// 解析后的配置相关路径，无法确定 home 目录时对应项为 None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPaths {
+    /// 配置文件
+    pub config: Option<PathBuf>,
+    /// 主题目录
+    pub themes_dir: Option<PathBuf>,
+    /// 收藏颜色文件
+    pub favorites: Option<PathBuf>,
+    /// 各仓库记录的 git base 提交
+    pub git_bases: Option<PathBuf>,
+}
+
+impl ResolvedPaths {
+    /// 环境变量只覆盖配置文件本身，主题和收藏仍放在配置目录下
+    fn resolve(config_dir: Option<PathBuf>, config_override: Option<PathBuf>) -> Self {
+        Self {
+            config: config_override
+                .or_else(|| config_dir.as_ref().map(|dir| dir.join("config.toml"))),
+            themes_dir: config_dir.as_ref().map(|dir| dir.join("themes")),
+            favorites: config_dir.as_ref().map(|dir| dir.join("favorites.toml")),
+            git_bases: config_dir.map(|dir| dir.join("git_bases.json")),
+        }
+    }
+}
+
+/// 字段级默认值，与反序列化时缺省字段的取值一致，不含任何 segment
+/// 内置主题在此基础上设置各自的字段；完整可用的默认配置见 [`CxLineConfig::fallback`]
 impl Default for CxLineConfig {
     fn default() -> Self {
-        ThemePresets::get_theme("cometix")
+        Self {
+            enabled: true,
+            theme: default_theme(),
+            style: StyleMode::default(),
+            separator: default_separator(),
+            command_cache_ms: DEFAULT_COMMAND_CACHE_MS,
+            bubble: false,
+            outer_separators: false,
+            separator_color: None,
+            separator_dim: true,
+            powerline_arrow_padding: 0,
+            powerline_separator: PowerlineGlyph::default(),
+            powerline_trailing_cap: true,
+            tight_pills: false,
+            overflow_indicator: None,
+            rgb_input_layout: RgbInputLayout::default(),
+            segments: SegmentsConfig::new(Vec::new()),
+            order: default_order(),
+            extra: HashMap::new(),
+        }
     }
 }
 
 impl CxLineConfig {
+    /// 配置缺失或无法解析时使用的默认主题
+    pub fn fallback() -> Self {
+        ThemePresets::get_theme(&default_theme())
+    }
+
     /// 获取配置目录路径
     pub fn config_dir() -> Option<PathBuf> {
         dirs::home_dir().map(|home| home.join(".codex").join("cxline"))
     }
 
-    /// 获取配置文件路径
+    /// 获取配置文件路径（`CODEX_CXLINE_CONFIG` 优先）
     pub fn config_path() -> Option<PathBuf> {
-        Self::config_dir().map(|dir| dir.join("config.toml"))
+        Self::resolved_paths().config
     }
 
     /// 获取主题目录路径
     pub fn themes_dir() -> Option<PathBuf> {
-        Self::config_dir().map(|dir| dir.join("themes"))
+        Self::resolved_paths().themes_dir
+    }
+
+    /// 实际使用的各文件路径，供宿主打印或打开
+    pub fn resolved_paths() -> ResolvedPaths {
+        let config_override = std::env::var_os(CONFIG_PATH_ENV)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from);
+        ResolvedPaths::resolve(Self::config_dir(), config_override)
     }
 
     /// 初始化配置目录和主题文件
@@ -158,33 +870,45 @@ impl CxLineConfig {
         ThemePresets::ensure_themes_exist();
     }
 
+    /// 首次运行时的默认配置，按终端能力选择主题
+    pub fn first_run_default() -> Self {
+        Self::for_capabilities(TerminalCapabilities::detect())
+    }
+
+    /// 按给定的终端能力选择主题
+    pub fn for_capabilities(capabilities: TerminalCapabilities) -> Self {
+        ThemePresets::get_theme(capabilities.first_run_theme())
+    }
+
     /// 从文件加载配置
     pub fn load() -> Self {
+        Self::load_checked().config
+    }
+
+    /// 从文件加载配置，读取或解析失败时回退到默认配置并保留错误信息
+    pub fn load_checked() -> LoadedConfig {
         // 首先初始化目录结构
         Self::init();
 
         let Some(path) = Self::config_path() else {
-            return Self::default();
+            return LoadedConfig::ok(Self::fallback());
         };
 
         if !path.exists() {
-            let config = Self::default();
+            let config = Self::first_run_default();
             // 首次运行时创建默认配置文件
             let _ = config.save();
-            return config;
+            return LoadedConfig::ok(config);
         }
 
         match fs::read_to_string(&path) {
-            Ok(content) => match toml::from_str::<CxLineConfig>(&content) {
-                Ok(config) => config,
-                Err(e) => {
-                    tracing::warn!("解析 cxline 配置失败: {}, 使用默认配置", e);
-                    Self::default()
-                }
-            },
+            Ok(content) => LoadedConfig::from_toml(&content),
             Err(e) => {
                 tracing::warn!("读取 cxline 配置失败: {}, 使用默认配置", e);
-                Self::default()
+                LoadedConfig {
+                    config: Self::fallback(),
+                    last_error: Some(format!("读取配置失败: {e}")),
+                }
             }
         }
     }
@@ -203,40 +927,888 @@ impl CxLineConfig {
             fs::create_dir_all(parent)?;
         }
 
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        let content = self.to_toml_string()?;
 
         fs::write(&path, content)
     }
 
-    /// 应用主题
+    /// 序列化为 TOML
+    /// options 中存在 TOML 无法表示的值（如 null）时，错误信息会指明具体的键
+    pub fn to_toml_string(&self) -> std::io::Result<String> {
+        for segment in self.segments.iter() {
+            for (key, value) in &segment.options {
+                if let Err(e) = toml::Value::try_from(value) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "segments.{}.options.{key} 无法保存为 TOML: {e}",
+                            segment.id.as_str()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        toml::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// 应用主题（lock_colors 的 segment 保留当前颜色）
     pub fn apply_theme(&mut self, theme_name: &str) {
-        let theme = ThemePresets::get_theme(theme_name);
+        self.apply_theme_config(ThemePresets::get_theme(theme_name));
         self.theme = theme_name.to_string();
-        self.style = theme.style;
-        self.separator = theme.separator;
-        self.segments = theme.segments;
+    }
+
+    /// 应用已加载的主题配置：主题级字段全部取自主题，只保留 enabled、extra 和各 segment 的
+    /// extra，以及 lock_colors 的 segment 的颜色
+    pub fn apply_theme_config(&mut self, theme: CxLineConfig) {
+        let mut segments = theme.segments;
+        for segment in segments.iter_mut() {
+            let current = self.segments.get(segment.id);
+            if current.lock_colors {
+                segment.colors = current.colors.clone();
+                segment.lock_colors = true;
+            }
+            segment.extra = current.extra.clone();
+        }
+        // 主题只提供每个 id 的第一个配置，额外添加的实例原样保留
+        for id in SegmentId::ALL {
+            for extra in self.segments.instances(id).skip(1) {
+                segments.push(extra.clone());
+            }
+        }
+
+        *self = CxLineConfig {
+            enabled: self.enabled,
+            segments,
+            extra: std::mem::take(&mut self.extra),
+            ..theme
+        };
+    }
+
+    /// 丢弃所有自定义，恢复为当前主题的原始状态（保留 enabled、theme 和 extra）
+    pub fn reset_to_theme(&mut self) {
+        let theme = ThemePresets::get_theme(&self.theme);
+        *self = CxLineConfig {
+            enabled: self.enabled,
+            theme: std::mem::take(&mut self.theme),
+            extra: std::mem::take(&mut self.extra),
+            ..theme
+        };
+    }
+
+    /// 将 JSON 对象深度合并到当前配置，只覆盖补丁中出现的字段
+    /// 合并结果无法解析时返回错误，配置保持不变
+    pub fn apply_patch(&mut self, patch: serde_json::Value) -> Result<(), ConfigError> {
+        if !patch.is_object() {
+            return Err(ConfigError::PatchNotObject);
+        }
+
+        let mut merged = serde_json::to_value(&*self).map_err(ConfigError::InvalidPatch)?;
+        let mut patch = patch;
+        // 有重复 id 时 segments 是数组，按 id 合并到对应条目，而不是整体替换
+        if let Some(serde_json::Value::Array(items)) = merged.get_mut("segments")
+            && let Some(patch) = patch.as_object_mut()
+            && patch
+                .get("segments")
+                .is_some_and(serde_json::Value::is_object)
+            && let Some(serde_json::Value::Object(segment_patches)) = patch.remove("segments")
+        {
+            merge_segment_patches(items, segment_patches);
+        }
+        merge_json(&mut merged, patch);
+        *self = serde_json::from_value(merged).map_err(ConfigError::InvalidPatch)?;
+        Ok(())
+    }
+
+    /// 返回将局部配置叠加到当前配置后的结果，当前配置不变
+    pub fn overlay(&self, partial: &PartialCxLineConfig) -> CxLineConfig {
+        let mut config = self.clone();
+        config.enabled = partial.enabled.unwrap_or(config.enabled);
+        config.style = partial.style.unwrap_or(config.style);
+        config.command_cache_ms = partial.command_cache_ms.unwrap_or(config.command_cache_ms);
+        config.bubble = partial.bubble.unwrap_or(config.bubble);
+        config.outer_separators = partial.outer_separators.unwrap_or(config.outer_separators);
+        config.separator_color = partial.separator_color.or(config.separator_color);
+        config.separator_dim = partial.separator_dim.unwrap_or(config.separator_dim);
+        config.powerline_arrow_padding = partial
+            .powerline_arrow_padding
+            .unwrap_or(config.powerline_arrow_padding);
+        config.powerline_separator = partial
+            .powerline_separator
+            .unwrap_or(config.powerline_separator);
+        config.powerline_trailing_cap = partial
+            .powerline_trailing_cap
+            .unwrap_or(config.powerline_trailing_cap);
+        config.tight_pills = partial.tight_pills.unwrap_or(config.tight_pills);
+        if let Some(theme) = &partial.theme {
+            config.theme = theme.clone();
+        }
+        config.rgb_input_layout = partial.rgb_input_layout.unwrap_or(config.rgb_input_layout);
+        if let Some(indicator) = &partial.overflow_indicator {
+            config.overflow_indicator = Some(indicator.clone());
+        }
+        if let Some(separator) = &partial.separator {
+            config.separator = separator.clone();
+        }
+        if let Some(order) = &partial.order {
+            config.order = order.clone();
+        }
+        for (id, segment) in &partial.segments {
+            config.segments.get_mut(*id).apply_partial(segment);
+        }
+        config
+    }
+
+    /// 列出与当前主题预设不同的字段路径，例如 "segments.git.colors.text"
+    pub fn diff_from_theme(&self) -> Vec<String> {
+        let theme = ThemePresets::get_theme(&self.theme);
+        let (Ok(current), Ok(theme)) = (serde_json::to_value(self), serde_json::to_value(&theme))
+        else {
+            return Vec::new();
+        };
+
+        let mut paths = Vec::new();
+        collect_value_diffs("", &current, &theme, &mut paths);
+        paths
     }
 
     /// 获取指定 segment 的配置
     pub fn get_segment_config(&self, id: SegmentId) -> &SegmentItemConfig {
-        match id {
-            SegmentId::Model => &self.segments.model,
-            SegmentId::Directory => &self.segments.directory,
-            SegmentId::Git => &self.segments.git,
-            SegmentId::Context => &self.segments.context,
-            SegmentId::Usage => &self.segments.usage,
-        }
+        self.segments.get(id)
     }
 
     /// 获取指定 segment 的可变配置
     pub fn get_segment_config_mut(&mut self, id: SegmentId) -> &mut SegmentItemConfig {
-        match id {
-            SegmentId::Model => &mut self.segments.model,
-            SegmentId::Directory => &mut self.segments.directory,
-            SegmentId::Git => &mut self.segments.git,
-            SegmentId::Context => &mut self.segments.context,
-            SegmentId::Usage => &mut self.segments.usage,
+        self.segments.get_mut(id)
+    }
+
+    /// 检查手写配置中的常见问题，返回全部问题而不是遇到第一个就停止
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        if self.separator.is_empty()
+            && matches!(
+                self.style,
+                StyleMode::Powerline | StyleMode::PowerlineSeparated
+            )
+        {
+            errors.push(ConfigError::EmptySeparator(self.style));
+        }
+
+        for (index, id) in self.order.iter().enumerate() {
+            if self.order[..index].contains(id) {
+                errors.push(ConfigError::DuplicateSegment(id.as_str()));
+            }
+        }
+
+        for segment in self.segments.iter() {
+            let name = segment.id.as_str();
+            let known = known_option_keys(segment.id);
+            let mut keys: Vec<&String> = segment.options.keys().collect();
+            keys.sort();
+            for key in keys {
+                if !known.contains(&key.as_str()) && !COMMON_OPTION_KEYS.contains(&key.as_str()) {
+                    errors.push(ConfigError::UnknownOption {
+                        segment: name,
+                        key: key.clone(),
+                    });
+                    continue;
+                }
+                let value = &segment.options[key];
+                let invalid_color = if key == "branch_color_rules" {
+                    segment.option::<Vec<(String, AnsiColor)>>(key).is_none()
+                } else {
+                    key.ends_with("_color") && parse_color_value(value).is_none()
+                };
+                if invalid_color {
+                    errors.push(ConfigError::InvalidColorOption {
+                        segment: name,
+                        key: key.clone(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 指定 segment 的命令缓存时长：优先使用其 `cache_ms` 选项，否则使用全局配置
+    pub fn command_cache_ttl(&self, id: SegmentId) -> Duration {
+        self.command_cache_ttl_for(self.segments.get(id))
+    }
+
+    /// 同 [`Self::command_cache_ttl`]，用于同一 id 有多个配置时指定其中一个
+    pub fn command_cache_ttl_for(&self, segment: &SegmentItemConfig) -> Duration {
+        let ms = segment
+            .option::<u64>("cache_ms")
+            .unwrap_or(self.command_cache_ms);
+        Duration::from_millis(ms)
+    }
+
+    /// 将所有 RGB 颜色（含 `*_color` 选项和 `branch_color_rules`）改写为最接近的 256 色，
+    /// 供只支持 256 色的终端一次性转换；返回改写的颜色数量
+    pub fn downgrade_to_256(&mut self) -> usize {
+        let mut changed = 0;
+        let mut downgrade = |color: &mut AnsiColor| {
+            if let AnsiColor::Rgb { .. } = *color {
+                *color = AnsiColor::c256(color.to_256());
+                changed += 1;
+            }
+        };
+
+        for segment in self.segments.iter_mut() {
+            let colors = &mut segment.colors;
+            for color in [
+                &mut colors.icon,
+                &mut colors.text,
+                &mut colors.background,
+                &mut colors.badge,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                downgrade(color);
+            }
+
+            for (key, value) in segment.options.iter_mut() {
+                let json = if key == "branch_color_rules" {
+                    let Ok(mut rules) =
+                        serde_json::from_value::<Vec<(String, AnsiColor)>>(value.clone())
+                    else {
+                        continue;
+                    };
+                    if !rules.iter().any(|(_, color)| color.is_rgb()) {
+                        continue;
+                    }
+                    rules.iter_mut().for_each(|(_, color)| downgrade(color));
+                    serde_json::to_value(rules)
+                } else if key.ends_with("_color") {
+                    let Some(mut color) = parse_color_value(value).filter(AnsiColor::is_rgb) else {
+                        continue;
+                    };
+                    downgrade(&mut color);
+                    serde_json::to_value(color)
+                } else {
+                    continue;
+                };
+                if let Ok(json) = json {
+                    *value = json;
+                }
+            }
+        }
+        if let Some(color) = &mut self.separator_color {
+            downgrade(color);
         }
+        changed
+    }
+
+    /// 一次性启用或隐藏所有 segment
+    pub fn set_all_segments_enabled(&mut self, enabled: bool) {
+        for segment in self.segments.iter_mut() {
+            segment.enabled = enabled;
+        }
+    }
+
+    /// 是否至少有一个 segment 启用
+    pub fn any_enabled(&self) -> bool {
+        self.segments.iter().any(|segment| segment.enabled)
+    }
+}
+
+/// 按 id 把 segment 补丁合并到数组中第一个同 id 的条目，没有时追加
+fn merge_segment_patches(
+    items: &mut Vec<serde_json::Value>,
+    patches: serde_json::Map<String, serde_json::Value>,
+) {
+    for (id, patch) in patches {
+        let existing = items
+            .iter_mut()
+            .find(|item| item.get("id").and_then(serde_json::Value::as_str) == Some(id.as_str()));
+        match existing {
+            Some(item) => merge_json(item, patch),
+            None => {
+                let mut item = patch;
+                if let serde_json::Value::Object(fields) = &mut item {
+                    fields.insert("id".to_string(), serde_json::Value::String(id));
+                }
+                items.push(item);
+            }
+        }
+    }
+}
+
+/// 深度合并：对象逐键合并，其他类型直接覆盖
+fn merge_json(target: &mut serde_json::Value, patch: serde_json::Value) {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// 递归比较两个 JSON 值，记录不同的字段路径
+/// 颜色值整体视为一个字段，不再展开到 r/g/b
+fn collect_value_diffs(
+    path: &str,
+    current: &serde_json::Value,
+    base: &serde_json::Value,
+    out: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    match (current, base) {
+        (Value::Object(a), Value::Object(b)) if !is_color(current) && !is_color(base) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => collect_value_diffs(&child, x, y, out),
+                    _ => out.push(child),
+                }
+            }
+        }
+        _ => {
+            if current != base {
+                out.push(path.to_string());
+            }
+        }
+    }
+}
+
+fn is_color(value: &serde_json::Value) -> bool {
+    serde_json::from_value::<AnsiColor>(value.clone()).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statusline::style::AnsiColor;
+    use crate::statusline::style::ColorSupport;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        for name in crate::statusline::themes::THEME_NAMES {
+            assert!(ThemePresets::get_theme(name).validate().is_ok(), "{name}");
+        }
+
+        let mut config = ThemePresets::get_powerline_dark();
+        config.separator = String::new();
+        config.order.push(SegmentId::Model);
+        let git = &mut config.segments.get_mut(SegmentId::Git).options;
+        git.insert("show_stahs".to_string(), serde_json::json!(true));
+        git.insert("dirty_color".to_string(), serde_json::json!("#ff88zz"));
+        git.insert(
+            "clean_color".to_string(),
+            serde_json::json!({"r": 0, "g": 200, "b": 0}),
+        );
+        git.insert("cache_ms".to_string(), serde_json::json!(500));
+
+        let messages: Vec<String> = config
+            .validate()
+            .expect_err("invalid config")
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "style = Powerline 时 separator 不能为空".to_string(),
+                "order 中 segment id \"model\" 出现了多次".to_string(),
+                "segments.git.options.dirty_color 不是有效的颜色: \"#ff88zz\"".to_string(),
+                "segments.git.options.show_stahs 不是已知选项".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_theme_keeps_locked_colors() {
+        let custom_blue = AnsiColor::rgb(0, 0, 255);
+        let mut config = ThemePresets::get_cometix();
+        config.segments.get_mut(SegmentId::Git).colors.text = Some(custom_blue);
+        config.segments.get_mut(SegmentId::Git).lock_colors = true;
+
+        config.apply_theme("gruvbox");
+
+        let gruvbox = ThemePresets::get_theme("gruvbox");
+        assert_eq!(config.theme, "gruvbox");
+        assert_eq!(
+            config.segments.get(SegmentId::Git).colors.text,
+            Some(custom_blue)
+        );
+        assert!(config.segments.get(SegmentId::Git).lock_colors);
+        assert_eq!(
+            config.segments.get(SegmentId::Model).colors.text,
+            gruvbox.segments.get(SegmentId::Model).colors.text
+        );
+    }
+
+    #[test]
+    fn test_diff_from_theme_reports_changed_color() {
+        let mut config = ThemePresets::get_theme("cometix");
+        assert_eq!(config.diff_from_theme(), Vec::<String>::new());
+
+        config.segments.get_mut(SegmentId::Git).colors.text = Some(AnsiColor::rgb(1, 2, 3));
+        assert_eq!(
+            config.diff_from_theme(),
+            vec!["segments.git.colors.text".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_disables_git_only() {
+        let mut config = ThemePresets::get_theme("cometix");
+        config
+            .apply_patch(serde_json::json!({ "segments": { "git": { "enabled": false } } }))
+            .expect("patch applies");
+
+        assert!(!config.segments.get(SegmentId::Git).enabled);
+        assert_eq!(
+            config.diff_from_theme(),
+            vec!["segments.git.enabled".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_overlay_git_color_onto_gruvbox() {
+        let base = ThemePresets::get_theme("gruvbox");
+        let accent: PartialCxLineConfig = serde_json::from_value(serde_json::json!({
+            "segments": { "git": { "colors": { "text": { "c256": 196 } } } }
+        }))
+        .expect("partial config");
+
+        let config = base.overlay(&accent);
+        assert_eq!(
+            config.segments.get(SegmentId::Git).colors.text,
+            Some(AnsiColor::c256(196))
+        );
+        // 未覆盖的字段保持基础主题的值
+        assert_eq!(
+            config.segments.get(SegmentId::Git).colors.icon,
+            base.segments.get(SegmentId::Git).colors.icon
+        );
+        assert_eq!(config.separator, base.separator);
+        assert_eq!(
+            config.diff_from_theme(),
+            vec!["segments.git.colors.text".to_string()]
+        );
+        // 基础配置不受影响
+        assert_eq!(
+            base.segments.get(SegmentId::Git).colors.text,
+            Some(AnsiColor::c256(109))
+        );
+    }
+
+    #[test]
+    fn test_overlay_top_level_and_options() {
+        let base = ThemePresets::get_theme("gruvbox");
+        let mut accent = PartialCxLineConfig {
+            separator: Some(" > ".to_string()),
+            ..Default::default()
+        };
+        let mut git = PartialSegmentItemConfig::default();
+        git.options
+            .insert("show_branch".to_string(), serde_json::json!(false));
+        accent.segments.insert(SegmentId::Git, git);
+
+        let config = base.overlay(&accent);
+        assert_eq!(config.separator, " > ");
+        assert_eq!(config.style, base.style);
+        assert_eq!(
+            config
+                .segments
+                .get(SegmentId::Git)
+                .option_bool("show_branch"),
+            Some(false)
+        );
+        assert!(config.segments.get(SegmentId::Git).enabled);
+    }
+
+    #[test]
+    fn test_overlay_theme_alignment_and_auto_hide() {
+        let base = ThemePresets::get_theme("gruvbox");
+        let accent: PartialCxLineConfig = serde_json::from_value(serde_json::json!({
+            "theme": "nord",
+            "overflow_indicator": "+",
+            "segments": {
+                "git": { "auto_hide_when": "git_clean", "alignment": "right" }
+            }
+        }))
+        .expect("partial config");
+
+        let config = base.overlay(&accent);
+        assert_eq!(config.theme, "nord");
+        assert_eq!(config.overflow_indicator.as_deref(), Some("+"));
+        let git = config.segments.get(SegmentId::Git);
+        assert_eq!(git.auto_hide_when, Some(AutoHideRule::GitClean));
+        assert_eq!(git.alignment, SegmentAlignment::Right);
+        assert_eq!(
+            config.segments.get(SegmentId::Model).alignment,
+            SegmentAlignment::Left
+        );
+    }
+
+    #[test]
+    fn test_resolved_paths() {
+        let dir = PathBuf::from("/home/me/.codex/cxline");
+        let paths = ResolvedPaths::resolve(Some(dir.clone()), None);
+        assert_eq!(paths.config, Some(dir.join("config.toml")));
+        assert_eq!(paths.themes_dir, Some(dir.join("themes")));
+        assert_eq!(paths.favorites, Some(dir.join("favorites.toml")));
+        assert_eq!(paths.git_bases, Some(dir.join("git_bases.json")));
+
+        // 设置 CODEX_CXLINE_CONFIG 时只替换配置文件路径
+        let custom = PathBuf::from("/etc/cxline.toml");
+        let paths = ResolvedPaths::resolve(Some(dir.clone()), Some(custom.clone()));
+        assert_eq!(paths.config, Some(custom.clone()));
+        assert_eq!(paths.themes_dir, Some(dir.join("themes")));
+
+        let paths = ResolvedPaths::resolve(None, Some(custom.clone()));
+        assert_eq!(paths.config, Some(custom));
+        assert_eq!(paths.themes_dir, None);
+    }
+
+    #[test]
+    fn test_apply_patch_separator() {
+        let mut config = ThemePresets::get_theme("cometix");
+        config
+            .apply_patch(serde_json::json!({ "separator": " > " }))
+            .expect("patch applies");
+        assert_eq!(config.separator, " > ");
+        assert!(config.segments.get(SegmentId::Git).enabled);
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_type_mismatch() {
+        let mut config = ThemePresets::get_theme("cometix");
+        let err = config
+            .apply_patch(serde_json::json!({ "segments": { "git": { "enabled": "no" } } }))
+            .expect_err("type mismatch");
+        assert!(matches!(err, ConfigError::InvalidPatch(_)));
+        assert!(config.segments.get(SegmentId::Git).enabled);
+
+        assert!(matches!(
+            config.apply_patch(serde_json::json!([1, 2])),
+            Err(ConfigError::PatchNotObject)
+        ));
+    }
+
+    #[test]
+    fn test_for_capabilities_picks_theme() {
+        let pick = |nerd_font, truecolor| {
+            let config = CxLineConfig::for_capabilities(TerminalCapabilities {
+                nerd_font,
+                color_support: if truecolor {
+                    ColorSupport::TrueColor
+                } else {
+                    ColorSupport::Ansi16
+                },
+            });
+            (config.theme, config.style)
+        };
+
+        assert_eq!(
+            pick(true, true),
+            ("powerline-dark".to_string(), StyleMode::Powerline)
+        );
+        assert_eq!(
+            pick(true, false),
+            ("cometix".to_string(), StyleMode::NerdFont)
+        );
+        assert_eq!(pick(false, true), ("minimal".to_string(), StyleMode::Plain));
+    }
+
+    #[test]
+    fn test_reset_to_theme_restores_pristine_theme() {
+        let mut config = ThemePresets::get_theme("gruvbox");
+        config.enabled = false;
+        config.separator = " > ".to_string();
+        config.segments.get_mut(SegmentId::Git).colors.text = Some(AnsiColor::rgb(1, 2, 3));
+        config.segments.get_mut(SegmentId::Git).lock_colors = true;
+        config.segments.get_mut(SegmentId::Model).enabled = false;
+        config.bubble = !config.bubble;
+        config.tight_pills = !config.tight_pills;
+        config.order.reverse();
+        config.overflow_indicator = Some("…".to_string());
+        config.command_cache_ms = 5000;
+
+        config.reset_to_theme();
+
+        let mut expected = ThemePresets::get_theme("gruvbox");
+        expected.enabled = false;
+        assert_eq!(
+            serde_json::to_value(&config).expect("serialize"),
+            serde_json::to_value(&expected).expect("serialize")
+        );
+    }
+
+    #[test]
+    fn test_unserializable_option_names_key() {
+        let mut config = CxLineConfig::default();
+        config
+            .segments
+            .get_mut(SegmentId::Git)
+            .options
+            .insert("labels".to_string(), serde_json::json!(["a", null]));
+
+        let err = config.to_toml_string().expect_err("null is not valid TOML");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string().starts_with("segments.git.options.labels"),
+            "{err}"
+        );
+
+        config
+            .segments
+            .get_mut(SegmentId::Git)
+            .options
+            .remove("labels");
+        assert!(config.to_toml_string().is_ok());
+    }
+
+    #[test]
+    fn test_rgb_input_layout_round_trip() {
+        let toml = "[rgb_input_layout]\nhex_first = true\n";
+        let config: CxLineConfig = toml::from_str(toml).expect("parse");
+        assert_eq!(
+            config.rgb_input_layout,
+            RgbInputLayout {
+                hex_first: true,
+                hex_only: false,
+            }
+        );
+
+        let saved = config.to_toml_string().expect("serialize");
+        let reloaded: CxLineConfig = toml::from_str(&saved).expect("reload");
+        assert_eq!(reloaded.rgb_input_layout, config.rgb_input_layout);
+
+        // 默认布局不写入配置文件
+        let saved = ThemePresets::get_default()
+            .to_toml_string()
+            .expect("serialize");
+        assert!(!saved.contains("rgb_input_layout"));
+    }
+
+    #[test]
+    fn test_unknown_keys_survive_round_trip() {
+        let toml = "future_flag = true\n\n[segments.model]\nid = \"model\"\nfoo = 1\n";
+        let config: CxLineConfig = toml::from_str(toml).expect("parse");
+        assert_eq!(
+            config.segments.get(SegmentId::Model).extra.get("foo"),
+            Some(&toml::Value::Integer(1))
+        );
+
+        let saved = config.to_toml_string().expect("serialize");
+        let reparsed: toml::Table = toml::from_str(&saved).expect("reparse");
+        assert_eq!(
+            reparsed.get("future_flag"),
+            Some(&toml::Value::Boolean(true))
+        );
+        assert_eq!(
+            reparsed
+                .get("segments")
+                .and_then(|segments| segments.get("model"))
+                .and_then(|model| model.get("foo")),
+            Some(&toml::Value::Integer(1))
+        );
+    }
+
+    #[test]
+    fn test_segments_table_and_array_shapes() {
+        // 旧的表结构：缺少的 segment 使用默认配置，未知的 segment 保存时原样写回
+        let old = "[segments.git]\nenabled = false\n\n[segments.future]\nenabled = true\n";
+        let mut config: CxLineConfig = toml::from_str(old).expect("table shape");
+        assert!(!config.get_segment_config(SegmentId::Git).enabled);
+        assert_eq!(config.segments.iter().count(), SegmentId::ALL.len());
+        let saved = config.to_toml_string().expect("serialize");
+        assert!(saved.contains("[segments.git]"), "{saved}");
+        assert!(saved.contains("[segments.future]"), "{saved}");
+
+        // 切换为数组结构后未知的 segment 同样保留
+        let extra = config.get_segment_config(SegmentId::Git).clone();
+        config.segments.push(extra);
+        let saved = config.to_toml_string().expect("serialize");
+        let reloaded: CxLineConfig = toml::from_str(&saved).expect("reload");
+        let saved = reloaded.to_toml_string().expect("serialize");
+        assert!(saved.contains("id = \"future\""), "{saved}");
+
+        // 数组结构：同一 id 可以出现多次
+        let new = "[[segments]]\nid = \"directory\"\noptions = { path = \"../a\" }\n\n\
+                   [[segments]]\nid = \"directory\"\noptions = { path = \"../b\" }\n";
+        let config: CxLineConfig = toml::from_str(new).expect("array shape");
+        let paths: Vec<_> = config
+            .segments
+            .instances(SegmentId::Directory)
+            .map(|segment| segment.option_str("path"))
+            .collect();
+        assert_eq!(paths, vec![Some("../a"), Some("../b")]);
+        assert_eq!(
+            config
+                .get_segment_config(SegmentId::Directory)
+                .option_str("path"),
+            Some("../a")
+        );
+        assert!(config.get_segment_config(SegmentId::Model).enabled);
+
+        let saved = config.to_toml_string().expect("serialize");
+        assert!(saved.contains("[[segments]]"), "{saved}");
+        let reloaded: CxLineConfig = toml::from_str(&saved).expect("reload");
+        assert_eq!(reloaded.segments.instances(SegmentId::Directory).count(), 2);
+    }
+
+    #[test]
+    fn test_downgrade_to_256() {
+        let mut config = ThemePresets::get_theme("powerline-dark");
+        let git = config.segments.get_mut(SegmentId::Git);
+        git.colors.text = Some(AnsiColor::rgb(255, 255, 255));
+        git.colors.icon = Some(AnsiColor::c16(2));
+        git.options
+            .insert("dirty_color".to_string(), serde_json::json!("#000000"));
+        git.options.insert(
+            "branch_color_rules".to_string(),
+            serde_json::json!([
+                ["main", {"r": 255, "g": 0, "b": 0}],
+                ["feature/*", {"c16": 3}],
+            ]),
+        );
+
+        assert!(config.downgrade_to_256() > 0);
+        let git = config.get_segment_config(SegmentId::Git);
+        assert_eq!(git.colors.text, Some(AnsiColor::c256(231)));
+        assert_eq!(git.colors.icon, Some(AnsiColor::c16(2)));
+        assert_eq!(git.option_color("dirty_color"), Some(AnsiColor::c256(16)));
+        assert_eq!(
+            git.option::<Vec<(String, AnsiColor)>>("branch_color_rules"),
+            Some(vec![
+                ("main".to_string(), AnsiColor::c256(196)),
+                ("feature/*".to_string(), AnsiColor::c16(3)),
+            ])
+        );
+        assert!(config.segments.iter().all(|segment| {
+            [
+                segment.colors.icon,
+                segment.colors.text,
+                segment.colors.background,
+                segment.colors.badge,
+            ]
+            .iter()
+            .all(|color| !matches!(color, Some(AnsiColor::Rgb { .. })))
+        }));
+        assert_eq!(config.downgrade_to_256(), 0);
+    }
+
+    #[test]
+    fn test_command_cache_ttl() {
+        let mut config = CxLineConfig::default();
+        assert_eq!(
+            config.command_cache_ttl(SegmentId::Git),
+            Duration::from_millis(DEFAULT_COMMAND_CACHE_MS)
+        );
+
+        config.command_cache_ms = 500;
+        assert_eq!(
+            config.command_cache_ttl(SegmentId::Git),
+            Duration::from_millis(500)
+        );
+
+        config
+            .segments
+            .get_mut(SegmentId::Git)
+            .options
+            .insert("cache_ms".to_string(), serde_json::json!(10_000));
+        assert_eq!(
+            config.command_cache_ttl(SegmentId::Git),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn test_separator_style_defaults() {
+        let config: CxLineConfig = toml::from_str("style = \"plain\"").expect("parse");
+        assert!(config.separator_dim);
+        assert_eq!(config.separator_color, None);
+
+        let toml = toml::to_string(&config).expect("toml");
+        assert!(!toml.contains("separator_dim"), "{toml}");
+
+        let config: CxLineConfig =
+            toml::from_str("separator_dim = false\nseparator_color = { c16 = 15 }").expect("parse");
+        assert!(!config.separator_dim);
+        assert_eq!(config.separator_color, Some(AnsiColor::c16(15)));
+    }
+
+    #[test]
+    fn test_patch_with_duplicate_segment_ids() {
+        let mut config = ThemePresets::get_default();
+        let mut second = config.get_segment_config(SegmentId::Directory).clone();
+        second
+            .options
+            .insert("path".to_string(), serde_json::json!("../other"));
+        config.segments.push(second);
+        config.segments.get_mut(SegmentId::Model).enabled = false;
+
+        config
+            .apply_patch(serde_json::json!({"segments": {"git": {"enabled": false}}}))
+            .expect("patch");
+
+        assert!(!config.get_segment_config(SegmentId::Git).enabled);
+        // 其他 segment 的修改和重复实例都保留
+        assert!(!config.get_segment_config(SegmentId::Model).enabled);
+        let paths: Vec<_> = config
+            .segments
+            .instances(SegmentId::Directory)
+            .map(|segment| segment.option_str("path"))
+            .collect();
+        assert_eq!(paths, vec![None, Some("../other")]);
+    }
+
+    #[test]
+    fn test_usage_secondary_prefix_comes_from_preset() {
+        let config = ThemePresets::get_default();
+        assert_eq!(
+            config
+                .get_segment_config(SegmentId::Usage)
+                .secondary_prefix(),
+            "· "
+        );
+        assert_eq!(
+            config.get_segment_config(SegmentId::Git).secondary_prefix(),
+            ""
+        );
+
+        // 旧配置没有 secondary_prefix 键时沿用预设值，显式的空串保持不变
+        let old: CxLineConfig = toml::from_str("[segments.usage]\nenabled = true\n").expect("old");
+        assert_eq!(
+            old.get_segment_config(SegmentId::Usage).secondary_prefix(),
+            "· "
+        );
+        let cleared: CxLineConfig =
+            toml::from_str("[segments.usage]\nsecondary_prefix = \"\"\n").expect("cleared");
+        assert_eq!(
+            cleared
+                .get_segment_config(SegmentId::Usage)
+                .secondary_prefix(),
+            ""
+        );
     }
 }
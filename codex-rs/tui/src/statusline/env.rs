@@ -0,0 +1,161 @@
+// 环境变量插值
+// 用于自定义字符串（前缀/后缀、命令模板等）中的 "$USER" 风格展开
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// 未定义变量的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownVar {
+    /// 替换为空字符串
+    #[default]
+    Empty,
+    /// 保留原文，例如 "$MISSING"
+    Verbatim,
+}
+
+/// 使用当前进程环境展开 `${VAR}` / `$VAR`，`$$` 表示字面量 `$`
+/// 未定义的变量替换为空字符串
+pub fn expand_env(s: &str) -> String {
+    expand_env_with(s, UnknownVar::Empty, |name| std::env::var(name).ok())
+}
+
+/// 使用自定义查找函数展开变量
+/// 只扫描一遍输入，替换结果不会再次展开，避免循环引用导致无限展开
+pub fn expand_env_with(
+    s: &str,
+    unknown: UnknownVar,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        // $$ -> $
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+            continue;
+        }
+
+        // ${VAR}
+        if let Some(braced) = after.strip_prefix('{') {
+            let Some(end) = braced.find('}') else {
+                // 没有闭合括号，原样保留剩余部分
+                out.push_str(&rest[pos..]);
+                return out;
+            };
+            let name = &braced[..end];
+            let raw = &rest[pos..pos + end + 3];
+            push_var(&mut out, name, raw, unknown, &lookup);
+            rest = &braced[end + 1..];
+            continue;
+        }
+
+        // $VAR
+        let name_len = var_name_len(after);
+        if name_len == 0 {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        let name = &after[..name_len];
+        let raw = &rest[pos..pos + name_len + 1];
+        push_var(&mut out, name, raw, unknown, &lookup);
+        rest = &after[name_len..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn push_var(
+    out: &mut String,
+    name: &str,
+    raw: &str,
+    unknown: UnknownVar,
+    lookup: &impl Fn(&str) -> Option<String>,
+) {
+    match lookup(name) {
+        Some(value) => out.push_str(&value),
+        None => {
+            if unknown == UnknownVar::Verbatim {
+                out.push_str(raw);
+            }
+        }
+    }
+}
+
+/// 变量名长度：首字符为字母或下划线，其后为字母、数字或下划线
+fn var_name_len(s: &str) -> usize {
+    let mut len = 0;
+    for (i, c) in s.char_indices() {
+        let valid = if i == 0 {
+            c.is_ascii_alphabetic() || c == '_'
+        } else {
+            c.is_ascii_alphanumeric() || c == '_'
+        };
+        if !valid {
+            break;
+        }
+        len = i + c.len_utf8();
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "USER" => Some("alice".to_string()),
+            // 值中包含变量引用，不应再次展开
+            "LOOP" => Some("$LOOP".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand_set_var() {
+        assert_eq!(
+            expand_env_with("hi $USER!", UnknownVar::Empty, lookup),
+            "hi alice!"
+        );
+        assert_eq!(
+            expand_env_with("${USER}_box", UnknownVar::Empty, lookup),
+            "alice_box"
+        );
+        assert_eq!(expand_env_with("$LOOP", UnknownVar::Empty, lookup), "$LOOP");
+    }
+
+    #[test]
+    fn test_expand_unset_var() {
+        assert_eq!(
+            expand_env_with("[$MISSING]", UnknownVar::Empty, lookup),
+            "[]"
+        );
+        assert_eq!(
+            expand_env_with("[$MISSING ${NOPE}]", UnknownVar::Verbatim, lookup),
+            "[$MISSING ${NOPE}]"
+        );
+    }
+
+    #[test]
+    fn test_expand_escaped_dollar() {
+        assert_eq!(
+            expand_env_with("cost: $$5 $USER", UnknownVar::Empty, lookup),
+            "cost: $5 alice"
+        );
+        assert_eq!(expand_env("$$HOME"), "$HOME");
+        assert_eq!(
+            expand_env_with("50$ ${", UnknownVar::Empty, lookup),
+            "50$ ${"
+        );
+    }
+}
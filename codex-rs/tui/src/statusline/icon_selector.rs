@@ -16,6 +16,9 @@ use ratatui::widgets::Widget;
 use super::color_picker::centered_rect;
 use super::style::StyleMode;
 
+/// 网格中每个图标单元的显示宽度
+const ICON_CELL_WIDTH: usize = 5;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum IconStyle {
     Plain,
@@ -37,6 +40,8 @@ pub struct IconSelector {
     pub custom_input: String,
     pub editing_custom: bool,
     pub current_icon: Option<String>,
+    /// 上次渲染时网格的列数（用于上下移动）
+    pub cached_cols: usize,
 }
 
 impl Default for IconSelector {
@@ -49,6 +54,7 @@ impl Default for IconSelector {
             custom_input: String::new(),
             editing_custom: false,
             current_icon: None,
+            cached_cols: 8,
         }
     }
 }
@@ -58,7 +64,9 @@ impl IconSelector {
         self.is_open = true;
         self.icon_style = match current_style {
             StyleMode::Plain => IconStyle::Plain,
-            StyleMode::NerdFont | StyleMode::Powerline => IconStyle::NerdFont,
+            StyleMode::NerdFont | StyleMode::Powerline | StyleMode::PowerlineSeparated => {
+                IconStyle::NerdFont
+            }
         };
         self.editing_custom = false;
         self.custom_input.clear();
@@ -104,28 +112,54 @@ impl IconSelector {
         }
     }
 
+    /// 左右移动，越过首尾时循环
     pub fn move_selection(&mut self, delta: i32) {
         if self.editing_custom {
             return;
         }
 
-        match self.icon_style {
-            IconStyle::Plain => {
-                let icons = get_plain_icons();
-                let new_selection =
-                    (self.selected_plain as i32 + delta).clamp(0, icons.len() as i32 - 1) as usize;
-                self.selected_plain = new_selection;
-            }
-            IconStyle::NerdFont => {
-                let icons = get_nerd_font_icons();
-                let new_selection =
-                    (self.selected_nerd as i32 + delta).clamp(0, icons.len() as i32 - 1) as usize;
-                self.selected_nerd = new_selection;
-            }
+        let count = self.icons().len() as i32;
+        let selected = self.selected_mut();
+        *selected = (*selected as i32 + delta).rem_euclid(count) as usize;
+        self.update_current_icon();
+    }
+
+    /// 上下移动一行，保持列不变，越过首尾行时循环
+    pub fn move_selection_vertical(&mut self, delta: i32) {
+        if self.editing_custom {
+            return;
+        }
+
+        let count = self.icons().len();
+        let cols = self.cached_cols.max(1);
+        let total_rows = count.div_ceil(cols) as i32;
+        let selected = self.selected_mut();
+        let col = *selected % cols;
+        let row = (*selected / cols) as i32;
+
+        let mut new_row = (row + delta).rem_euclid(total_rows);
+        // 最后一行可能不满，该列不存在时继续按方向跳过
+        while new_row as usize * cols + col >= count {
+            new_row = (new_row + delta.signum()).rem_euclid(total_rows);
         }
+        *selected = new_row as usize * cols + col;
         self.update_current_icon();
     }
 
+    fn icons(&self) -> Vec<IconInfo> {
+        match self.icon_style {
+            IconStyle::Plain => get_plain_icons(),
+            IconStyle::NerdFont => get_nerd_font_icons(),
+        }
+    }
+
+    fn selected_mut(&mut self) -> &mut usize {
+        match self.icon_style {
+            IconStyle::Plain => &mut self.selected_plain,
+            IconStyle::NerdFont => &mut self.selected_nerd,
+        }
+    }
+
     fn update_current_icon(&mut self) {
         match self.icon_style {
             IconStyle::Plain => {
@@ -147,7 +181,7 @@ impl IconSelector {
         self.current_icon.clone()
     }
 
-    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
         if !self.is_open {
             return;
         }
@@ -188,28 +222,47 @@ impl IconSelector {
         let list_inner = block.inner(list_area);
         block.render(list_area, buf);
 
-        let icons = match self.icon_style {
-            IconStyle::Plain => get_plain_icons(),
-            IconStyle::NerdFont => get_nerd_font_icons(),
-        };
-        let selected = match self.icon_style {
-            IconStyle::Plain => self.selected_plain,
-            IconStyle::NerdFont => self.selected_nerd,
-        };
+        let icons = self.icons();
+        let selected = *self.selected_mut();
 
-        let visible_rows = list_inner.height as usize;
-        let start_idx = selected.saturating_sub(visible_rows / 2);
+        // 网格布局：列数由可用宽度决定，最后一行显示选中图标名称
+        let cols = (list_inner.width as usize / ICON_CELL_WIDTH).max(1);
+        self.cached_cols = cols;
 
-        for (i, icon_info) in icons.iter().enumerate().skip(start_idx).take(visible_rows) {
-            let y = list_inner.y + (i - start_idx) as u16;
-            let is_selected = i == selected;
-            let style = if is_selected {
+        let visible_rows = (list_inner.height as usize).saturating_sub(1).max(1);
+        let selected_row = selected / cols;
+        let start_row = (selected_row / visible_rows) * visible_rows;
+
+        for (i, icon_info) in icons
+            .iter()
+            .enumerate()
+            .skip(start_row * cols)
+            .take(visible_rows * cols)
+        {
+            let row = i / cols - start_row;
+            let col = i % cols;
+            let x = list_inner.x + (col * ICON_CELL_WIDTH) as u16;
+            let y = list_inner.y + row as u16;
+            let style = if i == selected {
                 Style::default().add_modifier(Modifier::REVERSED)
             } else {
                 Style::default()
             };
-            let text = format!("{} {}", icon_info.icon, icon_info.name);
-            buf.set_string(list_inner.x, y, &text, style);
+            buf.set_string(x, y, format!(" {} ", icon_info.icon), style);
+        }
+
+        if let Some(icon_info) = icons.get(selected)
+            && list_inner.height > 1
+        {
+            let y = list_inner.y + list_inner.height - 1;
+            let name = format!("{} {}", icon_info.icon, icon_info.name);
+            buf.set_stringn(
+                list_inner.x,
+                y,
+                name,
+                list_inner.width as usize,
+                Style::default().fg(Color::Cyan),
+            );
         }
 
         // Custom input
@@ -232,7 +285,7 @@ impl IconSelector {
         let help = if self.editing_custom {
             "[Enter] Confirm  [Esc] Cancel"
         } else {
-            "[Enter] Select  [Tab] Switch  [c] Custom  [Esc] Cancel"
+            "[←↑↓→] Move  [Enter] Select  [Tab] Switch  [c] Custom  [Esc] Cancel"
         };
         Paragraph::new(help)
             .block(Block::default().borders(Borders::ALL))
@@ -373,3 +426,46 @@ pub fn get_nerd_font_icons() -> Vec<IconInfo> {
         },
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn selector(cols: usize) -> IconSelector {
+        let mut selector = IconSelector::default();
+        selector.open(StyleMode::Plain);
+        selector.cached_cols = cols;
+        selector
+    }
+
+    #[test]
+    fn test_horizontal_navigation_wraps() {
+        let mut selector = selector(4);
+        selector.move_selection(-1);
+        assert_eq!(selector.selected_plain, get_plain_icons().len() - 1);
+        selector.move_selection(1);
+        assert_eq!(selector.selected_plain, 0);
+        selector.move_selection(3);
+        assert_eq!(selector.selected_plain, 3);
+    }
+
+    #[test]
+    fn test_vertical_navigation_wraps() {
+        // 16 个 emoji 图标，5 列 -> 4 行，最后一行只有第 0 列
+        let mut selector = selector(5);
+        selector.move_selection_vertical(1);
+        assert_eq!(selector.selected_plain, 5);
+
+        selector.move_selection_vertical(-2);
+        assert_eq!(selector.selected_plain, 15);
+        selector.move_selection_vertical(1);
+        assert_eq!(selector.selected_plain, 0);
+
+        // 第 2 列在最后一行不存在，向上回绕时跳到第 2 行
+        selector.selected_plain = 2;
+        selector.move_selection_vertical(-1);
+        assert_eq!(selector.selected_plain, 12);
+        assert_eq!(selector.get_selected_icon().as_deref(), Some("💎"));
+    }
+}
@@ -1,8 +1,10 @@
 // Codex TUI 状态栏模块
 // 参考 CCometixLine 设计
 
+pub mod capability;
 pub mod color_picker;
 pub mod config;
+pub mod env;
 pub mod icon_selector;
 pub mod name_input;
 pub mod renderer;
@@ -11,23 +13,31 @@ pub mod segments;
 pub mod separator_editor;
 pub mod style;
 pub mod themes;
+pub mod truncate;
 
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
 
 use codex_protocol::openai_models::ReasoningEffort;
 
 pub use color_picker::ColorPicker;
 pub use color_picker::ColorTarget;
+pub use color_picker::RgbInputLayout;
 pub use config::CxLineConfig;
+pub use config::LoadedConfig;
 pub use icon_selector::IconSelector;
 pub use name_input::NameInputDialog;
+pub use renderer::StatusLineEvent;
 pub use renderer::StatusLineRenderer;
 pub use renderer::StatusLineWidget;
 pub use segment::Segment;
+pub use segment::SegmentAction;
 pub use segment::SegmentData;
 pub use segment::SegmentId;
 pub use segment::SegmentStyle;
 pub use separator_editor::SeparatorEditor;
+pub use style::PowerlineGlyph;
 pub use style::StyleMode;
 
 /// Git 预览数据（用于配置页预览）
@@ -37,6 +47,50 @@ pub struct GitPreviewData {
     pub status: String,
     pub ahead: u32,
     pub behind: u32,
+    pub commit_count: Option<u32>,
+    /// 工作区变更摘要，例如 "1 staged, 2 modified"
+    pub summary: Option<String>,
+    /// 冲突文件数（大于 0 时以徽标显示）
+    pub conflicts: u32,
+    /// 相对各远端同名分支的 ahead/behind
+    pub remotes: Vec<RemoteDivergence>,
+    /// 仓库生效的 user.email（仅在 show_identity 开启时读取）
+    pub identity: Option<String>,
+    /// HEAD 的完整提交 SHA（空仓库没有提交时为 None）
+    pub head_sha: Option<String>,
+    /// 相对记录的 base 提交的 ahead/behind（仅在 show_base 开启且已记录时）
+    pub base: Option<BaseDivergence>,
+    /// stash 条目数（仅在 show_stash 开启时读取）
+    pub stash: u32,
+    /// 未跟踪文件数
+    pub untracked: u32,
+}
+
+/// HEAD 相对记录的 base 提交的分叉情况
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseDivergence {
+    pub sha: String,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+/// 当前分支相对某个远端同名分支的分叉情况
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteDivergence {
+    pub remote: String,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+/// Jj 预览数据（用于配置页预览）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JjPreviewData {
+    /// 当前 change id（最短唯一前缀）
+    pub change_id: String,
+    /// 状态符号：✓ 空 change，● 有修改，⚠ 有冲突
+    pub status: String,
+    /// 描述首行
+    pub description: String,
 }
 
 /// 状态栏数据上下文
@@ -66,8 +120,32 @@ pub struct StatusLineContext<'a> {
     /// Weekly Rate limit 重置时间
     pub weekly_rate_limit_resets_at: Option<String>,
 
+    /// Weekly Rate limit 重置时刻（Unix 秒），Relative/Clock 格式优先使用
+    pub weekly_rate_limit_resets_at_timestamp: Option<i64>,
+
+    /// 本次会话中 Codex 修改过的文件数
+    pub session_edited_files: Option<usize>,
+
+    /// 会话剩余 token 预算
+    pub token_budget_remaining: Option<i64>,
+
+    /// 会话 token 预算总量（用于计算剩余占比）
+    pub token_budget_total: Option<i64>,
+
     /// Git 预览数据（用于配置页预览，覆盖实际 git 检测）
     pub git_preview: Option<GitPreviewData>,
+
+    /// Jj 预览数据（用于配置页预览，覆盖实际 jj 检测）
+    pub jj_preview: Option<JjPreviewData>,
+
+    /// 是否检查 cwd 仍然存在（配置页预览使用虚构路径时关闭）
+    pub check_cwd: bool,
+
+    /// 本次渲染临时替换的主要内容（如 "saved!"），不影响 metadata
+    pub overrides: HashMap<SegmentId, String>,
+
+    /// 配置加载失败的原因（已回退到默认配置）
+    pub config_error: Option<String>,
 }
 
 impl<'a> StatusLineContext<'a> {
@@ -81,7 +159,15 @@ impl<'a> StatusLineContext<'a> {
             hourly_rate_limit_percent: None,
             weekly_rate_limit_percent: None,
             weekly_rate_limit_resets_at: None,
+            weekly_rate_limit_resets_at_timestamp: None,
+            session_edited_files: None,
+            token_budget_remaining: None,
+            token_budget_total: None,
             git_preview: None,
+            jj_preview: None,
+            check_cwd: true,
+            overrides: HashMap::new(),
+            config_error: None,
         }
     }
 
@@ -108,6 +194,29 @@ impl<'a> StatusLineContext<'a> {
         self
     }
 
+    /// 设置周限重置的精确时刻（Unix 秒），避免从截断到小时的文本反推
+    pub fn with_rate_limit_reset_timestamp(mut self, timestamp: Option<i64>) -> Self {
+        self.weekly_rate_limit_resets_at_timestamp = timestamp;
+        self
+    }
+
+    /// 设置配置加载错误，由 WarningSegment 显示
+    pub fn with_config_error(mut self, error: Option<String>) -> Self {
+        self.config_error = error;
+        self
+    }
+
+    pub fn with_session_edited_files(mut self, count: Option<usize>) -> Self {
+        self.session_edited_files = count;
+        self
+    }
+
+    pub fn with_token_budget(mut self, remaining: Option<i64>, total: Option<i64>) -> Self {
+        self.token_budget_remaining = remaining;
+        self.token_budget_total = total;
+        self
+    }
+
     /// 设置 Git 预览数据（用于配置页预览）
     pub fn with_git_preview(mut self, branch: &str, status: &str, ahead: u32, behind: u32) -> Self {
         self.git_preview = Some(GitPreviewData {
@@ -115,6 +224,38 @@ impl<'a> StatusLineContext<'a> {
             status: status.to_string(),
             ahead,
             behind,
+            commit_count: None,
+            summary: None,
+            conflicts: 0,
+            remotes: Vec::new(),
+            identity: None,
+            head_sha: None,
+            base: None,
+            stash: 0,
+            untracked: 0,
+        });
+        self
+    }
+}
+
+impl<'a> StatusLineContext<'a> {
+    pub fn with_cwd_check(mut self, check: bool) -> Self {
+        self.check_cwd = check;
+        self
+    }
+
+    /// 临时替换某个 segment 的主要内容，仅对使用该 context 的渲染生效
+    pub fn with_override(mut self, id: SegmentId, text: impl Into<String>) -> Self {
+        self.overrides.insert(id, text.into());
+        self
+    }
+
+    /// 设置 Jj 预览数据（用于配置页预览）
+    pub fn with_jj_preview(mut self, change_id: &str, status: &str, description: &str) -> Self {
+        self.jj_preview = Some(JjPreviewData {
+            change_id: change_id.to_string(),
+            status: status.to_string(),
+            description: description.to_string(),
         });
         self
     }
@@ -127,6 +268,26 @@ impl GitPreviewData {
             status: String::new(),
             ahead: 0,
             behind: 0,
+            commit_count: None,
+            summary: None,
+            conflicts: 0,
+            remotes: Vec::new(),
+            identity: None,
+            head_sha: None,
+            base: None,
+            stash: 0,
+            untracked: 0,
+        }
+    }
+}
+
+impl JjPreviewData {
+    /// 不在 jj 仓库中：jj segment 隐藏，git segment 照常显示
+    pub fn empty() -> Self {
+        Self {
+            change_id: String::new(),
+            status: String::new(),
+            description: String::new(),
         }
     }
 }
@@ -137,55 +298,291 @@ pub fn build_statusline<'a>(
     config: &'a CxLineConfig,
     ctx: &StatusLineContext<'_>,
 ) -> StatusLineRenderer<'a> {
-    use segments::*;
-
     let mut renderer = StatusLineRenderer::new(config);
+    // 按 order 展开为启用的 segment 配置，同一 id 的多个配置按配置中的先后排列
+    let enabled: Vec<_> = config
+        .order
+        .iter()
+        .flat_map(|id| config.segments.instances(*id))
+        .filter(|segment_config| segment_config.enabled)
+        .collect();
+    // jj 仓库中 jj segment 替代 git segment；先收集 jj，使其不受 order 中的先后影响
+    let mut jj_data: Vec<Option<SegmentData>> = enabled
+        .iter()
+        .map(|segment_config| {
+            (segment_config.id == SegmentId::Jj)
+                .then(|| segments::collect_segment(config, segment_config, ctx))
+                .flatten()
+        })
+        .collect();
+    let in_jj_repo = jj_data.iter().any(Option::is_some);
 
-    // Model segment
-    if config.segments.model.enabled {
-        let segment = ModelSegment;
-        if let Some(data) = segment.collect(ctx) {
-            renderer.add_segment(SegmentId::Model, data);
+    for (index, segment_config) in enabled.into_iter().enumerate() {
+        let id = segment_config.id;
+        let collected = match id {
+            SegmentId::Jj => jj_data[index].take(),
+            SegmentId::Git if in_jj_repo => None,
+            _ => segments::collect_segment(config, segment_config, ctx),
+        };
+        let Some(mut data) = collected else {
+            continue;
+        };
+        // 临时消息需要被看到，不受自动隐藏规则影响
+        if let Some(text) = ctx.overrides.get(&id) {
+            data.primary = text.clone();
+            renderer.add_configured_segment(segment_config, data);
+        } else if !segment_config.should_hide(&data) {
+            renderer.add_configured_segment(segment_config, data);
         }
     }
 
-    // Directory segment
-    if config.segments.directory.enabled {
-        let segment = DirectorySegment;
-        if let Some(data) = segment.collect(ctx) {
-            renderer.add_segment(SegmentId::Directory, data);
-        }
+    renderer
+}
+
+/// 异步更新用的 Git 预览数据收集（避免在 render 中执行 git 命令）
+pub(crate) fn collect_git_preview(
+    cwd: &Path,
+    git_config: &config::SegmentItemConfig,
+) -> Option<GitPreviewData> {
+    // 轮询间隔即缓存时长，不再叠加 segment 自身的结果缓存
+    let segment = segments::GitSegment::from_config(git_config).with_cache_ttl(Duration::ZERO);
+    segment.collect_preview(cwd)
+}
+
+/// `/reset-base` 使用：将 cwd 所在仓库的当前 HEAD 记录为 git base，返回记录的 SHA
+/// git segment 未启用 show_base 时返回错误，不写入记录文件
+pub(crate) fn reset_git_base(
+    cwd: &Path,
+    git_config: &config::SegmentItemConfig,
+) -> std::io::Result<String> {
+    segments::GitSegment::from_config(git_config).reset_base(cwd)
+}
+
+/// 后台轮询使用：读取 jj 信息，不在 jj 仓库中时返回 None
+pub(crate) fn collect_jj_preview(
+    cwd: &Path,
+    jj_config: &config::SegmentItemConfig,
+) -> Option<JjPreviewData> {
+    let segment = segments::JjSegment::from_config(jj_config);
+    segment.collect_preview(cwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statusline::config::AutoHideRule;
+    use crate::statusline::themes::ThemePresets;
+
+    fn render_text(config: &CxLineConfig, ctx: &StatusLineContext<'_>) -> String {
+        build_statusline(config, ctx)
+            .render_line()
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
     }
 
-    // Git segment
-    if config.segments.git.enabled {
-        let segment = GitSegment;
-        if let Some(data) = segment.collect(ctx) {
-            renderer.add_segment(SegmentId::Git, data);
-        }
+    #[test]
+    fn test_set_all_segments_enabled() {
+        let mut config = ThemePresets::get_theme("minimal");
+        let ctx =
+            StatusLineContext::new("gpt-5", Path::new("/repo")).with_git_preview("main", "●", 0, 0);
+        assert!(config.any_enabled());
+
+        config.set_all_segments_enabled(false);
+        assert!(!config.any_enabled());
+        assert!(config.segments.iter().all(|segment| !segment.enabled));
+        assert!(build_statusline(&config, &ctx).segments().is_empty());
+
+        config.set_all_segments_enabled(true);
+        assert!(config.segments.iter().all(|segment| segment.enabled));
+        assert!(!build_statusline(&config, &ctx).segments().is_empty());
     }
 
-    // Context segment
-    if config.segments.context.enabled {
-        let segment = ContextSegment;
-        if let Some(data) = segment.collect(ctx) {
-            renderer.add_segment(SegmentId::Context, data);
-        }
+    #[test]
+    fn test_jj_preferred_over_git() {
+        let mut config = ThemePresets::get_theme("minimal");
+        let ctx = StatusLineContext::new("gpt-5", Path::new("/repo"))
+            .with_git_preview("main", "●", 0, 0)
+            .with_jj_preview("kxqzmtow", "●", "wip");
+
+        // 默认关闭 jj
+        let text = render_text(&config, &ctx);
+        assert!(text.contains("main"));
+        assert!(!text.contains("kxqzmtow"));
+
+        config.segments.get_mut(SegmentId::Jj).enabled = true;
+        let text = render_text(&config, &ctx);
+        assert!(text.contains("kxqzmtow ● wip"), "{text}");
+        assert!(!text.contains("main"));
+
+        // jj 排在 git 之后时同样替代 git
+        config.order.reverse();
+        let text = render_text(&config, &ctx);
+        assert!(text.contains("kxqzmtow"), "{text}");
+        assert!(!text.contains("main"));
+        config.order.reverse();
+
+        // 不在 jj 仓库中时回退到 git
+        let ctx = StatusLineContext::new("gpt-5", Path::new("/repo"))
+            .with_git_preview("main", "●", 0, 0)
+            .with_jj_preview("", "", "");
+        assert!(render_text(&config, &ctx).contains("main"));
     }
 
-    // Usage segment
-    if config.segments.usage.enabled {
-        let segment = UsageSegment;
-        if let Some(data) = segment.collect(ctx) {
-            renderer.add_segment(SegmentId::Usage, data);
-        }
+    #[test]
+    fn test_order_controls_segments() {
+        let mut config = ThemePresets::get_theme("minimal");
+        let ctx = StatusLineContext::new("gpt-5", Path::new("/repo"))
+            .with_cwd_check(false)
+            .with_context(Some(50000), Some(128000))
+            .with_git_preview("main", "●", 0, 0);
+        let ids = |config: &CxLineConfig| -> Vec<SegmentId> {
+            build_statusline(config, &ctx)
+                .segments()
+                .iter()
+                .map(|(id, _)| *id)
+                .collect()
+        };
+
+        let forward = ids(&config);
+        assert!(forward.len() > 2, "{forward:?}");
+        config.order.reverse();
+        let mut reversed = ids(&config);
+        reversed.reverse();
+        assert_eq!(reversed, forward);
+
+        let renderer = build_statusline(&config, &ctx);
+        let primary = |id: SegmentId| {
+            renderer
+                .segments()
+                .iter()
+                .find(|(segment_id, _)| *segment_id == id)
+                .map(|(_, data)| data.primary.clone())
+                .expect("segment present")
+        };
+        let text = render_text(&config, &ctx);
+        let model = text.find(&primary(SegmentId::Model)).expect("model span");
+        let git = text.find(&primary(SegmentId::Git)).expect("git span");
+        assert!(git < model, "{text}");
+
+        // 未列出的 segment 不显示，但配置仍可读取
+        config.order = vec![SegmentId::Git];
+        assert_eq!(ids(&config), vec![SegmentId::Git]);
+        assert!(config.get_segment_config(SegmentId::Model).enabled);
     }
 
-    renderer
-}
+    #[test]
+    fn test_two_directory_segments() {
+        let mut config = ThemePresets::get_theme("minimal");
+        let mut other = config.get_segment_config(SegmentId::Directory).clone();
+        other
+            .options
+            .insert("path".to_string(), serde_json::json!("/work/feature"));
+        other.colors.text = Some(style::AnsiColor::rgb(1, 2, 3));
+        let other_fg = other.colors.text_color();
+        config.segments.push(other);
 
-/// 异步更新用的 Git 预览数据收集（避免在 render 中执行 git 命令）
-pub(crate) fn collect_git_preview(cwd: &Path) -> Option<GitPreviewData> {
-    let segment = segments::GitSegment;
-    segment.collect_preview(cwd)
+        let ctx = StatusLineContext::new("gpt-5", Path::new("/work/main")).with_cwd_check(false);
+        let renderer = build_statusline(&config, &ctx);
+        let directories: Vec<&str> = renderer
+            .segments()
+            .iter()
+            .filter(|(id, _)| *id == SegmentId::Directory)
+            .map(|(_, data)| data.primary.as_str())
+            .collect();
+        assert_eq!(directories, vec!["main", "feature"]);
+
+        // 第二个实例使用自己的颜色
+        let line = renderer.render_line();
+        let fg = |text: &str| {
+            line.spans
+                .iter()
+                .find(|span| span.content.contains(text))
+                .and_then(|span| span.style.fg)
+        };
+        assert_eq!(fg("feature"), other_fg);
+        assert_ne!(fg("main"), other_fg);
+    }
+
+    #[test]
+    fn test_edits_segment_rendered() {
+        let config = ThemePresets::get_default();
+        let ctx = StatusLineContext::new("gpt-5", Path::new("/repo"))
+            .with_cwd_check(false)
+            .with_session_edited_files(Some(5));
+        assert!(render_text(&config, &ctx).contains("✎ 5"));
+    }
+
+    #[test]
+    fn test_status_line_event() {
+        let config = ThemePresets::get_theme("minimal");
+        let ctx = StatusLineContext::new("gpt-5.2-codex", Path::new("/repo"))
+            .with_cwd_check(false)
+            .with_git_preview("main", "●", 1, 0);
+        let renderer = build_statusline(&config, &ctx);
+        let event = renderer.to_event();
+
+        assert_eq!(event.rendered_text, render_text(&config, &ctx));
+        let primaries: Vec<&str> = event
+            .segments
+            .iter()
+            .map(|data| data.primary.as_str())
+            .collect();
+        let expected: Vec<&str> = renderer
+            .segments()
+            .iter()
+            .map(|(_, data)| data.primary.as_str())
+            .collect();
+        assert_eq!(primaries, expected);
+        assert!(primaries.contains(&"main"));
+    }
+
+    #[test]
+    fn test_git_clean_rule_hides_clean_repo() {
+        let mut config = ThemePresets::get_theme("minimal");
+        config.segments.get_mut(SegmentId::Git).auto_hide_when = Some(AutoHideRule::GitClean);
+        let cwd = Path::new("/repo");
+
+        let clean = StatusLineContext::new("gpt-5", cwd).with_git_preview("main", "✓", 0, 0);
+        assert!(!render_text(&config, &clean).contains("main"));
+
+        let dirty = StatusLineContext::new("gpt-5", cwd).with_git_preview("main", "●", 0, 0);
+        assert!(render_text(&config, &dirty).contains("main ●"));
+
+        let diverged = StatusLineContext::new("gpt-5", cwd).with_git_preview("main", "✓", 1, 0);
+        assert!(render_text(&config, &diverged).contains("main"));
+    }
+
+    #[test]
+    fn test_override_replaces_primary_text() {
+        let config = ThemePresets::get_theme("minimal");
+        let ctx = StatusLineContext::new("gpt-5", Path::new("/repo"))
+            .with_git_preview("main", "●", 2, 0)
+            .with_override(SegmentId::Git, "saved!");
+
+        let renderer = build_statusline(&config, &ctx);
+        let (_, git) = renderer
+            .segments()
+            .iter()
+            .find(|(id, _)| *id == SegmentId::Git)
+            .expect("git segment");
+        assert_eq!(git.primary, "saved!");
+        assert_eq!(git.metadata.get("branch").map(String::as_str), Some("main"));
+        assert_eq!(git.metadata.get("ahead").map(String::as_str), Some("2"));
+
+        // 不带 override 的 context 不受影响
+        let ctx =
+            StatusLineContext::new("gpt-5", Path::new("/repo")).with_git_preview("main", "●", 2, 0);
+        assert!(render_text(&config, &ctx).contains("main"));
+        assert!(!render_text(&config, &ctx).contains("saved!"));
+    }
+
+    #[test]
+    fn test_reset_git_base_requires_show_base() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let git_config = config::SegmentItemConfig::default_for(SegmentId::Git);
+        assert_eq!(git_config.option_bool("show_base"), None);
+        assert!(reset_git_base(dir.path(), &git_config).is_err());
+    }
 }
@@ -2,25 +2,41 @@
 // 参考 CCometixLine 的 statusline.rs
 
 use super::config::CxLineConfig;
+use super::config::SegmentAlignment;
+use super::config::SegmentItemConfig;
 use super::segment::SegmentData;
 use super::segment::SegmentId;
+use super::style::ColorSupport;
 use super::style::StyleMode;
 use super::style::separators;
+use crate::insert_history::write_spans;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use ratatui::style::Color;
 use ratatui::style::Style;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::WidgetRef;
+use unicode_width::UnicodeWidthStr;
 
-/// Powerline 箭头字符
-const POWERLINE_ARROW: &str = "\u{e0b0}";
+/// 状态栏内容快照，供宿主转发给外部程序（如通过 IPC）
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StatusLineEvent {
+    /// 已收集的 segment 数据（按显示顺序）
+    pub segments: Vec<SegmentData>,
+    /// 渲染后的纯文本
+    pub rendered_text: String,
+}
 
 /// 状态栏渲染器
 pub struct StatusLineRenderer<'a> {
     config: &'a CxLineConfig,
     segments: Vec<(SegmentId, SegmentData)>,
+    /// 与 segments 一一对应的配置（同一 id 可以有多个配置）
+    segment_configs: Vec<&'a SegmentItemConfig>,
+    /// 终端颜色级别，超出的颜色在渲染时降级
+    color_support: ColorSupport,
 }
 
 impl<'a> StatusLineRenderer<'a> {
@@ -28,52 +44,299 @@ impl<'a> StatusLineRenderer<'a> {
         Self {
             config,
             segments: Vec::new(),
+            segment_configs: Vec::new(),
+            color_support: ColorSupport::TrueColor,
         }
     }
 
-    /// 添加 segment 数据
+    /// 按终端颜色级别降级颜色，通常传入 [`TerminalCapabilities::detect`] 的结果
+    ///
+    /// [`TerminalCapabilities::detect`]: super::capability::TerminalCapabilities::detect
+    pub fn with_color_support(mut self, support: ColorSupport) -> Self {
+        self.color_support = support;
+        self
+    }
+
+    /// 添加 segment 数据，使用该 id 的第一个配置
     pub fn add_segment(&mut self, id: SegmentId, data: SegmentData) {
-        self.segments.push((id, data));
+        self.add_configured_segment(self.config.get_segment_config(id), data);
+    }
+
+    /// 添加 segment 数据，并指定渲染时使用的配置
+    pub fn add_configured_segment(&mut self, config: &'a SegmentItemConfig, data: SegmentData) {
+        self.segments.push((config.id, data));
+        self.segment_configs.push(config);
+    }
+
+    /// 按显示顺序遍历 segment 及其配置
+    fn entries(&self) -> impl Iterator<Item = (&'a SegmentItemConfig, &(SegmentId, SegmentData))> {
+        self.segment_configs.iter().copied().zip(&self.segments)
+    }
+
+    /// 已添加的 segment 数据
+    pub fn segments(&self) -> &[(SegmentId, SegmentData)] {
+        &self.segments
+    }
+
+    /// 生成当前内容的事件快照
+    pub fn to_event(&self) -> StatusLineEvent {
+        let rendered_text = self
+            .render_line()
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        StatusLineEvent {
+            segments: self.segments.iter().map(|(_, data)| data.clone()).collect(),
+            rendered_text,
+        }
+    }
+
+    /// 渲染为带 SGR 转义序列的纯文本，用于截图、文档或测试快照
+    pub fn to_ansi_string(&self) -> String {
+        let line = self.render_line();
+        let mut out = Vec::new();
+        // 写入内存缓冲区不会失败
+        let _ = write_spans(&mut out, line.spans.iter());
+        String::from_utf8_lossy(&out).into_owned()
     }
 
     /// 渲染为 Line
     pub fn render_line(&self) -> Line<'static> {
-        match self.config.style {
-            StyleMode::Powerline => self.render_powerline(),
+        self.render_line_measured().0
+    }
+
+    /// 渲染为 Line，同时返回其显示宽度
+    pub fn render_line_measured(&self) -> (Line<'static>, usize) {
+        let (line, _) = self.render_line_with_breaks();
+        let width = line.width();
+        (line, width)
+    }
+
+    /// 是否有启用的 segment 配置为靠右显示
+    pub fn has_right_aligned(&self) -> bool {
+        self.segment_configs
+            .iter()
+            .any(|config| config.enabled && config.alignment == SegmentAlignment::Right)
+    }
+
+    /// 按给定宽度渲染：靠左的 segment 在左侧，靠右的 segment 贴齐右边缘，中间以空格填充
+    /// 宽度不足以容纳两组时丢弃靠右的一组，不换行；靠左的一组仍放不下时从末尾丢弃 segment
+    /// 配置了 overflow_indicator 时在丢弃处显示，并为其预留宽度
+    pub fn render_line_for_width(&self, width: u16) -> Line<'static> {
+        let renderer = self.compacted_for_width(width);
+        let left_group = renderer.aligned_group(SegmentAlignment::Left);
+        let left = left_group.render_line();
+        let right = renderer
+            .aligned_group(SegmentAlignment::Right)
+            .render_line();
+        if left.width() > usize::from(width) {
+            return left_group.dropped_for_width(width);
+        }
+        if right.spans.is_empty() {
+            return left;
+        }
+
+        // Line::width 按 unicode 显示宽度计算，箭头和 emoji 也能对齐
+        let left_width = left.width();
+        let min_gap = usize::from(left_width > 0);
+        let Some(gap) = usize::from(width).checked_sub(left_width + min_gap + right.width()) else {
+            return left_group.dropped_for_width(width);
+        };
+
+        let mut spans = left.spans;
+        spans.push(Span::raw(" ".repeat(gap + min_gap)));
+        spans.extend(right.spans);
+        Line::from(spans)
+    }
+
+    /// 超出宽度时从最后一个 segment 开始逐个换成紧凑形式（alt_text），直到放得下或无可替换
+    /// 仍然放不下时由调用方丢弃或截断
+    pub fn compacted_for_width(&self, width: u16) -> StatusLineRenderer<'a> {
+        let mut renderer = StatusLineRenderer {
+            config: self.config,
+            segments: self.segments.clone(),
+            segment_configs: self.segment_configs.clone(),
+            color_support: self.color_support,
+        };
+        for index in (0..renderer.segments.len()).rev() {
+            if renderer.required_width() <= usize::from(width) {
+                break;
+            }
+            if !renderer.segment_configs[index].enabled {
+                continue;
+            }
+            if let Some(compact) = renderer.segments[index].1.compact() {
+                renderer.segments[index].1 = compact;
+            }
+        }
+        renderer
+    }
+
+    /// 完整显示所需宽度：有靠右的一组时按左右分组加最小间隔计算
+    fn required_width(&self) -> usize {
+        if !self.has_right_aligned() {
+            return self.render_line().width();
+        }
+        let left = self
+            .aligned_group(SegmentAlignment::Left)
+            .render_line()
+            .width();
+        let right = self
+            .aligned_group(SegmentAlignment::Right)
+            .render_line()
+            .width();
+        left + usize::from(left > 0) + right
+    }
+
+    /// 已有 segment 被丢弃时调用：从末尾逐个丢弃 segment，直到剩余内容加上暗色提示符放得下
+    /// 未配置 overflow_indicator 时不再丢弃，原样返回由调用方截断
+    fn dropped_for_width(&self, width: u16) -> Line<'static> {
+        let Some(indicator) = &self.config.overflow_indicator else {
+            return self.render_line();
+        };
+        let mut renderer = StatusLineRenderer {
+            config: self.config,
+            segments: self.segments.clone(),
+            segment_configs: self.segment_configs.clone(),
+            color_support: self.color_support,
+        };
+        loop {
+            let line = renderer.render_line();
+            let text = if line.spans.is_empty() {
+                indicator.clone()
+            } else {
+                format!(" {indicator}")
+            };
+            if line.width() + text.width() <= usize::from(width) || line.spans.is_empty() {
+                let mut spans = line.spans;
+                spans.push(Span::raw(text).dim());
+                return Line::from(spans);
+            }
+            renderer.segments.pop();
+            renderer.segment_configs.pop();
+        }
+    }
+
+    /// 只包含指定对齐方式 segment 的渲染器
+    fn aligned_group(&self, alignment: SegmentAlignment) -> StatusLineRenderer<'a> {
+        let (segment_configs, segments) = self
+            .entries()
+            .filter(|(config, _)| config.alignment == alignment)
+            .map(|(config, segment)| (config, segment.clone()))
+            .unzip();
+        StatusLineRenderer {
+            config: self.config,
+            segments,
+            segment_configs,
+            color_support: self.color_support,
+        }
+    }
+
+    /// 渲染副标题行，每个 subtitle 对齐到其 segment 内容的起始列
+    /// 没有任何 segment 提供 subtitle 时返回 None
+    pub fn render_subtitle_line(&self) -> Option<Line<'static>> {
+        let (line, breaks) = self.render_line_with_breaks();
+        self.subtitle_line(&line, &breaks)
+    }
+
+    fn subtitle_line(&self, line: &Line<'_>, breaks: &[usize]) -> Option<Line<'static>> {
+        // breaks 与启用的 segment 一一对应
+        let enabled = self.entries().filter(|(config, _)| config.enabled);
+
+        let mut spans: Vec<Span<'static>> = Vec::new();
+        let mut column = 0;
+        for ((_, (_, data)), start) in enabled.zip(breaks) {
+            let Some(subtitle) = &data.subtitle else {
+                continue;
+            };
+            let target: usize = line.spans[..*start].iter().map(Span::width).sum();
+            // 与前一个副标题重叠时顺延，至少保留一个空格
+            let gap = if target > column {
+                target - column
+            } else {
+                usize::from(column > 0)
+            };
+            if gap > 0 {
+                spans.push(Span::raw(" ".repeat(gap)));
+            }
+            spans.push(Span::raw(subtitle.clone()).dim());
+            column += gap + subtitle.width();
+        }
+
+        if spans.is_empty() {
+            None
+        } else {
+            Some(Line::from(spans))
+        }
+    }
+
+    /// 渲染为 Line，同时返回每个 segment 内容起始的 span 下标（用于按 segment 换行）
+    fn render_line_with_breaks(&self) -> (Line<'static>, Vec<usize>) {
+        let (mut line, breaks) = match self.config.style {
+            StyleMode::Powerline | StyleMode::PowerlineSeparated => self.render_powerline(),
             _ => self.render_plain(),
+        };
+        if self.color_support != ColorSupport::TrueColor {
+            let support = self.color_support;
+            for span in &mut line.spans {
+                span.style.fg = span.style.fg.map(|fg| support.downgrade_color(fg));
+                span.style.bg = span.style.bg.map(|bg| support.downgrade_color(bg));
+            }
         }
+        (line, breaks)
     }
 
     /// 渲染普通模式（Plain / NerdFont）
-    fn render_plain(&self) -> Line<'static> {
+    fn render_plain(&self) -> (Line<'static>, Vec<usize>) {
         let mut spans: Vec<Span<'static>> = Vec::new();
+        let mut breaks = Vec::new();
         let separator = self.get_separator();
+        let separator_style = self.separator_style();
         let mut first = true;
 
-        for (id, data) in self.segments.iter() {
-            let segment_config = self.config.get_segment_config(*id);
+        if self.config.outer_separators {
+            spans.push(Span::styled(separator.to_string(), separator_style));
+        }
+
+        for (segment_config, (_, data)) in self.entries() {
             if !segment_config.enabled {
                 continue;
             }
 
             if !first {
-                spans.push(Span::raw(separator.to_string()).dim());
+                spans.push(Span::styled(separator.to_string(), separator_style));
             }
             first = false;
+            breaks.push(spans.len());
+
+            // 背景色只铺在图标和内容上，不影响分隔符；气泡模式两端再加圆角
+            let bg_color = segment_config.colors.background_color();
+            let bubble_bg = if self.config.bubble { bg_color } else { None };
+            let base_style = match bg_color {
+                Some(bg) => Style::default().bg(bg),
+                None => Style::default(),
+            };
+            if let Some(bg) = bubble_bg {
+                spans.push(Span::styled(
+                    separators::ROUND_LEFT,
+                    Style::default().fg(bg),
+                ));
+            }
 
             // 渲染图标
-            let icon = self.get_icon(*id, data);
+            let icon = self.get_icon(segment_config, data);
             if !icon.is_empty() {
-                let mut icon_style = Style::default();
-                if let Some(color) = segment_config.colors.icon_color() {
+                let mut icon_style = base_style;
+                if let Some(color) = Self::icon_color(segment_config, data) {
                     icon_style = icon_style.fg(color);
                 }
                 spans.push(Span::styled(format!("{icon} "), icon_style));
             }
 
-            // 渲染主要内容
-            let mut text_style = Style::default();
-            if let Some(color) = segment_config.colors.text_color() {
+            // 渲染主要内容（动态颜色优先）
+            let mut text_style = base_style;
+            if let Some(color) = Self::text_color(segment_config, data) {
                 text_style = text_style.fg(color);
             }
             if segment_config.styles.text_bold {
@@ -83,33 +346,60 @@ impl<'a> StatusLineRenderer<'a> {
 
             // 渲染次要内容
             if !data.secondary.is_empty() {
-                spans.push(Span::styled(format!(" {}", data.secondary), text_style));
+                spans.push(Span::styled(
+                    format!(
+                        "{}{}{}",
+                        segment_config.secondary_separator,
+                        segment_config.secondary_prefix(),
+                        data.secondary
+                    ),
+                    text_style,
+                ));
+            }
+
+            // 渲染徽标
+            if let Some(badge) = &data.badge {
+                let badge_style = base_style.fg(segment_config.colors.badge_color()).bold();
+                spans.push(Span::styled(format!(" {badge}"), badge_style));
+            }
+
+            if let Some(bg) = bubble_bg {
+                spans.push(Span::styled(
+                    separators::ROUND_RIGHT,
+                    Style::default().fg(bg),
+                ));
             }
         }
 
-        Line::from(spans)
+        if breaks.is_empty() {
+            // 没有 segment 时不显示孤立的外侧分隔符
+            spans.clear();
+        } else if self.config.outer_separators {
+            spans.push(Span::styled(separator.to_string(), separator_style));
+        }
+
+        (Line::from(spans), breaks)
     }
 
     /// 渲染 Powerline 模式（带背景色和箭头过渡）
-    fn render_powerline(&self) -> Line<'static> {
+    fn render_powerline(&self) -> (Line<'static>, Vec<usize>) {
         let mut spans: Vec<Span<'static>> = Vec::new();
+        let mut breaks = Vec::new();
 
         // 收集启用的 segment
         let enabled_segments: Vec<_> = self
-            .segments
-            .iter()
-            .filter(|(id, _)| self.config.get_segment_config(*id).enabled)
+            .entries()
+            .filter(|(config, _)| config.enabled)
             .collect();
 
         let segment_count = enabled_segments.len();
+        let arrow_padding = self.config.powerline_arrow_padding;
 
-        for (i, (id, data)) in enabled_segments.iter().enumerate() {
-            let segment_config = self.config.get_segment_config(*id);
-
+        for (i, (segment_config, (_, data))) in enabled_segments.iter().enumerate() {
             // 获取背景色
             let bg_color = segment_config.colors.background_color();
-            let text_color = segment_config.colors.text_color();
-            let icon_color = segment_config.colors.icon_color();
+            let text_color = Self::text_color(segment_config, data);
+            let icon_color = Self::icon_color(segment_config, data);
 
             // 构建 segment 样式
             let mut segment_style = Style::default();
@@ -123,17 +413,31 @@ impl<'a> StatusLineRenderer<'a> {
                 segment_style = segment_style.bold();
             }
 
-            // 添加左边距
-            spans.push(Span::styled(" ", segment_style));
+            // 添加左边距（负间距时省略紧跟箭头的左边距）
+            breaks.push(spans.len());
+            if i == 0 || arrow_padding > -2 {
+                spans.push(Span::styled(" ", segment_style));
+            }
 
-            // 渲染图标
-            let icon = self.get_icon(*id, data);
+            // 渲染图标：背景始终与 pill 一致，前景按 icon → text → 默认 回退
+            let icon = self.get_icon(segment_config, data);
             if !icon.is_empty() {
                 let mut icon_style = segment_style;
+                if let Some(bg) = bg_color {
+                    icon_style = icon_style.bg(bg);
+                }
                 if let Some(ic) = icon_color {
                     icon_style = icon_style.fg(ic);
                 }
                 spans.push(Span::styled(format!("{icon} "), icon_style));
+
+                // 混合模式：pill 内图标与文本之间的细分隔符
+                if self.config.style == StyleMode::PowerlineSeparated {
+                    spans.push(Span::styled(
+                        format!("{} ", self.config.powerline_separator.thin()),
+                        segment_style,
+                    ));
+                }
             }
 
             // 渲染主要内容
@@ -141,15 +445,34 @@ impl<'a> StatusLineRenderer<'a> {
 
             // 渲染次要内容
             if !data.secondary.is_empty() {
-                spans.push(Span::styled(format!(" {}", data.secondary), segment_style));
+                spans.push(Span::styled(
+                    format!(
+                        "{}{}{}",
+                        segment_config.secondary_separator,
+                        segment_config.secondary_prefix(),
+                        data.secondary
+                    ),
+                    segment_style,
+                ));
+            }
+
+            // 渲染徽标（保留 pill 背景）
+            if let Some(badge) = &data.badge {
+                let badge_style = segment_style.fg(segment_config.colors.badge_color()).bold();
+                spans.push(Span::styled(format!(" {badge}"), badge_style));
             }
 
-            // 添加右边距
-            spans.push(Span::styled(" ", segment_style));
+            // 添加右边距（负间距时省略；tight_pills 下没有次要内容和徽标时也省略）
+            let is_last = i == segment_count - 1;
+            let tight =
+                self.config.tight_pills && data.secondary.is_empty() && data.badge.is_none();
+            if !tight && (is_last || arrow_padding > -1) {
+                spans.push(Span::styled(" ", segment_style));
+            }
 
-            // 添加 Powerline 箭头过渡（最后一个 segment 不需要箭头）
-            if i < segment_count - 1 {
-                let next_segment_config = self.config.get_segment_config(enabled_segments[i + 1].0);
+            // 添加 Powerline 箭头过渡（最后一个 segment 按配置绘制收尾箭头）
+            if !is_last {
+                let next_segment_config = enabled_segments[i + 1].0;
                 let next_bg = next_segment_config.colors.background_color();
 
                 let mut arrow_style = Style::default();
@@ -159,29 +482,86 @@ impl<'a> StatusLineRenderer<'a> {
                 if let Some(next_bg_color) = next_bg {
                     arrow_style = arrow_style.bg(next_bg_color);
                 }
-                spans.push(Span::styled(POWERLINE_ARROW, arrow_style));
+
+                // 正间距：箭头前使用当前背景，箭头后使用下一个 segment 的背景
+                let padding = " ".repeat(arrow_padding.max(0) as usize);
+                if !padding.is_empty() {
+                    spans.push(Span::styled(padding.clone(), segment_style));
+                }
+                spans.push(Span::styled(
+                    self.config.powerline_separator.filled(),
+                    arrow_style,
+                ));
+                if !padding.is_empty() {
+                    let mut after_style = Style::default();
+                    if let Some(next_bg_color) = next_bg {
+                        after_style = after_style.bg(next_bg_color);
+                    }
+                    spans.push(Span::styled(padding, after_style));
+                }
+            } else if self.config.powerline_trailing_cap
+                && let Some(last_bg) = bg_color
+            {
+                // 收尾箭头：从最后一个 segment 的背景过渡到终端默认背景
+                spans.push(Span::styled(
+                    self.config.powerline_separator.filled(),
+                    Style::default().fg(last_bg),
+                ));
             }
         }
 
-        Line::from(spans)
+        (Line::from(spans), breaks)
     }
 
     /// 获取分隔符
     fn get_separator(&self) -> &'static str {
         match self.config.style {
-            StyleMode::Powerline => separators::POWERLINE_THIN,
+            StyleMode::Powerline | StyleMode::PowerlineSeparated => {
+                self.config.powerline_separator.thin()
+            }
             _ => separators::SIMPLE,
         }
     }
 
+    /// 非 Powerline 模式分隔符样式：可选颜色，默认变暗
+    fn separator_style(&self) -> Style {
+        let mut style = Style::default();
+        if let Some(color) = self.config.separator_color {
+            style = style.fg(color.to_ratatui_color());
+        }
+        if self.config.separator_dim {
+            style = style.dim();
+        }
+        style
+    }
+
+    /// 获取文本颜色：强制颜色 → 动态颜色 → 配置颜色
+    fn text_color(segment_config: &SegmentItemConfig, data: &SegmentData) -> Option<Color> {
+        data.color_override
+            .or(data.color_hint)
+            .map(|color| color.to_ratatui_color())
+            .or_else(|| segment_config.colors.text_color())
+    }
+
+    /// 获取图标颜色：动态图标颜色 → 动态颜色 → 配置中的 icon → text
+    fn icon_color(segment_config: &SegmentItemConfig, data: &SegmentData) -> Option<Color> {
+        data.icon_color_hint
+            .or(data.color_hint)
+            .map(|color| color.to_ratatui_color())
+            .or_else(|| segment_config.colors.resolved_icon_color())
+    }
+
     /// 获取图标
-    fn get_icon(&self, id: SegmentId, data: &SegmentData) -> String {
+    fn get_icon(&self, segment_config: &SegmentItemConfig, data: &SegmentData) -> String {
+        if let Some(icon) = &data.icon_override {
+            return icon.get(self.config.style).to_string();
+        }
+
         // 优先使用动态图标（从元数据）
         if let Some(dynamic_icon) = data.metadata.get("dynamic_icon") {
             return dynamic_icon.clone();
         }
 
-        let segment_config = self.config.get_segment_config(id);
         segment_config.icon.get(self.config.style).to_string()
     }
 }
@@ -189,17 +569,67 @@ impl<'a> StatusLineRenderer<'a> {
 /// 状态栏 Widget
 pub struct StatusLineWidget<'a> {
     line: Line<'a>,
+    /// 可换行位置（每个 segment 起始的 span 下标）
+    breaks: Vec<usize>,
+    /// 超出宽度时按 segment 边界换行
+    wrap: bool,
+    /// 副标题行（区域高度足够时显示在主行下方）
+    subtitle: Option<Line<'a>>,
 }
 
 impl<'a> StatusLineWidget<'a> {
     pub fn new(line: Line<'a>) -> Self {
-        Self { line }
+        Self {
+            line,
+            breaks: Vec::new(),
+            wrap: false,
+            subtitle: None,
+        }
     }
 
     pub fn from_renderer(renderer: &StatusLineRenderer<'_>) -> Self {
+        let (line, breaks) = renderer.render_line_with_breaks();
+        let subtitle = renderer.subtitle_line(&line, &breaks);
         Self {
-            line: renderer.render_line(),
+            line,
+            breaks,
+            wrap: false,
+            subtitle,
+        }
+    }
+
+    /// 宽度不足时在 segment 边界处换到下一行（不会拆分 segment 内容）
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// 按 segment 边界把 line 拆成不超过 width 的多行
+    /// 单个 segment 本身超宽时独占一行，由 set_line 截断
+    fn wrapped_lines(&self, width: usize) -> Vec<Line<'a>> {
+        let spans = &self.line.spans;
+        let mut bounds: Vec<usize> = self.breaks.iter().copied().filter(|b| *b > 0).collect();
+        bounds.push(spans.len());
+
+        let mut lines = Vec::new();
+        let mut current: Vec<Span<'a>> = Vec::new();
+        let mut current_width = 0;
+        let mut start = 0;
+        for end in bounds {
+            let group = &spans[start..end];
+            let group_width: usize = group.iter().map(Span::width).sum();
+            if !current.is_empty() && current_width + group_width > width {
+                lines.push(Line::from(std::mem::take(&mut current)));
+                current_width = 0;
+            }
+            current.extend_from_slice(group);
+            current_width += group_width;
+            start = end;
+        }
+        if !current.is_empty() {
+            lines.push(Line::from(current));
         }
+        lines
     }
 }
 
@@ -209,8 +639,791 @@ impl WidgetRef for StatusLineWidget<'_> {
             return;
         }
 
+        if self.wrap && self.line.width() > area.width as usize {
+            let lines = self.wrapped_lines(area.width as usize);
+            for (row, line) in lines.iter().take(area.height as usize).enumerate() {
+                buf.set_line(area.x, area.y + row as u16, line, area.width);
+            }
+            return;
+        }
+
         // 渲染状态栏内容
         let line = self.line.clone();
         buf.set_line(area.x, area.y, &line, area.width);
+
+        // 副标题画在主行下方，需要调用方预留第二行
+        if let Some(subtitle) = &self.subtitle
+            && area.height > 1
+        {
+            buf.set_line(area.x, area.y + 1, subtitle, area.width);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statusline::style::AnsiColor;
+    use crate::statusline::style::IconConfig;
+    use crate::statusline::style::PowerlineGlyph;
+    use crate::statusline::style::ansi16;
+    use crate::statusline::themes::ThemePresets;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_powerline_icon_color_over_pill_background() {
+        let mut config = ThemePresets::get_powerline_dark();
+        let colors = &mut config.segments.get_mut(SegmentId::Model).colors;
+        colors.icon = Some(AnsiColor::c16(1));
+        colors.text = None;
+        colors.background = Some(AnsiColor::c16(4));
+
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+        let line = renderer.render_line();
+
+        let icon_span = line
+            .spans
+            .iter()
+            .find(|span| span.content.starts_with('\u{e26d}'))
+            .expect("icon span");
+        assert_eq!(icon_span.style.fg, Some(Color::Red));
+        assert_eq!(icon_span.style.bg, Some(Color::Blue));
+
+        let text_span = line
+            .spans
+            .iter()
+            .find(|span| span.content == "GPT 5")
+            .expect("text span");
+        assert_eq!(text_span.style.fg, None);
+        assert_eq!(text_span.style.bg, Some(Color::Blue));
+    }
+
+    #[test]
+    fn test_icon_override_follows_style_mode() {
+        let data = SegmentData::new("50%").with_icon_override(IconConfig::new("◑", "\u{f007f}"));
+        let render = |style| {
+            let mut config = ThemePresets::get_default();
+            config.style = style;
+            let mut renderer = StatusLineRenderer::new(&config);
+            renderer.add_segment(SegmentId::Context, data.clone());
+            renderer.render_line().to_string()
+        };
+
+        let plain = render(StyleMode::Plain);
+        assert!(plain.contains("◑"), "{plain}");
+        assert!(!plain.contains("\u{f007f}"), "{plain}");
+        assert!(render(StyleMode::NerdFont).contains("\u{f007f}"));
+    }
+
+    #[test]
+    fn test_badge_rendered_after_secondary() {
+        let mut config = ThemePresets::get_default();
+        config.segments.get_mut(SegmentId::Git).colors.text = Some(AnsiColor::c16(2));
+        config.segments.get_mut(SegmentId::Git).colors.badge = Some(AnsiColor::c16(1));
+
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(
+            SegmentId::Git,
+            SegmentData::new("main").with_secondary("⚠").with_badge("3"),
+        );
+        let line = renderer.render_line();
+
+        let secondary_index = line
+            .spans
+            .iter()
+            .position(|span| span.content == " ⚠")
+            .expect("secondary span");
+        let badge_span = &line.spans[secondary_index + 1];
+        assert_eq!(badge_span.content, " 3");
+        assert_eq!(badge_span.style.fg, Some(Color::Red));
+        assert_ne!(badge_span.style, line.spans[secondary_index].style);
+    }
+
+    #[test]
+    fn test_secondary_separator() {
+        let mut config = ThemePresets::get_default();
+        config
+            .segments
+            .get_mut(SegmentId::Usage)
+            .secondary_separator = " · ".to_string();
+        config.segments.get_mut(SegmentId::Usage).secondary_prefix = Some(String::new());
+
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(
+            SegmentId::Usage,
+            SegmentData::new("25%").with_secondary("1-28-14"),
+        );
+        renderer.add_segment(SegmentId::Git, SegmentData::new("main").with_secondary("✓"));
+        let line = renderer.render_line();
+        let text: String = line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+
+        assert!(text.contains("25% · 1-28-14"), "{text}");
+        assert!(text.contains("main ✓"), "{text}");
+    }
+
+    #[test]
+    fn test_powerline_separated_inserts_thin_separator_in_pill() {
+        let mut config = ThemePresets::get_powerline_dark();
+        config.style = StyleMode::PowerlineSeparated;
+        let bg = config
+            .segments
+            .get(SegmentId::Model)
+            .colors
+            .background_color();
+
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+        let line = renderer.render_line();
+
+        let icon_index = line
+            .spans
+            .iter()
+            .position(|span| span.content.starts_with('\u{e26d}'))
+            .expect("icon span");
+        let separator = &line.spans[icon_index + 1];
+        assert_eq!(separator.content, "\u{e0b1} ");
+        assert_eq!(separator.style.bg, bg);
+        assert_eq!(line.spans[icon_index + 2].content, "GPT 5");
+    }
+
+    #[test]
+    fn test_render_line_measured_width() {
+        for config in [
+            ThemePresets::get_default(),
+            ThemePresets::get_powerline_dark(),
+        ] {
+            let mut renderer = StatusLineRenderer::new(&config);
+            renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+            renderer.add_segment(
+                SegmentId::Git,
+                SegmentData::new("主分支").with_secondary("✓ ↑1"),
+            );
+            let (line, width) = renderer.render_line_measured();
+
+            let expected: usize = line
+                .spans
+                .iter()
+                .map(|span| UnicodeWidthStr::width(span.content.as_ref()))
+                .sum();
+            assert_eq!(width, expected);
+            assert_eq!(line, renderer.render_line());
+        }
+    }
+
+    #[test]
+    fn test_wrap_at_segment_boundaries() {
+        let config = ThemePresets::get_theme("minimal");
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5.2 Codex"));
+        renderer.add_segment(SegmentId::Directory, SegmentData::new("project"));
+        let full_width = renderer.render_line_measured().1;
+
+        let area = Rect::new(0, 0, 22, 3);
+        assert!(full_width > area.width as usize);
+        let mut buf = Buffer::empty(area);
+        StatusLineWidget::from_renderer(&renderer)
+            .with_wrap(true)
+            .render_ref(area, &mut buf);
+
+        let row = |y: u16| -> String {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        };
+        assert!(row(0).contains("GPT 5.2 Codex"), "{:?}", row(0));
+        assert!(!row(0).contains("project"));
+        assert!(row(1).contains("project"), "{:?}", row(1));
+        assert_eq!(row(2).trim(), "");
+    }
+
+    #[test]
+    fn test_subtitle_row_under_parent_segment() {
+        let config = ThemePresets::get_theme("minimal");
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Directory, SegmentData::new("project"));
+        renderer.add_segment(
+            SegmentId::Model,
+            SegmentData::new("GPT 5.2").with_subtitle("openai"),
+        );
+
+        let area = Rect::new(0, 0, 40, 2);
+        let mut buf = Buffer::empty(area);
+        StatusLineWidget::from_renderer(&renderer).render_ref(area, &mut buf);
+
+        let row = |y: u16| -> String {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        };
+        let model_column = row(0).find("GPT").expect("model on main row");
+        let icon_width = config
+            .segments
+            .get(SegmentId::Model)
+            .icon
+            .get(config.style)
+            .width();
+        let subtitle_column = row(1).find("openai").expect("subtitle row");
+        // 对齐到 segment 起始（图标所在列）
+        let offset = if icon_width > 0 { icon_width + 1 } else { 0 };
+        assert_eq!(
+            row(1)[..subtitle_column].width(),
+            row(0)[..model_column].width() - offset
+        );
+    }
+
+    #[test]
+    fn test_bubble_caps_surround_segment() {
+        let mut config = ThemePresets::get_theme("minimal");
+        config.segments.get_mut(SegmentId::Model).colors.background = Some(AnsiColor::c16(4));
+        let render = |config: &CxLineConfig| {
+            let mut renderer = StatusLineRenderer::new(config);
+            renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+            renderer.add_segment(SegmentId::Directory, SegmentData::new("project"));
+            renderer.render_line()
+        };
+
+        let line = render(&config);
+        assert!(
+            !line
+                .spans
+                .iter()
+                .any(|span| span.content == separators::ROUND_LEFT)
+        );
+
+        config.bubble = true;
+        let line = render(&config);
+        let bg = AnsiColor::c16(4).to_ratatui_color();
+        let first = line.spans.first().expect("left cap");
+        assert_eq!(first.content, separators::ROUND_LEFT);
+        assert_eq!(first.style.fg, Some(bg));
+        let right = line
+            .spans
+            .iter()
+            .position(|span| span.content == separators::ROUND_RIGHT)
+            .expect("right cap");
+        assert!(
+            line.spans[1..right]
+                .iter()
+                .all(|span| span.style.bg == Some(bg))
+        );
+        let text: String = line.spans[1..right]
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert!(text.contains("GPT 5"));
+        // 分隔符在气泡之间；没有背景色的 segment 不加圆角
+        assert_eq!(line.spans[right + 1].content, separators::SIMPLE);
+        assert_eq!(
+            line.spans
+                .iter()
+                .filter(|span| span.content == separators::ROUND_LEFT)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_outer_separators() {
+        let mut config = ThemePresets::get_theme("minimal");
+        let render = |config: &CxLineConfig| {
+            let mut renderer = StatusLineRenderer::new(config);
+            renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+            renderer.add_segment(SegmentId::Directory, SegmentData::new("project"));
+            renderer.render_line()
+        };
+
+        let line = render(&config);
+        assert_ne!(line.spans[0].content, separators::SIMPLE);
+        assert_ne!(
+            line.spans.last().map(|span| span.content.as_ref()),
+            Some(separators::SIMPLE)
+        );
+
+        config.outer_separators = true;
+        let line = render(&config);
+        assert_eq!(line.spans[0].content, separators::SIMPLE);
+        assert_eq!(
+            line.spans.last().map(|span| span.content.as_ref()),
+            Some(separators::SIMPLE)
+        );
+        assert_eq!(
+            line.spans
+                .iter()
+                .filter(|span| span.content == separators::SIMPLE)
+                .count(),
+            3
+        );
+
+        // 没有 segment 时为空
+        let renderer = StatusLineRenderer::new(&config);
+        assert_eq!(renderer.render_line_measured().1, 0);
+    }
+
+    #[test]
+    fn test_git_icon_colored_by_status() {
+        use crate::statusline::segment::Segment;
+        use crate::statusline::segments::GitSegment;
+        use crate::statusline::style::ansi16;
+        use std::path::Path;
+
+        let mut config = ThemePresets::get_theme("cometix");
+        config.segments.get_mut(SegmentId::Git).options.insert(
+            "colorize_icon_by_status".to_string(),
+            serde_json::json!(true),
+        );
+        let ctx = crate::statusline::StatusLineContext::new("", Path::new("/repo"))
+            .with_git_preview("main", "●", 0, 0);
+        let data = GitSegment::from_config(config.segments.get(SegmentId::Git))
+            .collect(&ctx)
+            .expect("git data");
+
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Git, data);
+        let line = renderer.render_line();
+
+        let icon = config.segments.get(SegmentId::Git).icon.get(config.style);
+        let icon_span = line
+            .spans
+            .iter()
+            .find(|span| span.content.starts_with(icon))
+            .expect("icon span");
+        assert_eq!(icon_span.style.fg, Some(ansi16::YELLOW.to_ratatui_color()));
+
+        let text_span = line
+            .spans
+            .iter()
+            .find(|span| span.content == "main")
+            .expect("text span");
+        assert_eq!(
+            text_span.style.fg,
+            config.segments.get(SegmentId::Git).colors.text_color()
+        );
+        assert_ne!(text_span.style.fg, icon_span.style.fg);
+    }
+
+    #[test]
+    fn test_powerline_arrow_padding() {
+        let mut config = ThemePresets::get_powerline_dark();
+        let text = |config: &CxLineConfig| -> String {
+            let mut renderer = StatusLineRenderer::new(config);
+            renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+            renderer.add_segment(SegmentId::Directory, SegmentData::new("project"));
+            renderer
+                .render_line()
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+
+        assert!(text(&config).contains(&format!("GPT 5 {} ", separators::POWERLINE)));
+
+        config.powerline_arrow_padding = 2;
+        assert!(text(&config).contains(&format!("GPT 5   {}   ", separators::POWERLINE)));
+
+        config.powerline_arrow_padding = -2;
+        let compact = text(&config);
+        assert!(compact.contains(&format!("GPT 5{}", separators::POWERLINE)));
+        assert!(compact.starts_with(' '));
+        // 最后一个 segment 保留右边距，再接收尾箭头
+        assert!(compact.ends_with(&format!(" {}", separators::POWERLINE)));
+    }
+
+    #[test]
+    fn test_tight_pills() {
+        let mut config = ThemePresets::get_powerline_dark();
+        let width = |config: &CxLineConfig, data: SegmentData| -> usize {
+            let mut renderer = StatusLineRenderer::new(config);
+            renderer.add_segment(SegmentId::Git, data);
+            renderer.render_line().width()
+        };
+        let plain = || SegmentData::new("main");
+        let with_secondary = || SegmentData::new("main").with_secondary("✓");
+
+        // 默认：无论是否有次要内容，右边距都是一个空格
+        let loose_plain = width(&config, plain());
+        let loose_secondary = width(&config, with_secondary());
+        assert_eq!(loose_secondary - loose_plain, 2); // " ✓"
+
+        config.tight_pills = true;
+        assert_eq!(width(&config, plain()), loose_plain - 1);
+        assert_eq!(width(&config, with_secondary()), loose_secondary);
+    }
+
+    #[test]
+    fn test_secondary_prefix() {
+        let mut config = ThemePresets::get_default();
+        let text = |config: &CxLineConfig| -> String {
+            let mut renderer = StatusLineRenderer::new(config);
+            renderer.add_segment(
+                SegmentId::Usage,
+                SegmentData::new("25%").with_secondary("1-28-14"),
+            );
+            renderer.add_segment(SegmentId::Git, SegmentData::new("main").with_secondary("✓"));
+            renderer
+                .render_line()
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+
+        // usage 默认前缀与之前硬编码的 "· " 一致
+        let default = text(&config);
+        assert!(default.contains("25% · 1-28-14"), "{default}");
+        assert!(default.contains("main ✓"), "{default}");
+
+        config.segments.get_mut(SegmentId::Git).secondary_prefix = Some("» ".to_string());
+        assert!(text(&config).contains("main » ✓"));
+
+        // 前缀中的环境变量在渲染时展开，`$$` 为字面量 `$`
+        config.segments.get_mut(SegmentId::Git).secondary_prefix = Some("$HOME$$ ".to_string());
+        let home = std::env::var("HOME").unwrap_or_default();
+        let rendered = text(&config);
+        assert!(rendered.contains(&format!("main {home}$ ✓")), "{rendered}");
+    }
+
+    #[test]
+    fn test_render_line_for_width_right_group() {
+        let mut config = ThemePresets::get_theme("minimal");
+        config.segments.get_mut(SegmentId::Usage).alignment = SegmentAlignment::Right;
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+        renderer.add_segment(SegmentId::Usage, SegmentData::new("42%"));
+        renderer.add_segment(SegmentId::Directory, SegmentData::new("项目"));
+        assert!(renderer.has_right_aligned());
+
+        let text = |line: &Line<'_>| -> String {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+        let left = renderer.aligned_group(SegmentAlignment::Left).render_line();
+        let right = renderer
+            .aligned_group(SegmentAlignment::Right)
+            .render_line();
+
+        let line = renderer.render_line_for_width(60);
+        assert_eq!(line.width(), 60);
+        assert!(text(&line).starts_with(&text(&left)));
+        assert!(text(&line).ends_with(&text(&right)));
+        // 填充按显示宽度计算，而不是字节长度
+        assert!(left.width() < text(&left).len());
+        let padding = &line.spans[left.spans.len()];
+        assert_eq!(padding.content.len(), 60 - left.width() - right.width());
+
+        // 宽度不足时丢弃靠右的一组
+        let narrow = renderer.render_line_for_width((left.width() + right.width()) as u16);
+        assert_eq!(text(&narrow), text(&left));
+    }
+
+    /// minimal 主题下 model、directory、git 三个靠左的 segment
+    fn left_only_renderer(config: &CxLineConfig) -> StatusLineRenderer<'_> {
+        let mut renderer = StatusLineRenderer::new(config);
+        renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+        renderer.add_segment(SegmentId::Directory, SegmentData::new("crate"));
+        renderer.add_segment(SegmentId::Git, SegmentData::new("main"));
+        renderer
+    }
+
+    #[test]
+    fn test_overflow_indicator_drops_trailing_left_segments() {
+        let mut config = ThemePresets::get_theme("minimal");
+        config.overflow_indicator = Some("…".to_string());
+        let renderer = left_only_renderer(&config);
+        let full_width = renderer.render_line().width();
+
+        // 放得下时不显示提示符
+        let line = renderer.render_line_for_width(full_width as u16);
+        assert_eq!(line.width(), full_width);
+        assert!(line.spans.iter().all(|span| span.content != " …"));
+
+        // 少一列时丢弃最后一个 segment，并在丢弃处显示暗色提示符
+        let line = renderer.render_line_for_width(full_width as u16 - 1);
+        let text = line.to_string();
+        assert!(text.contains("crate") && !text.contains("main"), "{text}");
+        let last = line.spans.last().expect("indicator span");
+        assert_eq!(last.content, " …");
+        assert!(
+            last.style
+                .add_modifier
+                .contains(ratatui::style::Modifier::DIM)
+        );
+        assert!(line.width() < full_width);
+    }
+
+    #[test]
+    fn test_overflow_indicator_reserves_room() {
+        let mut config = ThemePresets::get_theme("minimal");
+        config.overflow_indicator = Some("…".to_string());
+        let renderer = left_only_renderer(&config);
+
+        let mut two = StatusLineRenderer::new(&config);
+        two.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+        two.add_segment(SegmentId::Directory, SegmentData::new("crate"));
+        let two_width = two.render_line().width();
+
+        // 刚好放下两个 segment 但放不下提示符时，再丢弃一个为提示符让出位置
+        let line = renderer.render_line_for_width(two_width as u16);
+        let text = line.to_string();
+        assert!(text.contains("GPT 5") && !text.contains("crate"), "{text}");
+        assert!(text.ends_with(" …"), "{text}");
+        assert!(line.width() <= two_width);
+
+        // 未配置提示符时保持原样，由调用方截断
+        config.overflow_indicator = None;
+        let renderer = left_only_renderer(&config);
+        let line = renderer.render_line_for_width(two_width as u16);
+        assert!(line.to_string().contains("main"));
+    }
+
+    #[test]
+    fn test_compact_forms_before_dropping() {
+        let mut config = ThemePresets::get_theme("minimal");
+        config.segments.get_mut(SegmentId::Usage).alignment = SegmentAlignment::Right;
+        let model = || SegmentData::new("GPT 5.1 Codex Max").with_alt_text("5.1cm");
+        let git = || {
+            SegmentData::new("main")
+                .with_secondary("●")
+                .with_alt_text("●")
+        };
+        let build = |model: SegmentData, git: SegmentData| {
+            let mut renderer = StatusLineRenderer::new(&config);
+            renderer.add_segment(SegmentId::Model, model);
+            renderer.add_segment(SegmentId::Git, git);
+            renderer.add_segment(SegmentId::Usage, SegmentData::new("42%"));
+            renderer
+        };
+        let text = |line: Line<'static>| -> String {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+        let renderer = build(model(), git());
+        let full_width = renderer.required_width();
+        let git_compact_width =
+            build(model(), git().compact().expect("git compact")).required_width();
+        let all_compact_width = build(
+            model().compact().expect("model compact"),
+            git().compact().expect("git compact"),
+        )
+        .required_width();
+        assert!(all_compact_width < git_compact_width && git_compact_width < full_width);
+
+        let full = text(renderer.render_line_for_width(full_width as u16));
+        assert!(full.contains("GPT 5.1 Codex Max") && full.contains("main"));
+
+        // 中等宽度：先把靠后的 segment 换成紧凑形式
+        let line = text(renderer.render_line_for_width(git_compact_width as u16));
+        assert!(line.contains("GPT 5.1 Codex Max") && !line.contains("main"));
+        assert!(line.contains("●") && line.ends_with("42%"));
+
+        let line = text(renderer.render_line_for_width(all_compact_width as u16));
+        assert!(line.contains("5.1cm") && line.ends_with("42%"));
+
+        // 紧凑形式也放不下时才丢弃靠右的一组
+        let line = text(renderer.render_line_for_width(all_compact_width as u16 - 1));
+        assert!(line.contains("5.1cm") && !line.contains("42%"));
+    }
+
+    #[test]
+    fn test_color_override_wins_over_hint_and_config() {
+        let config = ThemePresets::get_powerline_dark();
+        let fg = |data: SegmentData| {
+            let mut renderer = StatusLineRenderer::new(&config);
+            renderer.add_segment(SegmentId::Git, data);
+            renderer
+                .render_line()
+                .spans
+                .iter()
+                .find(|span| span.content == "main")
+                .expect("text span")
+                .style
+                .fg
+        };
+
+        assert_eq!(
+            fg(SegmentData::new("main")),
+            config.segments.get(SegmentId::Git).colors.text_color()
+        );
+        assert_eq!(
+            fg(SegmentData::new("main")
+                .with_color_hint(AnsiColor::c256(33))
+                .with_dynamic_color(ansi16::RED)),
+            Some(Color::Red)
+        );
+    }
+
+    #[test]
+    fn test_color_support_downgrades_rgb() {
+        let mut config = ThemePresets::get_powerline_dark();
+        let git = config.segments.get_mut(SegmentId::Git);
+        git.colors.text = Some(AnsiColor::rgb(250, 10, 10));
+        git.colors.background = Some(AnsiColor::rgb(255, 135, 0));
+        let spans = |support: ColorSupport| {
+            let mut renderer = StatusLineRenderer::new(&config).with_color_support(support);
+            renderer.add_segment(SegmentId::Git, SegmentData::new("main"));
+            renderer.render_line().spans
+        };
+
+        let true_color = spans(ColorSupport::TrueColor);
+        let text = true_color
+            .iter()
+            .find(|span| span.content.contains("main"))
+            .expect("text span");
+        assert_eq!(
+            text.style.fg,
+            Some(AnsiColor::rgb(250, 10, 10).to_ratatui_color())
+        );
+
+        let ansi256 = spans(ColorSupport::Ansi256);
+        let text = ansi256
+            .iter()
+            .find(|span| span.content.contains("main"))
+            .expect("text span");
+        assert_eq!(text.style.bg, Some(AnsiColor::c256(208).to_ratatui_color()));
+
+        // 16 色终端上不应再出现任何 RGB/256 色
+        for span in spans(ColorSupport::Ansi16) {
+            for color in [span.style.fg, span.style.bg].into_iter().flatten() {
+                assert!(
+                    !matches!(color, Color::Rgb(..) | Color::Indexed(_)),
+                    "{color:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_plain_mode_applies_background() {
+        let mut config = ThemePresets::get_default();
+        config.style = StyleMode::NerdFont;
+        config.segments.get_mut(SegmentId::Model).colors.background = Some(AnsiColor::c16(4));
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+        renderer.add_segment(SegmentId::Git, SegmentData::new("main"));
+        let line = renderer.render_line();
+
+        let bg = Some(AnsiColor::c16(4).to_ratatui_color());
+        let span = |text: &str| {
+            line.spans
+                .iter()
+                .find(|span| span.content.contains(text))
+                .expect("span")
+        };
+        assert_eq!(span("\u{e26d}").style.bg, bg);
+        assert_eq!(span("GPT 5").style.bg, bg);
+        // 分隔符和没有配置背景的 segment 保持透明
+        assert_eq!(span(separators::SIMPLE.trim()).style.bg, None);
+        assert_eq!(span("main").style.bg, None);
+    }
+
+    #[test]
+    fn test_separator_color_and_dim() {
+        let mut config = ThemePresets::get_default();
+        let separator_style = |config: &CxLineConfig| {
+            let mut renderer = StatusLineRenderer::new(config);
+            renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+            renderer.add_segment(SegmentId::Git, SegmentData::new("main"));
+            let line = renderer.render_line();
+            line.spans
+                .iter()
+                .find(|span| span.content == separators::SIMPLE)
+                .expect("separator span")
+                .style
+        };
+
+        // 默认保持原来的变暗效果
+        assert_eq!(separator_style(&config), Style::default().dim());
+
+        config.separator_color = Some(ansi16::BRIGHT_WHITE);
+        config.separator_dim = false;
+        assert_eq!(
+            separator_style(&config),
+            Style::default().fg(ansi16::BRIGHT_WHITE.to_ratatui_color())
+        );
+    }
+
+    #[test]
+    fn test_powerline_separator_glyphs_keep_transition_colors() {
+        let mut config = ThemePresets::get_powerline_dark();
+        let model_bg = config
+            .segments
+            .get(SegmentId::Model)
+            .colors
+            .background_color();
+        let git_bg = config
+            .segments
+            .get(SegmentId::Git)
+            .colors
+            .background_color();
+
+        for glyph in PowerlineGlyph::ALL {
+            config.powerline_separator = glyph;
+            let mut renderer = StatusLineRenderer::new(&config);
+            renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+            renderer.add_segment(SegmentId::Git, SegmentData::new("main"));
+            let line = renderer.render_line();
+
+            let arrow = line
+                .spans
+                .iter()
+                .find(|span| span.content == glyph.filled())
+                .expect("transition span");
+            assert_eq!(arrow.style.fg, model_bg, "{glyph:?}");
+            assert_eq!(arrow.style.bg, git_bg, "{glyph:?}");
+        }
+    }
+
+    #[test]
+    fn test_powerline_trailing_cap() {
+        let mut config = ThemePresets::get_powerline_dark();
+        let last_span = |config: &CxLineConfig| {
+            let mut renderer = StatusLineRenderer::new(config);
+            renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+            renderer.add_segment(SegmentId::Git, SegmentData::new("main"));
+            renderer.render_line().spans.last().cloned().expect("spans")
+        };
+
+        let cap = last_span(&config);
+        assert_eq!(cap.content, separators::POWERLINE);
+        assert_eq!(
+            cap.style.fg,
+            config
+                .segments
+                .get(SegmentId::Git)
+                .colors
+                .background_color()
+        );
+        assert_eq!(cap.style.bg, None);
+
+        config.powerline_trailing_cap = false;
+        assert_eq!(last_span(&config).content, " ");
+    }
+
+    #[test]
+    fn test_to_ansi_string() {
+        let mut config = ThemePresets::get_powerline_dark();
+        config.segments.get_mut(SegmentId::Model).styles.text_bold = true;
+        let mut renderer = StatusLineRenderer::new(&config);
+        renderer.add_segment(SegmentId::Model, SegmentData::new("GPT 5"));
+        renderer.add_segment(SegmentId::Git, SegmentData::new("main"));
+        let ansi = renderer.to_ansi_string();
+
+        // model pill 加粗：白色前景 + model 背景色，pill 结束后关闭加粗
+        assert!(
+            ansi.starts_with("\x1b[1m\x1b[38;2;255;255;255;48;2;45;45;45m "),
+            "{ansi:?}"
+        );
+        assert!(ansi.contains("GPT 5 \x1b[22m"), "{ansi:?}");
+        assert!(ansi.contains("main"));
+        assert!(ansi.ends_with("\x1b[39m\x1b[49m\x1b[0m"), "{ansi:?}");
     }
 }
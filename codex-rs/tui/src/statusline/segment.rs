@@ -1,11 +1,13 @@
 // 状态栏 Segment 定义
 // 参考 CCometixLine 的设计模式
 
+use super::style::AnsiColor;
+use super::style::IconConfig;
 use ratatui::style::Color;
 use std::collections::HashMap;
 
 /// Segment 数据，由各 Segment 实现收集后返回
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct SegmentData {
     /// 主要内容
     pub primary: String,
@@ -13,6 +15,34 @@ pub struct SegmentData {
     pub secondary: String,
     /// 元数据（用于动态图标等）
     pub metadata: HashMap<String, String>,
+    /// 动态文本颜色（优先于配置中的文本颜色）
+    pub color_hint: Option<AnsiColor>,
+    /// 动态图标颜色（仅作用于图标，优先于 color_hint）
+    pub icon_color_hint: Option<AnsiColor>,
+    /// 动态图标，优先于配置中的图标，按样式模式选用 plain 或 Nerd Font 字形
+    pub icon_override: Option<IconConfig>,
+    /// 强制文本颜色，优先于 color_hint 和配置颜色（如 git 冲突时标红）
+    pub color_override: Option<AnsiColor>,
+    /// 详细描述（不参与渲染，供宿主的详情视图使用）
+    pub detail: Option<String>,
+    /// 尾部徽标（如冲突数、任务数），以独立颜色显示在次要内容之后
+    pub badge: Option<String>,
+    /// 点击动作（不参与渲染，由宿主映射到鼠标事件）
+    pub action: Option<SegmentAction>,
+    /// 副标题（显示在状态栏下一行，与 segment 内容对齐）
+    pub subtitle: Option<String>,
+    /// 宽度不足时代替主要内容的紧凑文本（如 git 只显示状态符号）
+    pub alt_text: Option<String>,
+}
+
+/// Segment 点击动作
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentAction {
+    /// 在编辑器中打开配置
+    OpenEditor,
+    /// 执行命令，例如 "git status"
+    RunCommand(String),
 }
 
 impl SegmentData {
@@ -21,6 +51,15 @@ impl SegmentData {
             primary: primary.into(),
             secondary: String::new(),
             metadata: HashMap::new(),
+            color_hint: None,
+            icon_color_hint: None,
+            icon_override: None,
+            color_override: None,
+            detail: None,
+            badge: None,
+            action: None,
+            subtitle: None,
+            alt_text: None,
         }
     }
 
@@ -33,6 +72,64 @@ impl SegmentData {
         self.metadata.insert(key.into(), value.into());
         self
     }
+
+    pub fn with_color_hint(mut self, color: AnsiColor) -> Self {
+        self.color_hint = Some(color);
+        self
+    }
+
+    pub fn with_icon_color_hint(mut self, color: AnsiColor) -> Self {
+        self.icon_color_hint = Some(color);
+        self
+    }
+
+    pub fn with_icon_override(mut self, icon: IconConfig) -> Self {
+        self.icon_override = Some(icon);
+        self
+    }
+
+    /// 按本次渲染的状态强制文本颜色，见 [`SegmentData::color_override`]
+    pub fn with_dynamic_color(mut self, color: AnsiColor) -> Self {
+        self.color_override = Some(color);
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_badge(mut self, badge: impl Into<String>) -> Self {
+        self.badge = Some(badge.into());
+        self
+    }
+
+    pub fn with_action(mut self, action: SegmentAction) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    pub fn with_subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    pub fn with_alt_text(mut self, alt_text: impl Into<String>) -> Self {
+        self.alt_text = Some(alt_text.into());
+        self
+    }
+
+    /// 紧凑形式：以 alt_text 作为主要内容，去掉次要内容和副标题；没有 alt_text 时返回 None
+    pub fn compact(&self) -> Option<SegmentData> {
+        let alt_text = self.alt_text.clone()?;
+        Some(SegmentData {
+            primary: alt_text,
+            secondary: String::new(),
+            subtitle: None,
+            alt_text: None,
+            ..self.clone()
+        })
+    }
 }
 
 /// Segment 样式
@@ -76,9 +173,36 @@ pub enum SegmentId {
     Git,
     Context,
     Usage,
+    Budget,
+    Jj,
+    Edits,
+    Timezone,
+    Host,
+    Warning,
 }
 
 impl SegmentId {
+    /// 所有 segment，按默认显示顺序排列
+    /// jj 与 git 互斥，占据同一位置
+    pub const ALL: [SegmentId; 11] = [
+        Self::Model,
+        Self::Directory,
+        Self::Jj,
+        Self::Git,
+        Self::Edits,
+        Self::Context,
+        Self::Usage,
+        Self::Budget,
+        Self::Timezone,
+        Self::Host,
+        Self::Warning,
+    ];
+
+    /// 所有 segment，见 [`SegmentId::ALL`]
+    pub fn all() -> &'static [SegmentId] {
+        &Self::ALL
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Model => "model",
@@ -86,6 +210,12 @@ impl SegmentId {
             Self::Git => "git",
             Self::Context => "context",
             Self::Usage => "usage",
+            Self::Budget => "budget",
+            Self::Jj => "jj",
+            Self::Edits => "edits",
+            Self::Timezone => "timezone",
+            Self::Host => "host",
+            Self::Warning => "warning",
         }
     }
 }
@@ -98,3 +228,28 @@ pub trait Segment {
     /// 返回 segment ID
     fn id(&self) -> SegmentId;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_segment_ids() {
+        assert_eq!(SegmentId::ALL.len(), 11);
+        for id in [
+            SegmentId::Model,
+            SegmentId::Directory,
+            SegmentId::Git,
+            SegmentId::Context,
+            SegmentId::Usage,
+            SegmentId::Budget,
+            SegmentId::Jj,
+            SegmentId::Edits,
+            SegmentId::Timezone,
+            SegmentId::Host,
+            SegmentId::Warning,
+        ] {
+            assert!(SegmentId::all().contains(&id), "{}", id.as_str());
+        }
+    }
+}
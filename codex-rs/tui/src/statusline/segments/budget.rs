@@ -0,0 +1,99 @@
+// Budget Segment - 显示会话剩余 token 预算
+
+use super::context::format_tokens;
+use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
+use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentData;
+use crate::statusline::segment::SegmentId;
+use crate::statusline::style::ansi16;
+
+/// 默认在剩余不足 10% 时标红
+const DEFAULT_WARN_FRACTION: f64 = 0.1;
+
+pub struct BudgetSegment {
+    warn_fraction: f64,
+}
+
+impl Default for BudgetSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BudgetSegment {
+    pub fn new() -> Self {
+        Self {
+            warn_fraction: DEFAULT_WARN_FRACTION,
+        }
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        let mut segment = Self::new();
+        if let Some(fraction) = config.option::<f64>("warn_fraction") {
+            segment = segment.with_warn_fraction(fraction);
+        }
+        segment
+    }
+
+    /// 剩余预算低于总预算的该比例时显示为红色
+    pub fn with_warn_fraction(mut self, fraction: f64) -> Self {
+        self.warn_fraction = fraction;
+        self
+    }
+}
+
+impl Segment for BudgetSegment {
+    fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
+        let remaining = ctx.token_budget_remaining?;
+        let display = format!("budget: {}", format_tokens(remaining.max(0)));
+
+        let mut data = SegmentData::new(display).with_metadata("remaining", remaining.to_string());
+
+        if let Some(total) = ctx.token_budget_total.filter(|total| *total > 0) {
+            data = data.with_metadata("total", total.to_string());
+            if (remaining as f64) < total as f64 * self.warn_fraction {
+                data = data.with_color_hint(ansi16::RED);
+            }
+        }
+
+        Some(data)
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Budget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_healthy_budget() {
+        let ctx = StatusLineContext::new("", Path::new("/tmp"))
+            .with_token_budget(Some(12_500), Some(100_000));
+        let data = BudgetSegment::new().collect(&ctx).expect("budget data");
+
+        assert_eq!(data.primary, "budget: 12.5k");
+        assert_eq!(data.color_hint, None);
+    }
+
+    #[test]
+    fn test_near_depletion_budget_is_red() {
+        let ctx = StatusLineContext::new("", Path::new("/tmp"))
+            .with_token_budget(Some(2_000), Some(100_000));
+        let data = BudgetSegment::new().collect(&ctx).expect("budget data");
+
+        assert_eq!(data.primary, "budget: 2.0k");
+        assert_eq!(data.color_hint, Some(ansi16::RED));
+    }
+
+    #[test]
+    fn test_absent_budget_is_hidden() {
+        let ctx = StatusLineContext::new("", Path::new("/tmp"));
+        assert!(BudgetSegment::new().collect(&ctx).is_none());
+    }
+}
@@ -1,55 +1,270 @@
 // Context Segment - 显示上下文窗口使用情况
 
+use super::valid_percent;
 use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
 use crate::statusline::segment::Segment;
 use crate::statusline::segment::SegmentData;
 use crate::statusline::segment::SegmentId;
+use crate::statusline::style::AnsiColor;
+use crate::statusline::style::IconConfig;
+use crate::statusline::style::ansi16;
+use serde::Deserialize;
+use serde::Serialize;
 
-pub struct ContextSegment;
+/// token 数的显示格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberStyle {
+    /// 缩写："1.2M"
+    #[default]
+    Compact,
+    /// 千位分组："1,234,567"
+    Grouped,
+    /// 完整数字："1234567"
+    Plain,
+}
+
+/// 主要内容的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextDisplay {
+    /// "62% · 124.0k tokens"
+    #[default]
+    Text,
+    /// "▰▰▰▱▱ 62%"
+    Bar,
+}
+
+/// 进度条默认格数
+const DEFAULT_BAR_WIDTH: usize = 5;
+const BAR_FILLED: char = '▰';
+const BAR_EMPTY: char = '▱';
+
+/// 使用占比达到该值（%）时文本变黄，可通过 options["warn_at"] 调整
+const DEFAULT_WARN_AT: f64 = 60.0;
+/// 使用占比超过该值（%）时文本变红，可通过 options["crit_at"] 调整
+const DEFAULT_CRIT_AT: f64 = 85.0;
+
+/// 剩余模式下，剩余不足窗口的 10% 时标红
+const REMAINING_WARN_FRACTION: f64 = 0.1;
+
+pub struct ContextSegment {
+    /// 按使用占比切换电池图标，关闭时使用配置中的静态图标
+    dynamic_icon: bool,
+    number_style: NumberStyle,
+    /// Grouped 模式下的千位分隔符
+    group_separator: String,
+    /// 显示剩余 token 数而不是已使用数
+    display_remaining: bool,
+    /// 已使用 token 数超过该值后才显示，没有 token 数据时也隐藏
+    min_tokens: Option<i64>,
+    display: ContextDisplay,
+    /// Bar 模式下进度条的格数
+    bar_width: usize,
+    /// 文本颜色阈值（使用占比 %）：低于 warn_at 沿用主题颜色，warn_at 到 crit_at 为黄，超过 crit_at 为红
+    warn_at: f64,
+    crit_at: f64,
+}
+
+impl Default for ContextSegment {
+    fn default() -> Self {
+        Self {
+            dynamic_icon: false,
+            number_style: NumberStyle::Compact,
+            group_separator: ",".to_string(),
+            display_remaining: false,
+            min_tokens: None,
+            display: ContextDisplay::Text,
+            bar_width: DEFAULT_BAR_WIDTH,
+            warn_at: DEFAULT_WARN_AT,
+            crit_at: DEFAULT_CRIT_AT,
+        }
+    }
+}
+
+impl ContextSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        let mut segment = Self::new()
+            .with_dynamic_icon(config.option_bool("dynamic_icon").unwrap_or(false))
+            .with_number_style(config.option("number_style").unwrap_or_default())
+            .with_display_remaining(config.option_bool("display_remaining").unwrap_or(false))
+            .with_min_tokens(config.option::<i64>("min_tokens"))
+            .with_display(
+                config.option("display").unwrap_or_default(),
+                config.option::<usize>("bar_width"),
+            )
+            .with_thresholds(
+                config.option::<f64>("warn_at").unwrap_or(DEFAULT_WARN_AT),
+                config.option::<f64>("crit_at").unwrap_or(DEFAULT_CRIT_AT),
+            );
+        if let Some(separator) = config.option_str("group_separator") {
+            segment.group_separator = separator.to_string();
+        }
+        segment
+    }
+
+    pub fn with_dynamic_icon(mut self, enabled: bool) -> Self {
+        self.dynamic_icon = enabled;
+        self
+    }
+
+    pub fn with_number_style(mut self, style: NumberStyle) -> Self {
+        self.number_style = style;
+        self
+    }
+
+    /// 显示 "154.8k left"；窗口大小未知时仍显示已使用数
+    pub fn with_display_remaining(mut self, enabled: bool) -> Self {
+        self.display_remaining = enabled;
+        self
+    }
+
+    pub fn with_min_tokens(mut self, min_tokens: Option<i64>) -> Self {
+        self.min_tokens = min_tokens;
+        self
+    }
+
+    /// 设置显示方式，bar_width 为 None 时使用默认格数
+    pub fn with_display(mut self, display: ContextDisplay, bar_width: Option<usize>) -> Self {
+        self.display = display;
+        self.bar_width = bar_width.unwrap_or(DEFAULT_BAR_WIDTH);
+        self
+    }
+
+    pub fn with_thresholds(mut self, warn_at: f64, crit_at: f64) -> Self {
+        self.warn_at = warn_at;
+        self.crit_at = crit_at;
+        self
+    }
+
+    /// 按使用占比选择文本颜色，低于 warn_at 时返回 None 以保留主题颜色
+    fn level_color(&self, percent: i64) -> Option<AnsiColor> {
+        let percent = percent as f64;
+        if percent > self.crit_at {
+            Some(ansi16::RED)
+        } else if percent >= self.warn_at {
+            Some(ansi16::YELLOW)
+        } else {
+            None
+        }
+    }
+
+    /// 没有可用数据时的占位显示，Bar 模式下为空进度条
+    fn placeholder(&self) -> SegmentData {
+        match self.display {
+            ContextDisplay::Text => placeholder(),
+            ContextDisplay::Bar => SegmentData {
+                primary: render_bar(0, self.bar_width),
+                ..placeholder()
+            },
+        }
+    }
+
+    /// 按配置的格式显示 token 数
+    fn format_count(&self, tokens: i64) -> String {
+        match self.number_style {
+            NumberStyle::Compact => format_tokens(tokens),
+            NumberStyle::Grouped => group_digits(tokens, &self.group_separator),
+            NumberStyle::Plain => tokens.to_string(),
+        }
+    }
+}
 
 impl Segment for ContextSegment {
     fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
+        if let Some(min_tokens) = self.min_tokens
+            && ctx
+                .context_used_tokens
+                .is_none_or(|used| used <= min_tokens)
+        {
+            return None;
+        }
+
         // 如果有 token 数和窗口大小，计算使用占比
         // 使用占比 = (已使用 tokens / 窗口大小) * 100
         let used_percent = match (ctx.context_used_tokens, ctx.context_window_size) {
             (Some(used), Some(window)) if window > 0 => {
-                Some((used as f64 / window as f64 * 100.0) as i64)
+                match valid_percent(used as f64 / window as f64 * 100.0) {
+                    Some(percent) => Some(percent as i64),
+                    // 异常数据按没有数据处理，显示占位符
+                    None => return Some(self.placeholder()),
+                }
             }
             _ => None,
         };
 
         // 根据数据情况显示
         match (used_percent, ctx.context_used_tokens) {
+            (Some(percent), Some(used_tokens)) if self.display_remaining => {
+                let window = ctx.context_window_size.unwrap_or_default();
+                let remaining = (window - used_tokens).max(0);
+                let mut data = SegmentData::new(format!("{} left", self.format_count(remaining)))
+                    .with_detail(format!(
+                        "{remaining} / {window} tokens left ({}%)",
+                        100 - percent.min(100)
+                    ))
+                    .with_metadata("percent", percent.to_string())
+                    .with_metadata("tokens", used_tokens.to_string())
+                    .with_metadata("remaining", remaining.to_string())
+                    .with_metadata("type", "remaining");
+                if let Some(color) = self.level_color(percent) {
+                    data = data.with_color_hint(color);
+                }
+                if (remaining as f64) < window as f64 * REMAINING_WARN_FRACTION {
+                    data = data.with_color_hint(ansi16::RED);
+                }
+                if self.dynamic_icon {
+                    data = data.with_icon_override(get_gauge_icon(percent));
+                }
+                Some(data)
+            }
             (Some(percent), Some(used_tokens)) => {
-                // 格式: {percentage}% · {tokens} tokens
+                // 格式: {percentage}% · {tokens} tokens，或 {bar} {percentage}%
                 let percentage_display = format!("{percent}%");
-                let tokens_display = format!("{} tokens", format_tokens(used_tokens));
-                let display = format!("{percentage_display} · {tokens_display}");
-                Some(
-                    SegmentData::new(display)
-                        .with_metadata("percent", percent.to_string())
-                        .with_metadata("tokens", used_tokens.to_string())
-                        .with_metadata("type", "full"),
-                )
+                let display = match self.display {
+                    ContextDisplay::Text => {
+                        let tokens_display = format!("{} tokens", self.format_count(used_tokens));
+                        format!("{percentage_display} · {tokens_display}")
+                    }
+                    ContextDisplay::Bar => {
+                        format!(
+                            "{} {percentage_display}",
+                            render_bar(percent, self.bar_width)
+                        )
+                    }
+                };
+                let window = ctx.context_window_size.unwrap_or_default();
+                let mut data = SegmentData::new(display)
+                    .with_detail(format!("{used_tokens} / {window} tokens used ({percent}%)"))
+                    .with_metadata("percent", percent.to_string())
+                    .with_metadata("tokens", used_tokens.to_string())
+                    .with_metadata("type", "full");
+                if let Some(color) = self.level_color(percent) {
+                    data = data.with_color_hint(color);
+                }
+                if self.dynamic_icon {
+                    data = data.with_icon_override(get_gauge_icon(percent));
+                }
+                Some(data)
             }
             (None, Some(used_tokens)) => {
                 // 只有 token 数（没有窗口大小，无法计算百分比）
-                let display = format!("{} tokens", format_tokens(used_tokens));
+                let display = format!("{} tokens", self.format_count(used_tokens));
                 Some(
                     SegmentData::new(display)
+                        .with_detail(format!("{used_tokens} tokens used"))
                         .with_metadata("tokens", used_tokens.to_string())
                         .with_metadata("type", "tokens"),
                 )
             }
-            _ => {
-                // 没有数据时显示占位符
-                Some(
-                    SegmentData::new("- · - tokens".to_string())
-                        .with_metadata("percent", "-".to_string())
-                        .with_metadata("tokens", "-".to_string())
-                        .with_metadata("type", "placeholder"),
-                )
-            }
+            // 没有数据时显示占位符
+            _ => Some(self.placeholder()),
         }
     }
 
@@ -58,8 +273,39 @@ impl Segment for ContextSegment {
     }
 }
 
+/// 没有可用数据时的占位显示
+fn placeholder() -> SegmentData {
+    SegmentData::new("- · - tokens".to_string())
+        .with_metadata("percent", "-".to_string())
+        .with_metadata("tokens", "-".to_string())
+        .with_metadata("type", "placeholder")
+}
+
+/// 按使用占比绘制进度条，超过 100% 时填满
+fn render_bar(percent: i64, width: usize) -> String {
+    let filled = ((percent.clamp(0, 100) as f64 / 100.0) * width as f64).round() as usize;
+    let mut bar = String::with_capacity(width * BAR_FILLED.len_utf8());
+    bar.extend(std::iter::repeat_n(BAR_FILLED, filled));
+    bar.extend(std::iter::repeat_n(BAR_EMPTY, width - filled));
+    bar
+}
+
+/// 根据使用占比获取电池图标
+/// 使用 Nerd Font Material Design Icons
+fn get_gauge_icon(percent: i64) -> IconConfig {
+    let (plain, nerd_font) = match percent {
+        ..=9 => ("○", "\u{f008e}"),    // battery_outline
+        10..=29 => ("◔", "\u{f007b}"), // battery_20
+        30..=49 => ("◔", "\u{f007d}"), // battery_40
+        50..=69 => ("◑", "\u{f007f}"), // battery_60
+        70..=89 => ("◕", "\u{f0081}"), // battery_80
+        _ => ("●", "\u{f0079}"),       // battery (full)
+    };
+    IconConfig::new(plain, nerd_font)
+}
+
 /// 格式化 token 数量
-fn format_tokens(tokens: i64) -> String {
+pub(crate) fn format_tokens(tokens: i64) -> String {
     if tokens >= 1_000_000 {
         format!("{:.1}M", tokens as f64 / 1_000_000.0)
     } else if tokens >= 1_000 {
@@ -69,9 +315,27 @@ fn format_tokens(tokens: i64) -> String {
     }
 }
 
+/// 千位分组，例如 (1234567, ",") -> "1,234,567"
+pub(crate) fn group_digits(value: i64, separator: &str) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 * separator.len() + 1);
+    if value < 0 {
+        out.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(separator);
+        }
+        out.push(c);
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::statusline::style::StyleMode;
+    use std::path::Path;
 
     #[test]
     fn test_format_tokens() {
@@ -80,4 +344,243 @@ mod tests {
         assert_eq!(format_tokens(150000), "150.0k");
         assert_eq!(format_tokens(1500000), "1.5M");
     }
+
+    #[test]
+    fn test_detail_has_exact_token_counts() {
+        let ctx =
+            StatusLineContext::new("", Path::new("/")).with_context(Some(12_345), Some(200_000));
+        let data = ContextSegment::new().collect(&ctx).expect("context data");
+        assert_eq!(
+            data.detail.as_deref(),
+            Some("12345 / 200000 tokens used (6%)")
+        );
+
+        let ctx = StatusLineContext::new("", Path::new("/"));
+        let data = ContextSegment::new()
+            .collect(&ctx)
+            .expect("placeholder data");
+        assert_eq!(data.detail, None);
+    }
+
+    #[test]
+    fn test_dynamic_gauge_icon() {
+        let segment = ContextSegment::new().with_dynamic_icon(true);
+        let icon_at = |used, style| {
+            let ctx =
+                StatusLineContext::new("", Path::new("/")).with_context(Some(used), Some(1000));
+            segment
+                .collect(&ctx)
+                .and_then(|data| data.icon_override)
+                .map(|icon| icon.get(style).to_string())
+        };
+
+        assert_eq!(
+            icon_at(0, StyleMode::NerdFont).as_deref(),
+            Some("\u{f008e}")
+        );
+        assert_eq!(
+            icon_at(500, StyleMode::NerdFont).as_deref(),
+            Some("\u{f007f}")
+        );
+        assert_eq!(
+            icon_at(1000, StyleMode::NerdFont).as_deref(),
+            Some("\u{f0079}")
+        );
+
+        // Plain 模式不使用 Nerd Font 字形
+        assert_eq!(icon_at(0, StyleMode::Plain).as_deref(), Some("○"));
+        assert_eq!(icon_at(500, StyleMode::Plain).as_deref(), Some("◑"));
+        assert_eq!(icon_at(1000, StyleMode::Plain).as_deref(), Some("●"));
+
+        // 默认关闭，使用静态图标
+        let ctx = StatusLineContext::new("", Path::new("/")).with_context(Some(500), Some(1000));
+        let data = ContextSegment::new().collect(&ctx).expect("context data");
+        assert!(data.icon_override.is_none());
+    }
+
+    #[test]
+    fn test_number_style() {
+        let ctx = StatusLineContext::new("", Path::new("/")).with_context(Some(1_234_567), None);
+        let display = |style| {
+            ContextSegment::new()
+                .with_number_style(style)
+                .collect(&ctx)
+                .map(|data| data.primary)
+        };
+        assert_eq!(
+            display(NumberStyle::Compact).as_deref(),
+            Some("1.2M tokens")
+        );
+        assert_eq!(
+            display(NumberStyle::Grouped).as_deref(),
+            Some("1,234,567 tokens")
+        );
+        assert_eq!(
+            display(NumberStyle::Plain).as_deref(),
+            Some("1234567 tokens")
+        );
+
+        let mut config = SegmentItemConfig::default_for(SegmentId::Context);
+        config
+            .options
+            .insert("number_style".to_string(), serde_json::json!("grouped"));
+        config
+            .options
+            .insert("group_separator".to_string(), serde_json::json!("."));
+        let data = ContextSegment::from_config(&config)
+            .collect(&ctx)
+            .expect("context data");
+        assert_eq!(data.primary, "1.234.567 tokens");
+    }
+
+    #[test]
+    fn test_group_digits() {
+        assert_eq!(group_digits(0, ","), "0");
+        assert_eq!(group_digits(999, ","), "999");
+        assert_eq!(group_digits(1000, ","), "1,000");
+        assert_eq!(group_digits(-1_234_567, " "), "-1 234 567");
+    }
+
+    #[test]
+    fn test_display_remaining() {
+        let segment = ContextSegment::new().with_display_remaining(true);
+
+        let ctx =
+            StatusLineContext::new("", Path::new("/")).with_context(Some(154_800), Some(309_600));
+        let data = segment.collect(&ctx).expect("context data");
+        assert_eq!(data.primary, "154.8k left");
+        assert_eq!(
+            data.metadata.get("remaining").map(String::as_str),
+            Some("154800")
+        );
+        assert_eq!(data.color_hint, None);
+
+        // 接近耗尽时标红
+        let ctx =
+            StatusLineContext::new("", Path::new("/")).with_context(Some(195_000), Some(200_000));
+        let data = segment.collect(&ctx).expect("context data");
+        assert_eq!(data.primary, "5.0k left");
+        assert_eq!(data.color_hint, Some(ansi16::RED));
+
+        // 窗口大小未知时退回显示已使用数
+        let ctx = StatusLineContext::new("", Path::new("/")).with_context(Some(12_345), None);
+        let data = segment.collect(&ctx).expect("context data");
+        assert_eq!(data.primary, "12.3k tokens");
+        assert_eq!(
+            data.metadata.get("type").map(String::as_str),
+            Some("tokens")
+        );
+    }
+
+    #[test]
+    fn test_min_tokens() {
+        let segment = ContextSegment::new().with_min_tokens(Some(5_000));
+        let collect = |used| {
+            let ctx = StatusLineContext::new("", Path::new("/")).with_context(used, Some(200_000));
+            segment.collect(&ctx).map(|data| data.primary)
+        };
+
+        assert_eq!(collect(Some(1_200)), None);
+        assert_eq!(collect(Some(5_000)), None);
+        assert_eq!(collect(Some(12_000)).as_deref(), Some("6% · 12.0k tokens"));
+        // 没有 token 数据时隐藏，而不是显示占位符
+        assert_eq!(collect(None), None);
+
+        let mut config = SegmentItemConfig::default_for(SegmentId::Context);
+        config
+            .options
+            .insert("min_tokens".to_string(), serde_json::json!(5_000));
+        let ctx = StatusLineContext::new("", Path::new("/")).with_context(Some(100), Some(200_000));
+        assert!(ContextSegment::from_config(&config).collect(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_invalid_percent_renders_placeholder() {
+        assert_eq!(valid_percent(f64::NAN), None);
+        assert_eq!(valid_percent(f64::INFINITY), None);
+        assert_eq!(valid_percent(-5.0), None);
+        assert_eq!(valid_percent(42.0), Some(42.0));
+
+        // 负的 token 数会得到负百分比
+        let ctx = StatusLineContext::new("", Path::new("/")).with_context(Some(-10), Some(200));
+        let data = ContextSegment::new()
+            .collect(&ctx)
+            .expect("placeholder data");
+        assert_eq!(data.primary, "- · - tokens");
+        assert_eq!(
+            data.metadata.get("type").map(String::as_str),
+            Some("placeholder")
+        );
+    }
+
+    #[test]
+    fn test_bar_display() {
+        let filled = |bar: &str| bar.chars().filter(|c| *c == BAR_FILLED).count();
+        assert_eq!(filled(&render_bar(0, 10)), 0);
+        assert_eq!(filled(&render_bar(50, 10)), 5);
+        assert_eq!(filled(&render_bar(100, 10)), 10);
+        assert_eq!(render_bar(150, 4).chars().count(), 4);
+
+        let mut config = SegmentItemConfig::default_for(SegmentId::Context);
+        config
+            .options
+            .insert("display".to_string(), serde_json::json!("bar"));
+        let segment = ContextSegment::from_config(&config);
+        let ctx = StatusLineContext::new("", Path::new("/")).with_context(Some(62), Some(100));
+        let data = segment.collect(&ctx).expect("context data");
+        assert_eq!(data.primary, "▰▰▰▱▱ 62%");
+
+        config
+            .options
+            .insert("bar_width".to_string(), serde_json::json!(8));
+        let segment = ContextSegment::from_config(&config);
+        let data = segment.collect(&ctx).expect("context data");
+        assert_eq!(data.primary, "▰▰▰▰▰▱▱▱ 62%");
+
+        // 没有 token 数据时显示空进度条
+        let ctx = StatusLineContext::new("", Path::new("/"));
+        let data = segment.collect(&ctx).expect("placeholder data");
+        assert_eq!(data.primary, "▱▱▱▱▱▱▱▱");
+        assert_eq!(
+            data.metadata.get("type").map(String::as_str),
+            Some("placeholder")
+        );
+    }
+
+    #[test]
+    fn test_usage_threshold_colors() {
+        let hint = |segment: &ContextSegment, used: i64| {
+            let ctx =
+                StatusLineContext::new("", Path::new("/")).with_context(Some(used), Some(100));
+            segment.collect(&ctx).expect("context data").color_hint
+        };
+
+        let segment = ContextSegment::new();
+        assert_eq!(hint(&segment, 10), None);
+        assert_eq!(hint(&segment, 60), Some(ansi16::YELLOW));
+        assert_eq!(hint(&segment, 85), Some(ansi16::YELLOW));
+        assert_eq!(hint(&segment, 86), Some(ansi16::RED));
+
+        let mut config = SegmentItemConfig::default_for(SegmentId::Context);
+        config
+            .options
+            .insert("warn_at".to_string(), serde_json::json!(30));
+        config
+            .options
+            .insert("crit_at".to_string(), serde_json::json!(50.5));
+        let segment = ContextSegment::from_config(&config);
+        assert_eq!(hint(&segment, 29), None);
+        assert_eq!(hint(&segment, 30), Some(ansi16::YELLOW));
+        assert_eq!(hint(&segment, 51), Some(ansi16::RED));
+
+        // 只有 token 数时无法计算占比，不着色
+        let ctx = StatusLineContext::new("", Path::new("/")).with_context(Some(10), None);
+        assert_eq!(
+            ContextSegment::new()
+                .collect(&ctx)
+                .expect("data")
+                .color_hint,
+            None
+        );
+    }
 }
@@ -1,16 +1,119 @@
 // Directory Segment - 显示当前工作目录名称
 
 use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
 use crate::statusline::segment::Segment;
 use crate::statusline::segment::SegmentData;
 use crate::statusline::segment::SegmentId;
+use std::path::Path;
+use std::path::PathBuf;
 
-pub struct DirectorySegment;
+/// cwd 已被删除时显示的标记
+const DELETED_MARKER: &str = "(deleted)";
+
+pub struct DirectorySegment {
+    /// 显示路径末尾的组件数
+    components: usize,
+    /// 组件之间的连接符
+    join: String,
+    /// cwd 已被删除时隐藏 segment，否则显示 DELETED_MARKER
+    hide_if_missing: bool,
+    /// 将 home 目录前缀显示为 `~`
+    home_tilde: bool,
+    /// 显示指定路径而不是 cwd（相对路径基于 cwd）
+    path: Option<PathBuf>,
+}
+
+impl Default for DirectorySegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirectorySegment {
+    pub fn new() -> Self {
+        Self {
+            components: 1,
+            join: std::path::MAIN_SEPARATOR_STR.to_string(),
+            hide_if_missing: false,
+            home_tilde: false,
+            path: None,
+        }
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        let mut segment = Self::new();
+        // `components` 是 `depth` 的旧名称
+        if let Some(depth) = config
+            .option::<usize>("depth")
+            .or_else(|| config.option::<usize>("components"))
+        {
+            segment = segment.with_components(depth);
+        }
+        if let Some(join) = config.option_str("join") {
+            segment = segment.with_join(join);
+        }
+        if let Some(path) = config.option_str("path") {
+            segment = segment.with_path(path);
+        }
+        segment
+            .with_hide_if_missing(config.option_bool("hide_if_missing").unwrap_or(false))
+            .with_home_tilde(config.option_bool("home_tilde").unwrap_or(false))
+    }
+
+    /// 显示最后 N 个路径组件，例如 2 -> "project/src"
+    pub fn with_components(mut self, components: usize) -> Self {
+        self.components = components.max(1);
+        self
+    }
+
+    /// 设置组件之间的连接符（默认使用系统路径分隔符）
+    pub fn with_join(mut self, join: impl Into<String>) -> Self {
+        self.join = join.into();
+        self
+    }
+
+    pub fn with_hide_if_missing(mut self, hide: bool) -> Self {
+        self.hide_if_missing = hide;
+        self
+    }
+
+    /// 显示指定目录，例如并排的另一个 worktree
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// home 目录及其子目录显示为 "~"、"~/projects"
+    pub fn with_home_tilde(mut self, enabled: bool) -> Self {
+        self.home_tilde = enabled;
+        self
+    }
+}
 
 impl Segment for DirectorySegment {
     fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
-        let cwd = ctx.cwd;
-        let dir_name = extract_directory_name(cwd);
+        let path = self.path.as_ref().map(|path| ctx.cwd.join(path));
+        let cwd = path.as_deref().unwrap_or(ctx.cwd);
+        // 工作目录在会话期间被删除
+        if ctx.check_cwd && !cwd.exists() {
+            if self.hide_if_missing {
+                return None;
+            }
+            return Some(
+                SegmentData::new(DELETED_MARKER)
+                    .with_metadata("full_path", cwd.to_string_lossy())
+                    .with_metadata("missing", "true"),
+            );
+        }
+
+        let home = if self.home_tilde {
+            dirs::home_dir()
+        } else {
+            None
+        };
+        let dir_name = format_directory(cwd, self.components, &self.join, home.as_deref());
 
         if dir_name.is_empty() {
             return None;
@@ -24,20 +127,73 @@ impl Segment for DirectorySegment {
     }
 }
 
+/// 路径分隔符（同时识别 Unix 和 Windows 风格）
+const PATH_SEPARATORS: [char; 2] = ['/', '\\'];
+
 /// 提取目录名称
-/// 支持 Unix 和 Windows 路径
+/// 支持 Unix 和 Windows 路径，与当前平台无关
 fn extract_directory_name(path: &std::path::Path) -> String {
+    let path = path.to_string_lossy();
+
+    // 先去掉末尾分隔符，"/a/b/" 与 "/a/b" 结果一致
+    let trimmed = path.trim_end_matches(PATH_SEPARATORS);
+    if trimmed.is_empty() {
+        // 只包含分隔符即根目录，返回 "/"
+        return if path.is_empty() {
+            String::new()
+        } else {
+            "/".to_string()
+        };
+    }
+
     // 获取最后一个组件（目录名）
-    path.file_name()
-        .map(|name| name.to_string_lossy().to_string())
-        .unwrap_or_else(|| {
-            // 如果是根目录，返回 "/"
-            if path.as_os_str().is_empty() {
-                String::new()
-            } else {
-                "/".to_string()
-            }
-        })
+    trimmed
+        .rsplit(PATH_SEPARATORS)
+        .next()
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+/// 提取路径末尾最多 depth 个组件并用 join 连接，路径组件不足时显示全部可用组件
+/// 传入 home 时，位于 home 下的路径以 "~" 作为第一个组件
+fn format_directory(path: &Path, depth: usize, join: &str, home: Option<&Path>) -> String {
+    let path_str = path.to_string_lossy();
+    let home_str = home.map(Path::to_string_lossy);
+    let (tilde, rest) = match home_str
+        .as_deref()
+        .and_then(|home| strip_home(&path_str, home))
+    {
+        Some(rest) => (Some("~"), rest),
+        None => (None, path_str.as_ref()),
+    };
+
+    let components: Vec<&str> = tilde
+        .into_iter()
+        .chain(rest.split(PATH_SEPARATORS).filter(|c| !c.is_empty()))
+        .collect();
+    match components.as_slice() {
+        [] => extract_directory_name(path),
+        // 只有盘符时保留分隔符，"C:\" 而不是 "C:"
+        [drive] if is_drive(drive) => format!("{drive}\\"),
+        _ => components[components.len().saturating_sub(depth.max(1))..].join(join),
+    }
+}
+
+/// path 位于 home 下时返回去掉 home 前缀后的部分（按组件边界匹配）
+fn strip_home<'a>(path: &'a str, home: &str) -> Option<&'a str> {
+    let home = home.trim_end_matches(PATH_SEPARATORS);
+    // home 为根目录时不替换，否则所有路径都会变成 "~"
+    if home.is_empty() {
+        return None;
+    }
+    let rest = path.strip_prefix(home)?;
+    (rest.is_empty() || rest.starts_with(PATH_SEPARATORS)).then_some(rest)
+}
+
+/// Windows 盘符，例如 "C:"
+fn is_drive(component: &str) -> bool {
+    let bytes = component.as_bytes();
+    bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
 }
 
 #[cfg(test)]
@@ -60,4 +216,116 @@ mod tests {
         // 相对路径
         assert_eq!(extract_directory_name(Path::new("some/path")), "path");
     }
+
+    #[test]
+    fn test_extract_directory_name_trailing_separator() {
+        assert_eq!(extract_directory_name(Path::new("/a/b/")), "b");
+        assert_eq!(extract_directory_name(Path::new("a/b/")), "b");
+        assert_eq!(extract_directory_name(Path::new("/")), "/");
+        assert_eq!(extract_directory_name(Path::new("//")), "/");
+        assert_eq!(extract_directory_name(Path::new("")), "");
+
+        // Windows 路径
+        assert_eq!(extract_directory_name(Path::new("C:\\a\\b\\")), "b");
+        assert_eq!(extract_directory_name(Path::new("C:\\a\\b")), "b");
+    }
+
+    #[test]
+    fn test_two_components() {
+        let segment = DirectorySegment::new().with_components(2).with_join("/");
+        let collect = |path: &str| {
+            let ctx = StatusLineContext::new("", Path::new(path)).with_cwd_check(false);
+            segment.collect(&ctx).map(|data| data.primary)
+        };
+
+        assert_eq!(
+            collect("/home/user/project/src").as_deref(),
+            Some("project/src")
+        );
+        assert_eq!(collect("/project/").as_deref(), Some("project"));
+        assert_eq!(collect("/").as_deref(), Some("/"));
+    }
+
+    #[test]
+    fn test_missing_cwd() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let missing = dir.path().join("removed");
+        let ctx = StatusLineContext::new("", &missing);
+
+        let data = DirectorySegment::new().collect(&ctx).expect("marker");
+        assert_eq!(data.primary, "(deleted)");
+
+        let mut config = SegmentItemConfig::default_for(SegmentId::Directory);
+        config
+            .options
+            .insert("hide_if_missing".to_string(), serde_json::json!(true));
+        assert!(
+            DirectorySegment::from_config(&config)
+                .collect(&ctx)
+                .is_none()
+        );
+
+        // 存在的目录正常显示
+        let ctx = StatusLineContext::new("", dir.path());
+        let data = DirectorySegment::new().collect(&ctx).expect("dir name");
+        assert_ne!(data.primary, "(deleted)");
+    }
+
+    #[test]
+    fn test_format_directory_depth_and_home() {
+        let home = Some(Path::new("/home/me"));
+        let format = |path: &str, depth| format_directory(Path::new(path), depth, "/", home);
+
+        assert_eq!(format("/home/me/projects/codex", 2), "projects/codex");
+        assert_eq!(format("/home/me/projects/codex", 3), "~/projects/codex");
+        assert_eq!(format("/home/me/projects/codex", 5), "~/projects/codex");
+        // 恰好位于 home
+        assert_eq!(format("/home/me", 1), "~");
+        assert_eq!(format("/home/me/", 2), "~");
+        // 前缀相同但不是 home 的子目录
+        assert_eq!(format("/home/meg/src", 3), "home/meg/src");
+        // 根目录
+        assert_eq!(format("/", 2), "/");
+        assert_eq!(
+            format_directory(Path::new("/"), 1, "/", Some(Path::new("/"))),
+            "/"
+        );
+
+        // Windows 盘符
+        assert_eq!(format_directory(Path::new("C:\\"), 2, "/", None), "C:\\");
+        assert_eq!(
+            format_directory(Path::new("C:\\Users\\me\\src"), 2, "/", None),
+            "me/src"
+        );
+        assert_eq!(
+            format_directory(
+                Path::new("C:\\Users\\me\\src"),
+                3,
+                "/",
+                Some(Path::new("C:\\Users\\me"))
+            ),
+            "~/src"
+        );
+    }
+
+    #[test]
+    fn test_depth_option_keeps_full_path() {
+        let mut config = SegmentItemConfig::default_for(SegmentId::Directory);
+        config
+            .options
+            .insert("depth".to_string(), serde_json::json!(2));
+        config
+            .options
+            .insert("join".to_string(), serde_json::json!("/"));
+        let ctx =
+            StatusLineContext::new("", Path::new("/srv/projects/codex")).with_cwd_check(false);
+        let data = DirectorySegment::from_config(&config)
+            .collect(&ctx)
+            .expect("dir name");
+        assert_eq!(data.primary, "projects/codex");
+        assert_eq!(
+            data.metadata.get("full_path").map(String::as_str),
+            Some("/srv/projects/codex")
+        );
+    }
 }
@@ -0,0 +1,51 @@
+// Edits Segment - 显示本次会话中 Codex 修改过的文件数
+
+use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
+use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentData;
+use crate::statusline::segment::SegmentId;
+
+#[derive(Default)]
+pub struct EditsSegment;
+
+impl EditsSegment {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(_config: &SegmentItemConfig) -> Self {
+        Self::new()
+    }
+}
+
+impl Segment for EditsSegment {
+    fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
+        // 未修改任何文件或宿主未提供数据时隐藏
+        let count = ctx.session_edited_files.filter(|count| *count > 0)?;
+        Some(SegmentData::new(count.to_string()).with_metadata("edited_files", count.to_string()))
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Edits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_edited_files() {
+        let collect = |count| {
+            let ctx = StatusLineContext::new("", Path::new("/")).with_session_edited_files(count);
+            EditsSegment::new().collect(&ctx).map(|data| data.primary)
+        };
+
+        assert_eq!(collect(Some(5)).as_deref(), Some("5"));
+        assert_eq!(collect(Some(0)), None);
+        assert_eq!(collect(None), None);
+    }
+}
@@ -1,13 +1,33 @@
 // Git Segment - 显示 Git 分支和状态
 // 搬迁自 CCometixLine
 
+use crate::statusline::BaseDivergence;
+use crate::statusline::CxLineConfig;
 use crate::statusline::GitPreviewData;
+use crate::statusline::RemoteDivergence;
 use crate::statusline::StatusLineContext;
+use crate::statusline::config::DEFAULT_COMMAND_CACHE_MS;
+use crate::statusline::config::SegmentItemConfig;
 use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentAction;
 use crate::statusline::segment::SegmentData;
 use crate::statusline::segment::SegmentId;
+use crate::statusline::style::AnsiColor;
+use crate::statusline::style::ansi16;
+use crate::statusline::truncate::TruncatePosition;
+use crate::statusline::truncate::truncate_display;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
 use std::path::Path;
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
 
 /// Git 状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,140 +38,1021 @@ pub enum GitStatus {
 }
 
 /// Git 信息
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GitInfo {
     pub branch: String,
     pub status: GitStatus,
     pub ahead: u32,
     pub behind: u32,
+    /// 相对 base 的提交数（仅在 show_commit_count 开启时计算）
+    pub commit_count: Option<u32>,
+    /// 工作区变更摘要
+    pub summary: String,
+    /// 冲突文件数
+    pub conflicts: u32,
+    /// 相对各远端同名分支的 ahead/behind
+    pub remotes: Vec<RemoteDivergence>,
+    /// 生效的 user.email
+    pub identity: Option<String>,
+    /// HEAD 的完整提交 SHA
+    pub head_sha: Option<String>,
+    /// 相对记录的 base 提交的分叉情况
+    pub base: Option<BaseDivergence>,
+    /// stash 条目数
+    pub stash: u32,
+    /// 未跟踪文件数
+    pub untracked: u32,
 }
 
-pub struct GitSegment;
+/// 各状态的文本颜色，来自 options 中的 clean_color / dirty_color / conflict_color
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GitStateColors {
+    pub clean: Option<AnsiColor>,
+    pub dirty: Option<AnsiColor>,
+    pub conflict: Option<AnsiColor>,
+}
+
+/// 按仓库根目录记录的 base 提交，保存在 JSON 文件中
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GitBaseStore {
+    bases: HashMap<String, String>,
+}
+
+/// base 记录缓存：记录文件路径 -> 内容，避免每次轮询都读取 JSON，写入时失效
+static BASE_STORE_CACHE: LazyLock<Mutex<HashMap<PathBuf, GitBaseStore>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+impl GitBaseStore {
+    /// 读取记录文件，文件不存在或无法解析时为空
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 读取 repo 记录的 base，记录文件只在首次使用或写入后重新读取
+    pub fn cached_base(path: &Path, repo: &str) -> Option<String> {
+        let mut cache = BASE_STORE_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        cache
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Self::load(path))
+            .get(repo)
+            .map(str::to_string)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        fs::write(path, content)?;
+        BASE_STORE_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(path);
+        Ok(())
+    }
+
+    pub fn get(&self, repo: &str) -> Option<&str> {
+        self.bases.get(repo).map(String::as_str)
+    }
+
+    pub fn set(&mut self, repo: &str, sha: &str) {
+        self.bases.insert(repo.to_string(), sha.to_string());
+    }
+}
+
+/// 远端分叉计数缓存：(HEAD sha, 远端分支 sha) -> (ahead, behind)
+/// 两个提交都不变时结果不变，避免每次轮询都执行 rev-list
+type DivergenceCache = HashMap<(String, String), (u32, u32)>;
+
+static DIVERGENCE_CACHE: LazyLock<Mutex<DivergenceCache>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 分叉计数缓存的条目上限，达到上限时清空（旧 HEAD 的结果不会再被用到）
+const DIVERGENCE_CACHE_CAPACITY: usize = 64;
+
+fn cache_divergence(key: (String, String), counts: (u32, u32)) {
+    let mut cache = DIVERGENCE_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if cache.len() >= DIVERGENCE_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(key, counts);
+}
+
+/// GitInfo 缓存：(规范化后的工作目录, 收集选项) -> (收集时间, 结果)
+/// 不同选项收集到的字段不同，因此选项也是键的一部分
+type GitInfoCache = HashMap<(PathBuf, String), (Instant, GitInfo)>;
+
+static GIT_INFO_CACHE: LazyLock<Mutex<GitInfoCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 丢弃 cwd 下的缓存结果，None 时清空全部（例如 cwd 切换后）
+pub fn invalidate_git_cache(cwd: Option<&Path>) {
+    let mut cache = GIT_INFO_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    match cwd.map(Path::canonicalize) {
+        Some(Ok(dir)) => cache.retain(|(path, _), _| *path != dir),
+        Some(Err(_)) => {}
+        None => cache.clear(),
+    }
+}
+
+/// 一次 git 调用的结果
+#[derive(Debug, Clone, Default)]
+pub struct GitOutput {
+    pub success: bool,
+    pub stdout: String,
+}
+
+/// Git 命令执行器，测试时可注入 mock
+pub trait GitRunner: Send + Sync {
+    /// 在 working_dir 下执行 `git <args>`
+    fn run(&self, working_dir: &str, args: &[&str]) -> std::io::Result<GitOutput>;
+}
+
+/// 调用系统 git 的默认执行器
+pub struct SystemGitRunner;
+
+impl GitRunner for SystemGitRunner {
+    fn run(&self, working_dir: &str, args: &[&str]) -> std::io::Result<GitOutput> {
+        // 工作目录已被删除时不启动进程
+        if !Path::new(working_dir).is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("working directory {working_dir} does not exist"),
+            ));
+        }
+
+        let output = Command::new("git")
+            .arg("--no-optional-locks")
+            .args(args)
+            .current_dir(working_dir)
+            .output()?;
+
+        Ok(GitOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        })
+    }
+}
+
+pub struct GitSegment {
+    runner: Box<dyn GitRunner>,
+    show_commit_count: bool,
+    commit_count_base: Option<String>,
+    /// 将状态符号之后的指示符包在括号中，None 时以空格平铺
+    indicator_brackets: Option<(String, String)>,
+    /// 分支名 glob → 颜色，第一个匹配的规则生效
+    branch_color_rules: Vec<(String, AnsiColor)>,
+    /// 关闭时不显示分支名，主内容为状态符号和 ahead/behind
+    show_branch: bool,
+    /// 分支名的最大显示宽度，超出时按 truncate_position 截断
+    max_branch_len: Option<usize>,
+    /// 分支名截断时省略号的位置，默认在中间
+    truncate_position: TruncatePosition,
+    /// 显示 stash 条目数（需要额外一次 git 调用）
+    show_stash: bool,
+    /// 显示未跟踪文件数
+    show_untracked: bool,
+    /// 同一目录的 git 结果复用时长，为 0 时每次都执行 git
+    cache_ttl: Duration,
+    /// 额外比较的远端，例如 ["origin", "upstream"]
+    remotes: Vec<String>,
+    /// 按状态为图标着色（干净/有修改/冲突），文本保持配置颜色
+    colorize_icon_by_status: bool,
+    /// 状态颜色改用色盲友好的蓝/橙/紫（Okabe-Ito 配色）
+    colorblind_safe: bool,
+    /// 按状态强制整段文本颜色，未设置的状态保持主题颜色（冲突默认标红）
+    state_colors: GitStateColors,
+    /// 显示 user.email 的本地部分
+    show_identity: bool,
+    /// 预期的 user.email glob，不匹配时整段标红；为空时不检查
+    expected_identities: Vec<String>,
+    /// base 提交记录文件，设置时显示相对 base 的 ahead/behind
+    base_store: Option<PathBuf>,
+}
+
+impl Default for GitSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl GitSegment {
+    pub fn new() -> Self {
+        Self {
+            runner: Box::new(SystemGitRunner),
+            show_commit_count: false,
+            commit_count_base: None,
+            indicator_brackets: None,
+            branch_color_rules: Vec::new(),
+            show_branch: true,
+            max_branch_len: None,
+            truncate_position: TruncatePosition::Middle,
+            show_stash: false,
+            show_untracked: false,
+            cache_ttl: Duration::from_millis(DEFAULT_COMMAND_CACHE_MS),
+            remotes: Vec::new(),
+            colorize_icon_by_status: false,
+            colorblind_safe: false,
+            state_colors: GitStateColors::default(),
+            show_identity: false,
+            expected_identities: Vec::new(),
+            base_store: None,
+        }
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        let segment = Self::new()
+            .with_commit_count(
+                config.option_bool("show_commit_count").unwrap_or(false),
+                config.option_str("commit_count_base"),
+            )
+            .with_branch_color_rules(
+                config
+                    .option::<Vec<(String, AnsiColor)>>("branch_color_rules")
+                    .unwrap_or_default(),
+            )
+            .with_show_branch(config.option_bool("show_branch").unwrap_or(true))
+            .with_max_branch_len(config.option::<usize>("max_branch_len"))
+            .with_truncate_position(
+                config
+                    .option("truncate_position")
+                    .unwrap_or(TruncatePosition::Middle),
+            )
+            .with_show_stash(config.option_bool("show_stash").unwrap_or(false))
+            .with_show_untracked(config.option_bool("show_untracked").unwrap_or(false))
+            .with_remotes(config.option::<Vec<String>>("remotes").unwrap_or_default())
+            .with_colorize_icon_by_status(
+                config
+                    .option_bool("colorize_icon_by_status")
+                    .unwrap_or(false),
+            )
+            .with_colorblind_safe(config.option_bool("colorblind_safe").unwrap_or(false))
+            .with_state_colors(GitStateColors {
+                clean: config.option_color("clean_color"),
+                dirty: config.option_color("dirty_color"),
+                conflict: config.option_color("conflict_color"),
+            })
+            .with_identity(
+                config.option_bool("show_identity").unwrap_or(false),
+                config
+                    .option::<Vec<String>>("expected_identities")
+                    .unwrap_or_default(),
+            );
+
+        let segment = if config.option_bool("show_base").unwrap_or(false) {
+            segment.with_base_store(CxLineConfig::resolved_paths().git_bases)
+        } else {
+            segment
+        };
+
+        if config.option_bool("group_indicators").unwrap_or(false) {
+            let (open, close) = config
+                .option::<(String, String)>("indicator_brackets")
+                .unwrap_or_else(|| ("[".to_string(), "]".to_string()));
+            segment.with_grouped_indicators(&open, &close)
+        } else {
+            segment
+        }
+    }
+
+    /// 替换 git 执行器
+    pub fn with_runner(mut self, runner: impl GitRunner + 'static) -> Self {
+        self.runner = Box::new(runner);
+        self
+    }
+
+    /// 显示当前分支相对 base 的提交数
+    /// base 为 None 时使用与默认分支的 merge-base
+    pub fn with_commit_count(mut self, enabled: bool, base: Option<&str>) -> Self {
+        self.show_commit_count = enabled;
+        self.commit_count_base = base.map(str::to_string);
+        self
+    }
+
+    /// 将 ahead/behind 等指示符合并为一组，例如 "● [↑2 ↓1]"
+    pub fn with_grouped_indicators(mut self, open: &str, close: &str) -> Self {
+        self.indicator_brackets = Some((open.to_string(), close.to_string()));
+        self
+    }
+
+    /// 按分支名着色，例如 ("feature/*", 绿色)
+    pub fn with_branch_color_rules(mut self, rules: Vec<(String, AnsiColor)>) -> Self {
+        self.branch_color_rules = rules;
+        self
+    }
+
+    /// 是否显示分支名，关闭时只显示同步状态，例如 "● ↑2↓1"
+    pub fn with_show_branch(mut self, show: bool) -> Self {
+        self.show_branch = show;
+        self
+    }
+
+    /// 分支名超过 max_len 时截断，默认保留首尾、中间以 "…" 截断，例如 "feature/JIRA…ription"
+    pub fn with_max_branch_len(mut self, max_len: Option<usize>) -> Self {
+        self.max_branch_len = max_len;
+        self
+    }
+
+    pub fn with_truncate_position(mut self, position: TruncatePosition) -> Self {
+        self.truncate_position = position;
+        self
+    }
+
+    /// 结果缓存时长，通常为 [`CxLineConfig::command_cache_ttl_for`]；0 表示不缓存
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// 有 stash 时显示 "⚑2"
+    pub fn with_show_stash(mut self, show: bool) -> Self {
+        self.show_stash = show;
+        self
+    }
+
+    /// 有未跟踪文件时显示 "?3"
+    pub fn with_show_untracked(mut self, show: bool) -> Self {
+        self.show_untracked = show;
+        self
+    }
+
+    /// 显示相对各远端同名分支的 ahead/behind，例如 "o:↑2 u:↓5"
+    pub fn with_remotes(mut self, remotes: Vec<String>) -> Self {
+        self.remotes = remotes;
+        self
+    }
+
+    /// 显示当前 git 身份，例如 "<alice>"；expected 为 user.email 的 glob 列表
+    pub fn with_identity(mut self, enabled: bool, expected: Vec<String>) -> Self {
+        self.show_identity = enabled;
+        self.expected_identities = expected;
+        self
+    }
+
+    /// 显示相对记录的 base 提交的 ahead/behind，例如 "base:↑3"
+    /// store 为记录文件路径，None 时关闭
+    pub fn with_base_store(mut self, store: Option<PathBuf>) -> Self {
+        self.base_store = store;
+        self
+    }
+
+    /// 将当前 HEAD 记录为 cwd 所在仓库的 base 提交，返回记录的 SHA
+    pub fn reset_base(&self, cwd: &Path) -> std::io::Result<String> {
+        let Some(store_path) = &self.base_store else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "未启用 show_base",
+            ));
+        };
+        let working_dir = cwd.to_string_lossy();
+        let not_found = |what: &str| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, format!("无法获取 {what}"))
+        };
+        let repo = self
+            .git(&working_dir, &["rev-parse", "--show-toplevel"])
+            .ok_or_else(|| not_found("仓库根目录"))?;
+        let head = self
+            .git(&working_dir, &["rev-parse", "HEAD"])
+            .filter(|sha| !sha.is_empty())
+            .ok_or_else(|| not_found("HEAD"))?;
+
+        let mut store = GitBaseStore::load(store_path);
+        store.set(&repo, &head);
+        store.save(store_path)?;
+        Ok(head)
+    }
+
+    pub fn with_colorize_icon_by_status(mut self, enabled: bool) -> Self {
+        self.colorize_icon_by_status = enabled;
+        self
+    }
+
+    pub fn with_colorblind_safe(mut self, enabled: bool) -> Self {
+        self.colorblind_safe = enabled;
+        self
+    }
+
+    pub fn with_state_colors(mut self, colors: GitStateColors) -> Self {
+        self.state_colors = colors;
+        self
+    }
+
+    /// 状态符号对应的强制文本颜色
+    /// 配置的状态颜色（clean_color / dirty_color / conflict_color）
+    fn configured_state_color(&self, status: GitStatus) -> Option<AnsiColor> {
+        match status {
+            GitStatus::Clean => self.state_colors.clean,
+            GitStatus::Dirty => self.state_colors.dirty,
+            GitStatus::Conflicts => self.state_colors.conflict,
+        }
+    }
+
+    /// 强制的文本颜色：配置的状态颜色优先，冲突未配置时使用默认状态配色
+    fn state_color(&self, status: GitStatus) -> Option<AnsiColor> {
+        self.configured_state_color(status)
+            .or_else(|| match status {
+                GitStatus::Conflicts => Some(status_color(status, self.colorblind_safe)),
+                GitStatus::Clean | GitStatus::Dirty => None,
+            })
+    }
+
+    /// 图标颜色：配置的状态颜色优先，否则使用默认状态配色
+    fn icon_state_color(&self, status: GitStatus) -> AnsiColor {
+        self.configured_state_color(status)
+            .unwrap_or_else(|| status_color(status, self.colorblind_safe))
+    }
+
+    /// 第一个匹配分支名的规则颜色
+    fn branch_color(&self, branch: &str) -> Option<AnsiColor> {
+        self.branch_color_rules
+            .iter()
+            .find(|(pattern, _)| glob_match(pattern, branch))
+            .map(|(_, color)| *color)
+    }
+
+    /// 执行 git 命令，成功时返回 trim 后的 stdout
+    fn git(&self, working_dir: &str, args: &[&str]) -> Option<String> {
+        match self.runner.run(working_dir, args) {
+            Ok(output) if output.success => Some(output.stdout.trim().to_string()),
+            _ => None,
+        }
+    }
+
+    /// 读取 git 信息，TTL 内复用同一目录、同一选项的上次结果
+    /// 目录无法规范化（例如已被删除）时不缓存
     fn get_git_info(&self, working_dir: &Path) -> Option<GitInfo> {
-        let working_dir = working_dir.to_string_lossy();
+        let key = match working_dir.canonicalize() {
+            Ok(dir) if !self.cache_ttl.is_zero() => (dir, self.cache_fingerprint()),
+            _ => return self.read_git_info(working_dir),
+        };
 
-        if !self.is_git_repository(&working_dir) {
-            return None;
+        if let Some((_, info)) = GIT_INFO_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .filter(|(at, _)| at.elapsed() < self.cache_ttl)
+        {
+            return Some(info.clone());
         }
 
-        let branch = self
-            .get_branch(&working_dir)
-            .unwrap_or_else(|| "detached".to_string());
-        let status = self.get_status(&working_dir);
-        let (ahead, behind) = self.get_ahead_behind(&working_dir);
+        let info = self.read_git_info(working_dir)?;
+        GIT_INFO_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, (Instant::now(), info.clone()));
+        Some(info)
+    }
+
+    /// 影响 read_git_info 结果的选项
+    fn cache_fingerprint(&self) -> String {
+        format!(
+            "{:?}",
+            (
+                self.show_commit_count,
+                &self.commit_count_base,
+                &self.remotes,
+                self.show_identity,
+                &self.base_store,
+                self.show_stash,
+            )
+        )
+    }
+
+    fn read_git_info(&self, working_dir: &Path) -> Option<GitInfo> {
+        let working_dir = working_dir.to_string_lossy();
+
+        // 一次 status 调用同时取得分支、HEAD、上游 ahead/behind 与变更统计，失败即视为不在仓库中
+        let porcelain = PorcelainStatus::parse(
+            &self.git(&working_dir, &["status", "--porcelain=v2", "--branch"])?,
+        );
+        let status = porcelain.counts.status();
+        let counts = porcelain.counts;
+        let (ahead, behind) = (porcelain.ahead, porcelain.behind);
+        let branch = porcelain.branch.unwrap_or_else(|| "detached".to_string());
+        let commit_count = if self.show_commit_count {
+            self.get_branch_commit_count(&working_dir)
+        } else {
+            None
+        };
+
+        // 远端与 base 比较复用 status 输出中的 HEAD
+        let head_sha = porcelain.oid;
+        let remotes = match &head_sha {
+            Some(head) => self.get_remote_divergence(&working_dir, head, &branch),
+            None => Vec::new(),
+        };
+        let base = head_sha
+            .as_deref()
+            .and_then(|head| self.get_base_divergence(&working_dir, head));
+        let stash = if self.show_stash {
+            self.get_stash_count(&working_dir)
+        } else {
+            0
+        };
+        // 随 git 预览一起按 command_cache_ms 轮询刷新
+        let identity = if self.show_identity {
+            self.git(&working_dir, &["config", "user.email"])
+                .filter(|email| !email.is_empty())
+        } else {
+            None
+        };
 
         Some(GitInfo {
             branch,
             status,
             ahead,
             behind,
+            commit_count,
+            summary: counts.summary(),
+            conflicts: counts.conflicted,
+            remotes,
+            identity,
+            head_sha,
+            base,
+            stash,
+            untracked: counts.untracked,
         })
     }
 
-    fn is_git_repository(&self, working_dir: &str) -> bool {
-        Command::new("git")
-            .args(["--no-optional-locks", "rev-parse", "--git-dir"])
-            .current_dir(working_dir)
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+    /// 相对记录的 base 提交的 ahead/behind，当前仓库没有记录时为 None
+    fn get_base_divergence(&self, working_dir: &str, head: &str) -> Option<BaseDivergence> {
+        let store_path = self.base_store.as_ref()?;
+        let repo = self.git(working_dir, &["rev-parse", "--show-toplevel"])?;
+        let sha = GitBaseStore::cached_base(store_path, &repo)?;
+        let (ahead, behind) = self.get_left_right_count(working_dir, head, &sha)?;
+        Some(BaseDivergence { sha, ahead, behind })
     }
 
-    fn get_branch(&self, working_dir: &str) -> Option<String> {
-        // 首先尝试 --show-current
-        if let Ok(output) = Command::new("git")
-            .args(["--no-optional-locks", "branch", "--show-current"])
-            .current_dir(working_dir)
-            .output()
-            && output.status.success()
+    /// 计算相对各远端同名分支的 ahead/behind，远端没有该分支时跳过
+    fn get_remote_divergence(
+        &self,
+        working_dir: &str,
+        head: &str,
+        branch: &str,
+    ) -> Vec<RemoteDivergence> {
+        self.remotes
+            .iter()
+            .filter_map(|remote| {
+                let remote_ref = format!("refs/remotes/{remote}/{branch}");
+                let remote_sha = self
+                    .git(
+                        working_dir,
+                        &["rev-parse", "--verify", "--quiet", &remote_ref],
+                    )
+                    .filter(|sha| !sha.is_empty())?;
+                let (ahead, behind) = self.get_left_right_count(working_dir, head, &remote_sha)?;
+                Some(RemoteDivergence {
+                    remote: remote.clone(),
+                    ahead,
+                    behind,
+                })
+            })
+            .collect()
+    }
+
+    /// `rev-list --left-right --count head...remote`，结果按提交对缓存
+    fn get_left_right_count(
+        &self,
+        working_dir: &str,
+        head: &str,
+        remote: &str,
+    ) -> Option<(u32, u32)> {
+        let key = (head.to_string(), remote.to_string());
+        if let Some(counts) = DIVERGENCE_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .copied()
         {
-            let branch = String::from_utf8(output.stdout).ok()?.trim().to_string();
-            if !branch.is_empty() {
-                return Some(branch);
+            return Some(counts);
+        }
+
+        let range = format!("{head}...{remote}");
+        let output = self.git(
+            working_dir,
+            &["rev-list", "--left-right", "--count", &range],
+        )?;
+        let mut counts = output.split_whitespace().map(str::parse::<u32>);
+        let (Some(Ok(ahead)), Some(Ok(behind))) = (counts.next(), counts.next()) else {
+            return None;
+        };
+
+        cache_divergence(key, (ahead, behind));
+        Some((ahead, behind))
+    }
+
+    /// `git stash list` 的条目数
+    fn get_stash_count(&self, working_dir: &str) -> u32 {
+        self.git(working_dir, &["stash", "list"])
+            .map(|list| list.lines().filter(|line| !line.is_empty()).count() as u32)
+            .unwrap_or(0)
+    }
+
+    /// 计算 `<base>..HEAD` 的提交数
+    fn get_branch_commit_count(&self, working_dir: &str) -> Option<u32> {
+        let base = match &self.commit_count_base {
+            Some(base) => base.clone(),
+            None => self.get_default_merge_base(working_dir)?,
+        };
+        let range = format!("{base}..HEAD");
+        self.git(working_dir, &["rev-list", "--count", &range])
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// 获取 HEAD 与默认分支的 merge-base
+    fn get_default_merge_base(&self, working_dir: &str) -> Option<String> {
+        let default_branch = self
+            .git(
+                working_dir,
+                &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+            )
+            .filter(|branch| !branch.is_empty())
+            .unwrap_or_else(|| "main".to_string());
+
+        self.git(working_dir, &["merge-base", "HEAD", &default_branch])
+            .filter(|sha| !sha.is_empty())
+    }
+
+    pub(crate) fn collect_preview(&self, cwd: &Path) -> Option<GitPreviewData> {
+        self.get_git_info(cwd).map(preview_from_info)
+    }
+
+    /// 将 Git 数据格式化为 SegmentData
+    fn format_preview(&self, preview: &GitPreviewData) -> SegmentData {
+        let mut sync = Vec::new();
+        if preview.ahead > 0 {
+            sync.push(format!("↑{}", preview.ahead));
+        }
+        if preview.behind > 0 {
+            sync.push(format!("↓{}", preview.behind));
+        }
+
+        // 不显示分支时，状态符号和 ahead/behind 作为主内容
+        let (primary, mut indicators) = if self.show_branch {
+            let branch = match self.max_branch_len {
+                Some(max_len) => truncate_display(&preview.branch, max_len, self.truncate_position),
+                None => preview.branch.clone(),
+            };
+            (branch, sync)
+        } else {
+            let mut primary = preview.status.clone();
+            if !sync.is_empty() {
+                primary.push(' ');
+                primary.push_str(&sync.concat());
+            }
+            (primary, Vec::new())
+        };
+
+        // 各远端的分叉情况
+        for divergence in &preview.remotes {
+            if let Some(indicator) = format_remote_divergence(divergence) {
+                indicators.push(indicator);
             }
         }
 
-        // 回退到 symbolic-ref
-        if let Ok(output) = Command::new("git")
-            .args(["--no-optional-locks", "symbolic-ref", "--short", "HEAD"])
-            .current_dir(working_dir)
-            .output()
-            && output.status.success()
+        if self.show_stash && preview.stash > 0 {
+            indicators.push(format!("⚑{}", preview.stash));
+        }
+
+        if self.show_untracked && preview.untracked > 0 {
+            indicators.push(format!("?{}", preview.untracked));
+        }
+
+        // 相对 base 的分叉情况
+        if let Some(base) = &preview.base
+            && let Some(indicator) = format_base_divergence(base)
+        {
+            indicators.push(indicator);
+        }
+
+        // 当前身份
+        if self.show_identity
+            && let Some(email) = &preview.identity
+        {
+            let local = email.split('@').next().unwrap_or(email);
+            indicators.push(format!("<{local}>"));
+        }
+
+        // 分支提交数
+        if self.show_commit_count
+            && let Some(count) = preview.commit_count
         {
-            let branch = String::from_utf8(output.stdout).ok()?.trim().to_string();
-            if !branch.is_empty() {
-                return Some(branch);
+            indicators.push(format_commit_count(count));
+        }
+
+        let mut status_parts = Vec::new();
+        if self.show_branch {
+            status_parts.push(preview.status.clone());
+        }
+        match &self.indicator_brackets {
+            Some((open, close)) if !indicators.is_empty() => {
+                status_parts.push(format!("{open}{}{close}", indicators.join(" ")));
             }
+            _ => status_parts.extend(indicators),
         }
 
-        None
+        let secondary = status_parts.join(" ");
+
+        let mut data = SegmentData::new(primary)
+            .with_secondary(secondary)
+            .with_metadata("branch", &preview.branch)
+            .with_metadata("status", &preview.status)
+            .with_metadata("ahead", preview.ahead.to_string())
+            .with_metadata("behind", preview.behind.to_string())
+            .with_action(SegmentAction::RunCommand("git status".to_string()));
+        // 宽度不足时只显示状态符号
+        if !preview.status.is_empty() {
+            data = data.with_alt_text(&preview.status);
+        }
+        // 供 JSON/事件输出的消费方使用，与显示选项无关
+        if let Some(sha) = &preview.head_sha {
+            data = data.with_metadata("full_sha", sha);
+        }
+        if self.show_stash {
+            data = data.with_metadata("stash", preview.stash.to_string());
+        }
+        if let Some(base) = &preview.base {
+            data = data
+                .with_metadata("base_sha", &base.sha)
+                .with_metadata("base_ahead", base.ahead.to_string())
+                .with_metadata("base_behind", base.behind.to_string());
+        }
+        if let Some(count) = preview.commit_count {
+            data = data.with_metadata("commit_count", count.to_string());
+        }
+        if preview.conflicts > 0 {
+            data = data.with_badge(preview.conflicts.to_string());
+        }
+        if let Some(color) = self.branch_color(&preview.branch) {
+            data = data.with_color_hint(color);
+        }
+        // 身份不在预期列表中时标红，优先于分支颜色
+        if let Some(email) = &preview.identity
+            && self.show_identity
+            && !self.expected_identities.is_empty()
+            && !self
+                .expected_identities
+                .iter()
+                .any(|pattern| glob_match(pattern, email))
+        {
+            data = data
+                .with_color_hint(ansi16::RED)
+                .with_metadata("unexpected_identity", email);
+        }
+        let status = parse_status(&preview.status);
+        if self.colorize_icon_by_status
+            && let Some(color) = status.map(|status| self.icon_state_color(status))
+        {
+            data = data.with_icon_color_hint(color);
+        }
+        // 按状态强制文本颜色（冲突默认标红），优先于分支与身份颜色
+        if let Some(color) = status.and_then(|status| self.state_color(status)) {
+            data = data.with_dynamic_color(color);
+        }
+        if let Some(summary) = &preview.summary {
+            data = data.with_detail(format!(
+                "{}: {summary} (ahead {}, behind {})",
+                preview.branch, preview.ahead, preview.behind
+            ));
+        }
+        data
     }
+}
 
-    fn get_status(&self, working_dir: &str) -> GitStatus {
-        let output = Command::new("git")
-            .args(["--no-optional-locks", "status", "--porcelain"])
-            .current_dir(working_dir)
-            .output();
+/// 远端分叉显示为 "o:↑2↓1"（取远端名首字符），完全同步时不显示
+fn format_remote_divergence(divergence: &RemoteDivergence) -> Option<String> {
+    let label: String = divergence.remote.chars().take(1).collect();
+    format_divergence(&label, divergence.ahead, divergence.behind)
+}
+
+/// base 分叉显示为 "base:↑3"，与 base 相同时不显示
+fn format_base_divergence(base: &BaseDivergence) -> Option<String> {
+    format_divergence("base", base.ahead, base.behind)
+}
+
+fn format_divergence(label: &str, ahead: u32, behind: u32) -> Option<String> {
+    if ahead == 0 && behind == 0 {
+        return None;
+    }
+    let mut out = format!("{label}:");
+    if ahead > 0 {
+        out.push_str(&format!("↑{ahead}"));
+    }
+    if behind > 0 {
+        out.push_str(&format!("↓{behind}"));
+    }
+    Some(out)
+}
 
-        match output {
-            Ok(output) if output.status.success() => {
-                let status_text = String::from_utf8(output.stdout).unwrap_or_default();
+/// 简单 glob 匹配：`*` 匹配任意字符序列，`?` 匹配单个字符
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 `*` 的位置及其匹配到的文本位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
 
-                if status_text.trim().is_empty() {
-                    return GitStatus::Clean;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
                 }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn preview_from_info(git_info: GitInfo) -> GitPreviewData {
+    GitPreviewData {
+        branch: git_info.branch,
+        status: status_symbol(git_info.status).to_string(),
+        ahead: git_info.ahead,
+        behind: git_info.behind,
+        commit_count: git_info.commit_count,
+        summary: Some(git_info.summary),
+        conflicts: git_info.conflicts,
+        remotes: git_info.remotes,
+        identity: git_info.identity,
+        head_sha: git_info.head_sha,
+        base: git_info.base,
+        stash: git_info.stash,
+        untracked: git_info.untracked,
+    }
+}
+
+/// 状态符号
+fn status_symbol(status: GitStatus) -> &'static str {
+    match status {
+        GitStatus::Clean => "✓",
+        GitStatus::Dirty => "●",
+        GitStatus::Conflicts => "⚠",
+    }
+}
+
+/// 预览数据中的状态：状态符号，或实时读取时的枚举名
+fn parse_status(status: &str) -> Option<GitStatus> {
+    match status {
+        "✓" | "Clean" => Some(GitStatus::Clean),
+        "●" | "Dirty" => Some(GitStatus::Dirty),
+        "⚠" | "Conflicts" => Some(GitStatus::Conflicts),
+        _ => None,
+    }
+}
+
+/// 默认的状态配色
+/// colorblind_safe 时使用 Okabe-Ito 配色中的蓝/橙/紫，避免依赖红绿区分
+fn status_color(status: GitStatus, colorblind_safe: bool) -> AnsiColor {
+    match (status, colorblind_safe) {
+        (GitStatus::Clean, false) => ansi16::GREEN,
+        (GitStatus::Dirty, false) => ansi16::YELLOW,
+        (GitStatus::Conflicts, false) => ansi16::RED,
+        (GitStatus::Clean, true) => AnsiColor::rgb(0, 114, 178),
+        (GitStatus::Dirty, true) => AnsiColor::rgb(230, 159, 0),
+        (GitStatus::Conflicts, true) => AnsiColor::rgb(204, 121, 167),
+    }
+}
+
+/// 格式化提交数，例如 "42 commits"
+fn format_commit_count(count: u32) -> String {
+    if count == 1 {
+        "1 commit".to_string()
+    } else {
+        format!("{count} commits")
+    }
+}
 
-                // 检查冲突标记
-                if status_text.contains("UU")
-                    || status_text.contains("AA")
-                    || status_text.contains("DD")
-                {
-                    GitStatus::Conflicts
-                } else {
-                    GitStatus::Dirty
+/// `git status --porcelain=v2 --branch` 的解析结果
+#[derive(Debug, Default, PartialEq, Eq)]
+struct PorcelainStatus {
+    /// `# branch.head`，detached 时为 None
+    branch: Option<String>,
+    /// `# branch.oid`，尚无提交时为 None
+    oid: Option<String>,
+    /// `# branch.ab`，没有上游时为 0
+    ahead: u32,
+    behind: u32,
+    counts: PorcelainCounts,
+}
+
+impl PorcelainStatus {
+    /// 按行首标记解析，见 git-status(1) "Porcelain Format Version 2"
+    /// 只看状态列，文件名中的 "UU" 等字符不会被误判
+    fn parse(status_text: &str) -> Self {
+        let mut parsed = Self::default();
+
+        for line in status_text.lines() {
+            if let Some(header) = line.strip_prefix("# ") {
+                let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+                match key {
+                    "branch.oid" if value != "(initial)" && !value.is_empty() => {
+                        parsed.oid = Some(value.to_string());
+                    }
+                    "branch.head" if value != "(detached)" && !value.is_empty() => {
+                        parsed.branch = Some(value.to_string());
+                    }
+                    "branch.ab" => {
+                        let mut parts = value.split_whitespace();
+                        parsed.ahead = parse_ab(parts.next(), '+');
+                        parsed.behind = parse_ab(parts.next(), '-');
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let mut fields = line.split(' ');
+            match (fields.next(), fields.next()) {
+                // 普通变更与重命名/复制：XY 中 '.' 表示未修改
+                (Some("1" | "2"), Some(xy)) => {
+                    let mut chars = xy.chars();
+                    if chars.next().is_some_and(|c| c != '.') {
+                        parsed.counts.staged += 1;
+                    }
+                    if chars.next().is_some_and(|c| c != '.') {
+                        parsed.counts.modified += 1;
+                    }
                 }
+                (Some("u"), Some(_)) => parsed.counts.conflicted += 1,
+                (Some("?"), Some(_)) => parsed.counts.untracked += 1,
+                _ => {}
             }
-            _ => GitStatus::Clean,
         }
-    }
 
-    fn get_ahead_behind(&self, working_dir: &str) -> (u32, u32) {
-        let ahead = self.get_commit_count(working_dir, "@{u}..HEAD");
-        let behind = self.get_commit_count(working_dir, "HEAD..@{u}");
-        (ahead, behind)
+        parsed
     }
+}
 
-    fn get_commit_count(&self, working_dir: &str, range: &str) -> u32 {
-        let output = Command::new("git")
-            .args(["--no-optional-locks", "rev-list", "--count", range])
-            .current_dir(working_dir)
-            .output();
+/// 解析 `branch.ab` 中的 "+N" / "-M"
+fn parse_ab(value: Option<&str>, sign: char) -> u32 {
+    value
+        .and_then(|value| value.strip_prefix(sign))
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0)
+}
 
-        match output {
-            Ok(output) if output.status.success() => String::from_utf8(output.stdout)
-                .ok()
-                .and_then(|s| s.trim().parse().ok())
-                .unwrap_or(0),
-            _ => 0,
+/// `git status --porcelain=v2` 各类变更的文件数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PorcelainCounts {
+    staged: u32,
+    modified: u32,
+    untracked: u32,
+    conflicted: u32,
+}
+
+impl PorcelainCounts {
+    fn status(&self) -> GitStatus {
+        if self.conflicted > 0 {
+            GitStatus::Conflicts
+        } else if self.staged + self.modified + self.untracked > 0 {
+            GitStatus::Dirty
+        } else {
+            GitStatus::Clean
         }
     }
 
-    pub(crate) fn collect_preview(&self, cwd: &Path) -> Option<GitPreviewData> {
-        let git_info = self.get_git_info(cwd)?;
-        let status = match git_info.status {
-            GitStatus::Clean => "✓",
-            GitStatus::Dirty => "●",
-            GitStatus::Conflicts => "⚠",
-        };
-
-        Some(GitPreviewData {
-            branch: git_info.branch,
-            status: status.to_string(),
-            ahead: git_info.ahead,
-            behind: git_info.behind,
-        })
+    /// 汇总文本，例如 "1 staged, 2 modified, 1 untracked"
+    fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            (self.conflicted, "conflicted"),
+            (self.staged, "staged"),
+            (self.modified, "modified"),
+            (self.untracked, "untracked"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect();
+
+        if parts.is_empty() {
+            "clean".to_string()
+        } else {
+            parts.join(", ")
+        }
     }
 }
 
@@ -162,59 +1063,787 @@ impl Segment for GitSegment {
             if preview.branch.is_empty() && preview.status.is_empty() {
                 return None;
             }
-            let primary = preview.branch.clone();
-            let mut status_parts = Vec::new();
-            status_parts.push(preview.status.clone());
-            if preview.ahead > 0 {
-                status_parts.push(format!("↑{}", preview.ahead));
-            }
-            if preview.behind > 0 {
-                status_parts.push(format!("↓{}", preview.behind));
-            }
-            let secondary = status_parts.join(" ");
-            return Some(
-                SegmentData::new(primary)
-                    .with_secondary(secondary)
-                    .with_metadata("branch", &preview.branch)
-                    .with_metadata("status", &preview.status)
-                    .with_metadata("ahead", preview.ahead.to_string())
-                    .with_metadata("behind", preview.behind.to_string()),
-            );
+            return Some(self.format_preview(preview));
         }
 
+        // 实时读取时 status 元数据沿用枚举名（"Clean"/"Dirty"/"Conflicts"），与之前的消费方保持一致
         let git_info = self.get_git_info(ctx.cwd)?;
+        let status = git_info.status;
+        let data = self.format_preview(&preview_from_info(git_info));
+        Some(data.with_metadata("status", format!("{status:?}")))
+    }
 
-        let primary = git_info.branch.clone();
-        let mut status_parts = Vec::new();
+    fn id(&self) -> SegmentId {
+        SegmentId::Git
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// 按参数返回预设输出的 mock 执行器，未登记的命令视为失败
+    #[derive(Default)]
+    struct MockGitRunner {
+        responses: HashMap<String, String>,
+    }
 
-        // 状态符号
-        match git_info.status {
-            GitStatus::Clean => status_parts.push("✓".to_string()),
-            GitStatus::Dirty => status_parts.push("●".to_string()),
-            GitStatus::Conflicts => status_parts.push("⚠".to_string()),
+    impl MockGitRunner {
+        fn repo() -> Self {
+            Self::default().status("")
         }
 
-        // ahead/behind
-        if git_info.ahead > 0 {
-            status_parts.push(format!("↑{}", git_info.ahead));
+        /// 在 feature 分支的 porcelain v2 输出后追加额外行
+        fn status(self, extra: &str) -> Self {
+            self.respond(
+                "status --porcelain=v2 --branch",
+                &format!("# branch.head feature\n{extra}"),
+            )
         }
-        if git_info.behind > 0 {
-            status_parts.push(format!("↓{}", git_info.behind));
+
+        fn respond(mut self, args: &str, stdout: &str) -> Self {
+            self.responses.insert(args.to_string(), stdout.to_string());
+            self
         }
+    }
 
-        let secondary = status_parts.join(" ");
+    impl GitRunner for MockGitRunner {
+        fn run(&self, _working_dir: &str, args: &[&str]) -> std::io::Result<GitOutput> {
+            Ok(match self.responses.get(&args.join(" ")) {
+                Some(stdout) => GitOutput {
+                    success: true,
+                    stdout: stdout.clone(),
+                },
+                None => GitOutput::default(),
+            })
+        }
+    }
 
-        Some(
-            SegmentData::new(primary)
-                .with_secondary(secondary)
-                .with_metadata("branch", &git_info.branch)
-                .with_metadata("status", format!("{:?}", git_info.status))
-                .with_metadata("ahead", git_info.ahead.to_string())
-                .with_metadata("behind", git_info.behind.to_string()),
-        )
+    #[test]
+    fn test_parse_porcelain_v2() {
+        // git 2.43 `status --porcelain=v2 --branch` 实际输出
+        let sample = "\
+# branch.oid 9f2c1e4b7a0d3c6e8f1a2b3c4d5e6f7a8b9c0d1e
+# branch.head main
+# branch.upstream origin/main
+# branch.ab +2 -1
+1 M. N... 100644 100644 100644 3b18e512dba79e4c8300dd08aeb37f8e728b8dad 8ab686eafeb1f44702738c8b0f24f2567c36da6d src/lib.rs
+1 .M N... 100644 100644 100644 e69de29bb2d1d6434b8b29ae775ad8c2e48c5391 e69de29bb2d1d6434b8b29ae775ad8c2e48c5391 UU.md
+1 MM N... 100644 100644 100644 d00491fd7e5bb6fa28c517a0bb32b8b506539d4d d00491fd7e5bb6fa28c517a0bb32b8b506539d4d both.rs
+2 R. N... 100644 100644 100644 a5c19667710254f835085b99726e523457150e03 a5c19667710254f835085b99726e523457150e03 R100 new name.rs\told.rs
+u UU N... 100644 100644 100644 100644 257cc5642cb1a054f08cc83f2d943e56fd3ebe99 5716ca5987cbf97d6bb54920bea6adde242d87e6 76018072e09c5d31c8c6e3113b8aa0fe625195ca conflict.rs
+? AArdvark.txt
+";
+        let parsed = PorcelainStatus::parse(sample);
+        assert_eq!(
+            parsed,
+            PorcelainStatus {
+                branch: Some("main".to_string()),
+                oid: Some("9f2c1e4b7a0d3c6e8f1a2b3c4d5e6f7a8b9c0d1e".to_string()),
+                ahead: 2,
+                behind: 1,
+                counts: PorcelainCounts {
+                    staged: 3,
+                    modified: 2,
+                    untracked: 1,
+                    conflicted: 1,
+                },
+            }
+        );
+        assert_eq!(parsed.counts.status(), GitStatus::Conflicts);
+
+        // 新仓库、detached 与干净工作区
+        let parsed = PorcelainStatus::parse("# branch.oid (initial)\n# branch.head (detached)\n");
+        assert_eq!(parsed, PorcelainStatus::default());
+        assert_eq!(parsed.counts.status(), GitStatus::Clean);
+
+        // 文件名包含 "UU" 不应被识别为冲突
+        let parsed = PorcelainStatus::parse("? AArdvark.txt\n? UU.md\n");
+        assert_eq!(parsed.counts.status(), GitStatus::Dirty);
     }
 
-    fn id(&self) -> SegmentId {
-        SegmentId::Git
+    #[test]
+    fn test_commit_count_against_fixed_base() {
+        let runner = MockGitRunner::repo().respond("rev-list --count v1.0..HEAD", "42\n");
+        let segment = GitSegment::new()
+            .with_runner(runner)
+            .with_commit_count(true, Some("v1.0"));
+
+        let ctx = StatusLineContext::new("", Path::new("/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+
+        assert_eq!(data.primary, "feature");
+        assert_eq!(data.secondary, "✓ 42 commits");
+        assert_eq!(data.metadata.get("commit_count"), Some(&"42".to_string()));
+    }
+
+    #[test]
+    fn test_commit_count_against_default_merge_base() {
+        let runner = MockGitRunner::repo()
+            .respond(
+                "symbolic-ref --short refs/remotes/origin/HEAD",
+                "origin/main",
+            )
+            .respond("merge-base HEAD origin/main", "abc123")
+            .respond("rev-list --count abc123..HEAD", "1");
+        let segment = GitSegment::new()
+            .with_runner(runner)
+            .with_commit_count(true, None);
+
+        let preview = segment
+            .collect_preview(Path::new("/repo"))
+            .expect("git preview");
+        assert_eq!(preview.commit_count, Some(1));
+        assert_eq!(segment.format_preview(&preview).secondary, "✓ 1 commit");
+    }
+
+    #[test]
+    fn test_commit_count_disabled_by_default() {
+        let runner = MockGitRunner::repo().respond("rev-list --count v1.0..HEAD", "42");
+        let segment = GitSegment::new().with_runner(runner);
+
+        let preview = segment
+            .collect_preview(Path::new("/repo"))
+            .expect("git preview");
+        assert_eq!(preview.commit_count, None);
+    }
+
+    #[test]
+    fn test_detail_contains_porcelain_summary() {
+        let runner = MockGitRunner::default().status(
+            "# branch.ab +2 -0\n\
+             1 M. N... 100644 100644 100644 a a staged.rs\n\
+             1 .M N... 100644 100644 100644 b b edited.rs\n\
+             1 MM N... 100644 100644 100644 c c both.rs\n\
+             ? new.txt\n",
+        );
+        let segment = GitSegment::new().with_runner(runner);
+
+        let ctx = StatusLineContext::new("", Path::new("/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+
+        assert_eq!(
+            data.detail.as_deref(),
+            Some("feature: 2 staged, 2 modified, 1 untracked (ahead 2, behind 0)")
+        );
+    }
+
+    #[test]
+    fn test_grouped_indicators() {
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        config
+            .options
+            .insert("group_indicators".to_string(), serde_json::json!(true));
+        config
+            .options
+            .insert("show_commit_count".to_string(), serde_json::json!(true));
+        let segment = GitSegment::from_config(&config);
+
+        let preview = GitPreviewData {
+            branch: "main".to_string(),
+            status: "●".to_string(),
+            ahead: 2,
+            behind: 1,
+            commit_count: Some(3),
+            summary: None,
+            conflicts: 0,
+            remotes: Vec::new(),
+            identity: None,
+            head_sha: None,
+            base: None,
+            stash: 0,
+            untracked: 0,
+        };
+        assert_eq!(
+            segment.format_preview(&preview).secondary,
+            "● [↑2 ↓1 3 commits]"
+        );
+
+        // 没有指示符时不输出空括号
+        let clean = GitPreviewData {
+            status: "✓".to_string(),
+            ..GitPreviewData::empty()
+        };
+        assert_eq!(segment.format_preview(&clean).secondary, "✓");
+
+        let segment = GitSegment::new().with_grouped_indicators("(", ")");
+        assert_eq!(segment.format_preview(&preview).secondary, "● (↑2 ↓1)");
+    }
+
+    #[test]
+    fn test_grouped_ahead_stash_untracked() {
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        for key in ["group_indicators", "show_stash", "show_untracked"] {
+            config
+                .options
+                .insert(key.to_string(), serde_json::json!(true));
+        }
+        let segment = GitSegment::from_config(&config);
+
+        let preview = GitPreviewData {
+            branch: "main".to_string(),
+            status: "●".to_string(),
+            ahead: 2,
+            stash: 1,
+            untracked: 3,
+            ..GitPreviewData::empty()
+        };
+        assert_eq!(segment.format_preview(&preview).secondary, "● [↑2 ⚑1 ?3]");
+
+        // 未开启 show_untracked 时不显示
+        let segment = GitSegment::new().with_show_stash(true);
+        assert_eq!(segment.format_preview(&preview).secondary, "● ↑2 ⚑1");
+    }
+
+    #[test]
+    fn test_conflict_count_badge() {
+        let runner = MockGitRunner::default().status(
+            "u UU N... 100644 100644 100644 100644 a b c a.rs\n\
+             u AA N... 000000 100644 100644 100644 0 b c b.rs\n\
+             1 .M N... 100644 100644 100644 d d c.rs\n",
+        );
+        let segment = GitSegment::new().with_runner(runner);
+
+        let ctx = StatusLineContext::new("", Path::new("/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.secondary, "⚠");
+        assert_eq!(data.badge.as_deref(), Some("2"));
+        assert_eq!(data.color_override, Some(ansi16::RED));
+    }
+
+    /// 模拟未安装 git：每次调用都返回 spawn 错误
+    struct MissingGitRunner;
+
+    impl GitRunner for MissingGitRunner {
+        fn run(&self, _working_dir: &str, _args: &[&str]) -> std::io::Result<GitOutput> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "git: command not found",
+            ))
+        }
+    }
+
+    #[test]
+    fn test_missing_git_binary_yields_nothing() {
+        let segment = GitSegment::new()
+            .with_runner(MissingGitRunner)
+            .with_commit_count(true, None);
+        let cwd = Path::new("/repo");
+
+        assert!(segment.get_git_info(cwd).is_none());
+        assert!(segment.collect_preview(cwd).is_none());
+
+        let ctx = StatusLineContext::new("", cwd);
+        assert!(segment.collect(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("feature/*", "feature/x"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("release-?.?", "release-1.2"));
+        assert!(glob_match("*fix*", "hotfix/urgent"));
+        assert!(!glob_match("feature/*", "main"));
+        assert!(!glob_match("main", "maintenance"));
+    }
+
+    #[test]
+    fn test_branch_color_rules() {
+        let feature = AnsiColor::c16(2);
+        let hotfix = AnsiColor::c16(1);
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        config.options.insert(
+            "branch_color_rules".to_string(),
+            serde_json::json!([["feature/*", feature], ["hotfix/*", hotfix], ["*", { "c16": 4 }]]),
+        );
+        // 第一个匹配的规则生效
+        let segment = GitSegment::from_config(&config);
+        let preview = |branch: &str| GitPreviewData {
+            branch: branch.to_string(),
+            status: "✓".to_string(),
+            ..GitPreviewData::empty()
+        };
+        assert_eq!(
+            segment.format_preview(&preview("feature/x")).color_hint,
+            Some(feature)
+        );
+
+        // 没有匹配时使用配置中的默认颜色
+        let segment = GitSegment::new().with_branch_color_rules(vec![
+            ("feature/*".to_string(), feature),
+            ("hotfix/*".to_string(), hotfix),
+        ]);
+        assert_eq!(segment.format_preview(&preview("main")).color_hint, None);
+    }
+
+    #[test]
+    fn test_git_status_action() {
+        let segment = GitSegment::new().with_runner(MockGitRunner::repo());
+        let ctx = StatusLineContext::new("", Path::new("/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(
+            data.action,
+            Some(SegmentAction::RunCommand("git status".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_colorblind_safe_status_colors() {
+        let hint = |segment: &GitSegment, status: &str| {
+            let preview = GitPreviewData {
+                branch: "main".to_string(),
+                status: status.to_string(),
+                ..GitPreviewData::empty()
+            };
+            segment.format_preview(&preview).icon_color_hint
+        };
+
+        let segment = GitSegment::new().with_colorize_icon_by_status(true);
+        assert_eq!(hint(&segment, "✓"), Some(ansi16::GREEN));
+        assert_eq!(hint(&segment, "⚠"), Some(ansi16::RED));
+
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        for key in ["colorize_icon_by_status", "colorblind_safe"] {
+            config
+                .options
+                .insert(key.to_string(), serde_json::json!(true));
+        }
+        let segment = GitSegment::from_config(&config);
+        assert_eq!(hint(&segment, "✓"), Some(AnsiColor::rgb(0, 114, 178)));
+        assert_eq!(hint(&segment, "●"), Some(AnsiColor::rgb(230, 159, 0)));
+        assert_eq!(hint(&segment, "⚠"), Some(AnsiColor::rgb(204, 121, 167)));
+    }
+
+    #[test]
+    fn test_state_colors_from_options() {
+        let override_for = |segment: &GitSegment, status: &str| {
+            let preview = GitPreviewData {
+                branch: "main".to_string(),
+                status: status.to_string(),
+                ..GitPreviewData::empty()
+            };
+            segment.format_preview(&preview).color_override
+        };
+
+        // 默认只有冲突强制标红
+        let segment = GitSegment::from_config(&SegmentItemConfig::default_for(SegmentId::Git));
+        assert_eq!(override_for(&segment, "✓"), None);
+        assert_eq!(override_for(&segment, "●"), None);
+        assert_eq!(override_for(&segment, "⚠"), Some(ansi16::RED));
+
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        for (key, value) in [
+            ("clean_color", serde_json::json!({"c16": 2})),
+            ("dirty_color", serde_json::json!("#ff8800")),
+            ("conflict_color", serde_json::json!({"c256": 201})),
+        ] {
+            config.options.insert(key.to_string(), value);
+        }
+        let segment = GitSegment::from_config(&config);
+        assert_eq!(override_for(&segment, "✓"), Some(ansi16::GREEN));
+        assert_eq!(
+            override_for(&segment, "●"),
+            Some(AnsiColor::rgb(255, 136, 0))
+        );
+        assert_eq!(override_for(&segment, "⚠"), Some(AnsiColor::c256(201)));
+
+        // 无法解析的值被忽略
+        config
+            .options
+            .insert("clean_color".to_string(), serde_json::json!({"c16": "x"}));
+        config
+            .options
+            .insert("conflict_color".to_string(), serde_json::json!(true));
+        let segment = GitSegment::from_config(&config);
+        assert_eq!(override_for(&segment, "✓"), None);
+        assert_eq!(override_for(&segment, "⚠"), Some(ansi16::RED));
+    }
+
+    #[test]
+    fn test_icon_tint_uses_configured_state_colors() {
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        config.options.insert(
+            "colorize_icon_by_status".to_string(),
+            serde_json::json!(true),
+        );
+        config
+            .options
+            .insert("dirty_color".to_string(), serde_json::json!({"c256": 208}));
+        let segment = GitSegment::from_config(&config);
+        let hint = |status: &str| {
+            let preview = GitPreviewData {
+                branch: "main".to_string(),
+                status: status.to_string(),
+                ..GitPreviewData::empty()
+            };
+            segment.format_preview(&preview).icon_color_hint
+        };
+        assert_eq!(hint("●"), Some(AnsiColor::c256(208)));
+        assert_eq!(hint("Dirty"), Some(AnsiColor::c256(208)));
+        // 未配置的状态使用默认配色
+        assert_eq!(hint("✓"), Some(ansi16::GREEN));
+    }
+
+    #[test]
+    fn test_hide_branch_keeps_sync_status() {
+        let preview = GitPreviewData {
+            branch: "feature/x".to_string(),
+            status: "●".to_string(),
+            ahead: 2,
+            behind: 1,
+            ..GitPreviewData::empty()
+        };
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        config
+            .options
+            .insert("show_branch".to_string(), serde_json::json!(false));
+        let data = GitSegment::from_config(&config).format_preview(&preview);
+        assert_eq!(data.primary, "● ↑2↓1");
+        assert_eq!(data.secondary, "");
+        assert!(!data.primary.contains("feature"));
+
+        let clean = GitPreviewData {
+            status: "✓".to_string(),
+            ahead: 0,
+            behind: 0,
+            ..preview
+        };
+        let data = GitSegment::new()
+            .with_show_branch(false)
+            .format_preview(&clean);
+        assert_eq!(data.primary, "✓");
+    }
+
+    #[test]
+    fn test_missing_cwd_yields_nothing() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let missing = dir.path().join("removed");
+        let ctx = StatusLineContext::new("", &missing);
+        assert!(GitSegment::new().collect(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_divergence_against_two_remotes() {
+        let runner = MockGitRunner::default()
+            .status("# branch.oid h1\n")
+            .respond(
+                "rev-parse --verify --quiet refs/remotes/origin/feature",
+                "o1",
+            )
+            .respond(
+                "rev-parse --verify --quiet refs/remotes/upstream/feature",
+                "u1",
+            )
+            .respond("rev-list --left-right --count h1...o1", "2\t0")
+            .respond("rev-list --left-right --count h1...u1", "0\t5");
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        config.options.insert(
+            "remotes".to_string(),
+            serde_json::json!(["origin", "upstream", "fork"]),
+        );
+        let segment = GitSegment::from_config(&config).with_runner(runner);
+
+        let preview = segment
+            .collect_preview(Path::new("/repo"))
+            .expect("git preview");
+        // fork 上没有该分支，跳过
+        assert_eq!(
+            preview.remotes,
+            vec![
+                RemoteDivergence {
+                    remote: "origin".to_string(),
+                    ahead: 2,
+                    behind: 0,
+                },
+                RemoteDivergence {
+                    remote: "upstream".to_string(),
+                    ahead: 0,
+                    behind: 5,
+                },
+            ]
+        );
+        assert_eq!(segment.format_preview(&preview).secondary, "✓ o:↑2 u:↓5");
+    }
+
+    #[test]
+    fn test_identity() {
+        let expected = vec!["*@work.example".to_string()];
+        let collect = |runner: MockGitRunner| {
+            GitSegment::new()
+                .with_runner(runner)
+                .with_identity(true, expected.clone())
+                .collect(&StatusLineContext::new("", Path::new("/repo")))
+                .expect("git data")
+        };
+
+        let data =
+            collect(MockGitRunner::repo().respond("config user.email", "alice@work.example"));
+        assert_eq!(data.secondary, "✓ <alice>");
+        assert_eq!(data.color_hint, None);
+
+        let data =
+            collect(MockGitRunner::repo().respond("config user.email", "alice@home.example"));
+        assert_eq!(data.secondary, "✓ <alice>");
+        assert_eq!(data.color_hint, Some(ansi16::RED));
+
+        // 未配置身份
+        let data = collect(MockGitRunner::repo());
+        assert_eq!(data.secondary, "✓");
+        assert_eq!(data.color_hint, None);
+    }
+
+    #[test]
+    fn test_full_sha_metadata() {
+        let sha = "4f1c2b9e8d7a6f5e4d3c2b1a0f9e8d7c6b5a4f3e";
+        let runner = MockGitRunner::default().status(&format!("# branch.oid {sha}\n"));
+        let segment = GitSegment::new().with_runner(runner);
+
+        let ctx = StatusLineContext::new("", Path::new("/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.metadata.get("full_sha").map(String::as_str), Some(sha));
+        assert_eq!(data.primary, "feature");
+        assert!(!data.secondary.contains("4f1c2b9"));
+
+        // 没有提交时不写入
+        let segment = GitSegment::new().with_runner(MockGitRunner::repo());
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.metadata.get("full_sha"), None);
+        // 实时读取的 status 元数据为枚举名
+        assert_eq!(
+            data.metadata.get("status").map(String::as_str),
+            Some("Clean")
+        );
+    }
+
+    #[test]
+    fn test_divergence_against_stored_base() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store_path = dir.path().join("git_bases.json");
+        let mut store = GitBaseStore::default();
+        store.set("/work/repo", "base0001");
+        store.save(&store_path).expect("save store");
+
+        let runner = MockGitRunner::default()
+            .status("# branch.oid head0002\n")
+            .respond("rev-parse --show-toplevel", "/work/repo")
+            .respond(
+                "rev-list --left-right --count head0002...base0001",
+                "3\t1\n",
+            );
+        let segment = GitSegment::new()
+            .with_runner(runner)
+            .with_base_store(Some(store_path));
+
+        let ctx = StatusLineContext::new("", Path::new("/work/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.secondary, "✓ base:↑3↓1");
+        assert_eq!(
+            data.metadata.get("base_sha").map(String::as_str),
+            Some("base0001")
+        );
+
+        // 其他仓库没有记录时不显示
+        let runner = MockGitRunner::default()
+            .status("# branch.oid head0002\n")
+            .respond("rev-parse --show-toplevel", "/work/other");
+        let segment = GitSegment::new()
+            .with_runner(runner)
+            .with_base_store(Some(dir.path().join("git_bases.json")));
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.secondary, "✓");
+        assert_eq!(data.metadata.get("base_sha"), None);
+    }
+
+    #[test]
+    fn test_reset_base_to_head() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let store_path = dir.path().join("cxline").join("git_bases.json");
+        let runner = MockGitRunner::default()
+            .status("# branch.oid head0003\n")
+            .respond("rev-parse --show-toplevel", "/work/repo")
+            .respond("rev-parse HEAD", "head0003")
+            .respond(
+                "rev-list --left-right --count head0003...head0003",
+                "0\t0\n",
+            );
+        let segment = GitSegment::new()
+            .with_runner(runner)
+            .with_base_store(Some(store_path.clone()));
+
+        let ctx = StatusLineContext::new("", Path::new("/work/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.metadata.get("base_ahead"), None);
+
+        // 写入后缓存失效，下一次收集读到新记录
+        assert_eq!(segment.reset_base(ctx.cwd).expect("reset base"), "head0003");
+        assert_eq!(
+            GitBaseStore::load(&store_path).get("/work/repo"),
+            Some("head0003")
+        );
+
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.secondary, "✓");
+        assert_eq!(
+            data.metadata.get("base_ahead").map(String::as_str),
+            Some("0")
+        );
+
+        // 未启用时无法重置
+        assert!(GitSegment::new().reset_base(ctx.cwd).is_err());
+    }
+
+    #[test]
+    fn test_divergence_cache_is_bounded() {
+        for index in 0..DIVERGENCE_CACHE_CAPACITY + 10 {
+            cache_divergence((format!("head{index}"), "remote".to_string()), (1, 0));
+        }
+        let len = DIVERGENCE_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len();
+        assert!(len <= DIVERGENCE_CACHE_CAPACITY, "{len}");
+    }
+
+    #[test]
+    fn test_max_branch_len_truncates_middle() {
+        let segment = GitSegment::new().with_max_branch_len(Some(8));
+        let format = |branch: &str| {
+            let preview = GitPreviewData {
+                branch: branch.to_string(),
+                status: "✓".to_string(),
+                ..GitPreviewData::empty()
+            };
+            segment.format_preview(&preview)
+        };
+
+        let data = format("feature/JIRA-1234-really-long-description");
+        assert_eq!(data.primary, "feat…ion");
+        assert_eq!(
+            data.metadata.get("branch").map(String::as_str),
+            Some("feature/JIRA-1234-really-long-description")
+        );
+
+        // 未超出时保持不变
+        assert_eq!(format("main").primary, "main");
+        assert_eq!(format("12345678").primary, "12345678");
+
+        // 非 ASCII 按字素和显示宽度截断
+        let data = format("功能/分支名称很长");
+        assert_eq!(data.primary, "功能…长");
+        assert_eq!(
+            data.metadata.get("branch").map(String::as_str),
+            Some("功能/分支名称很长")
+        );
+    }
+
+    #[test]
+    fn test_truncate_position_option() {
+        let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+        config
+            .options
+            .insert("max_branch_len".to_string(), serde_json::json!(8));
+        let format = |config: &SegmentItemConfig| {
+            let preview = GitPreviewData {
+                branch: "feature/JIRA-1234-really-long-description".to_string(),
+                status: "✓".to_string(),
+                ..GitPreviewData::empty()
+            };
+            GitSegment::from_config(config)
+                .format_preview(&preview)
+                .primary
+        };
+
+        assert_eq!(format(&config), "feat…ion");
+        config
+            .options
+            .insert("truncate_position".to_string(), serde_json::json!("end"));
+        assert_eq!(format(&config), "feature…");
+        config
+            .options
+            .insert("truncate_position".to_string(), serde_json::json!("start"));
+        assert_eq!(format(&config), "…ription");
+    }
+
+    #[test]
+    fn test_stash_count() {
+        let runner = MockGitRunner::default()
+            .status("# branch.ab +1 -0\n")
+            .respond(
+                "stash list",
+                "stash@{0}: WIP on feature: abc fix\nstash@{1}: On feature: try\n",
+            );
+        let segment = GitSegment::new().with_runner(runner).with_show_stash(true);
+        let ctx = StatusLineContext::new("", Path::new("/repo"));
+        let data = segment.collect(&ctx).expect("git data");
+        assert_eq!(data.secondary, "✓ ↑1 ⚑2");
+        assert_eq!(data.metadata.get("stash").map(String::as_str), Some("2"));
+
+        // 默认关闭，不执行 stash list
+        let runner = MockGitRunner::repo().respond("stash list", "stash@{0}: WIP\n");
+        let data = GitSegment::new()
+            .with_runner(runner)
+            .collect(&ctx)
+            .expect("git data");
+        assert_eq!(data.secondary, "✓");
+        assert_eq!(data.metadata.get("stash"), None);
+    }
+
+    /// 记录调用次数的执行器
+    struct CountingRunner {
+        inner: MockGitRunner,
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl GitRunner for CountingRunner {
+        fn run(&self, working_dir: &str, args: &[&str]) -> std::io::Result<GitOutput> {
+            if args == ["status", "--porcelain=v2", "--branch"] {
+                self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+            self.inner.run(working_dir, args)
+        }
+    }
+
+    #[test]
+    fn test_git_info_cached_per_directory() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let segment = |ttl| {
+            GitSegment::new()
+                .with_runner(CountingRunner {
+                    inner: MockGitRunner::repo(),
+                    calls: calls.clone(),
+                })
+                .with_cache_ttl(ttl)
+        };
+        let count = || calls.load(std::sync::atomic::Ordering::SeqCst);
+        let ttl = Duration::from_secs(60);
+
+        assert!(segment(ttl).collect_preview(dir.path()).is_some());
+        let ctx = StatusLineContext::new("", dir.path());
+        assert!(segment(ttl).collect(&ctx).is_some());
+        assert_eq!(count(), 1);
+
+        // 选项不同时单独缓存
+        assert!(
+            segment(ttl)
+                .with_show_stash(true)
+                .collect_preview(dir.path())
+                .is_some()
+        );
+        assert_eq!(count(), 2);
+
+        // 强制失效后重新执行 git
+        invalidate_git_cache(Some(dir.path()));
+        assert!(segment(ttl).collect_preview(dir.path()).is_some());
+        assert_eq!(count(), 3);
+
+        // TTL 为 0 时不缓存
+        assert!(
+            segment(Duration::ZERO)
+                .collect_preview(dir.path())
+                .is_some()
+        );
+        assert_eq!(count(), 4);
     }
 }
@@ -0,0 +1,191 @@
+// Host Segment - 显示 user@host，便于区分 SSH 远程会话
+
+use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
+use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentData;
+use crate::statusline::segment::SegmentId;
+use std::path::Path;
+use std::sync::LazyLock;
+
+#[derive(Default)]
+pub struct HostSegment {
+    /// 仅在 SSH 会话中显示（检测 SSH_TTY / SSH_CONNECTION）
+    ssh_only: bool,
+}
+
+impl HostSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        Self::new().with_ssh_only(config.option_bool("ssh_only").unwrap_or(false))
+    }
+
+    pub fn with_ssh_only(mut self, enabled: bool) -> Self {
+        self.ssh_only = enabled;
+        self
+    }
+
+    fn format(&self, env: &HostEnv) -> Option<SegmentData> {
+        if self.ssh_only && !env.ssh {
+            return None;
+        }
+
+        let host = env.host.as_deref().map(short_hostname);
+        let primary = match (env.user.as_deref(), host) {
+            (Some(user), Some(host)) => format!("{user}@{host}"),
+            (None, Some(host)) => host.to_string(),
+            (Some(user), None) => user.to_string(),
+            (None, None) => return None,
+        };
+
+        let mut data = SegmentData::new(primary).with_metadata("ssh", env.ssh.to_string());
+        if let Some(user) = &env.user {
+            data = data.with_metadata("user", user);
+        }
+        if let Some(host) = &env.host {
+            data = data.with_metadata("hostname", host);
+        }
+        Some(data)
+    }
+}
+
+impl Segment for HostSegment {
+    fn collect(&self, _ctx: &StatusLineContext) -> Option<SegmentData> {
+        self.format(&HOST_ENV)
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Host
+    }
+}
+
+/// 进程内不变的环境信息，首次渲染时读取一次
+static HOST_ENV: LazyLock<HostEnv> = LazyLock::new(HostEnv::current);
+
+/// 渲染所需的环境信息
+#[derive(Debug, Default)]
+struct HostEnv {
+    user: Option<String>,
+    host: Option<String>,
+    ssh: bool,
+}
+
+impl HostEnv {
+    fn current() -> Self {
+        Self::resolve(
+            |name| std::env::var(name).ok(),
+            Path::new("/etc/hostname"),
+            gethostname::gethostname().into_string().ok(),
+        )
+    }
+
+    /// 主机名优先取系统调用的结果，其次读取 hostname_file，最后回退到 shell 导出的变量
+    fn resolve(
+        lookup: impl Fn(&str) -> Option<String>,
+        hostname_file: &Path,
+        system_hostname: Option<String>,
+    ) -> Self {
+        let non_empty = |value: String| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        let var = |name: &str| lookup(name).and_then(non_empty);
+
+        let host = system_hostname
+            .and_then(non_empty)
+            .or_else(|| {
+                std::fs::read_to_string(hostname_file)
+                    .ok()
+                    .and_then(non_empty)
+            })
+            .or_else(|| var("HOSTNAME"))
+            .or_else(|| var("COMPUTERNAME"));
+
+        Self {
+            user: var("USER").or_else(|| var("USERNAME")),
+            host,
+            ssh: var("SSH_TTY").is_some() || var("SSH_CONNECTION").is_some(),
+        }
+    }
+}
+
+/// 去掉域名部分，例如 "build01.example.com" → "build01"
+fn short_hostname(host: &str) -> &str {
+    host.split('.').next().unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn env(user: Option<&str>, host: Option<&str>, ssh: bool) -> HostEnv {
+        HostEnv {
+            user: user.map(str::to_string),
+            host: host.map(str::to_string),
+            ssh,
+        }
+    }
+
+    #[test]
+    fn test_user_at_host() {
+        let data = HostSegment::new()
+            .format(&env(Some("alice"), Some("build01.example.com"), true))
+            .expect("host data");
+        assert_eq!(data.primary, "alice@build01");
+        assert_eq!(
+            data.metadata.get("hostname").map(String::as_str),
+            Some("build01.example.com")
+        );
+
+        let data = HostSegment::new()
+            .format(&env(None, Some("laptop"), false))
+            .expect("host data");
+        assert_eq!(data.primary, "laptop");
+
+        assert!(HostSegment::new().format(&env(None, None, false)).is_none());
+    }
+
+    #[test]
+    fn test_ssh_only() {
+        let segment = HostSegment::new().with_ssh_only(true);
+        assert!(
+            segment
+                .format(&env(Some("alice"), Some("laptop"), false))
+                .is_none()
+        );
+        assert_eq!(
+            segment
+                .format(&env(Some("alice"), Some("remote"), true))
+                .map(|data| data.primary),
+            Some("alice@remote".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_hostname_sources() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let missing = dir.path().join("hostname");
+        let no_vars = |_: &str| None;
+
+        // 没有 hostname 文件和环境变量时使用系统主机名
+        let env = HostEnv::resolve(no_vars, &missing, Some("build01.example.com".to_string()));
+        assert_eq!(env.host.as_deref(), Some("build01.example.com"));
+        assert_eq!(env.user, None);
+
+        // 系统调用失败时回退到 hostname 文件，再回退到环境变量
+        let file = dir.path().join("etc-hostname");
+        std::fs::write(&file, "devbox\n").expect("write hostname");
+        assert_eq!(
+            HostEnv::resolve(no_vars, &file, None).host.as_deref(),
+            Some("devbox")
+        );
+        let vars = |name: &str| (name == "HOSTNAME").then(|| "laptop".to_string());
+        assert_eq!(
+            HostEnv::resolve(vars, &missing, None).host.as_deref(),
+            Some("laptop")
+        );
+        assert_eq!(HostEnv::resolve(no_vars, &missing, None).host, None);
+    }
+}
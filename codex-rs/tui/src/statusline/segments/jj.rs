@@ -0,0 +1,265 @@
+// Jj Segment - 显示 Jujutsu 当前 change 和描述
+
+use super::git::GitOutput;
+use crate::statusline::JjPreviewData;
+use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
+use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentAction;
+use crate::statusline::segment::SegmentData;
+use crate::statusline::segment::SegmentId;
+use crate::statusline::truncate::TruncatePosition;
+use crate::statusline::truncate::truncate_display;
+use std::path::Path;
+use std::process::Command;
+
+/// 描述的默认最大显示宽度
+const DEFAULT_DESCRIPTION_WIDTH: usize = 24;
+
+/// `jj log` 模板：change id、工作区状态、描述首行，以 tab 分隔
+const LOG_TEMPLATE: &str = r#"change_id.shortest(8) ++ "\t" ++ if(conflict, "conflict", if(empty, "empty", "dirty")) ++ "\t" ++ description.first_line()"#;
+
+/// Jj 命令执行器，测试时可注入 mock
+pub trait JjRunner: Send + Sync {
+    /// 在 working_dir 下执行 `jj <args>`
+    fn run(&self, working_dir: &str, args: &[&str]) -> std::io::Result<GitOutput>;
+}
+
+/// 调用系统 jj 的默认执行器
+pub struct SystemJjRunner;
+
+impl JjRunner for SystemJjRunner {
+    fn run(&self, working_dir: &str, args: &[&str]) -> std::io::Result<GitOutput> {
+        let output = Command::new("jj")
+            .args(["--no-pager", "--color", "never"])
+            .args(args)
+            .current_dir(working_dir)
+            .output()?;
+
+        Ok(GitOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        })
+    }
+}
+
+pub struct JjSegment {
+    runner: Box<dyn JjRunner>,
+    /// 描述的最大显示宽度，0 表示不显示描述
+    description_width: usize,
+    /// 描述截断时省略号的位置，默认在末尾
+    truncate_position: TruncatePosition,
+}
+
+impl Default for JjSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JjSegment {
+    pub fn new() -> Self {
+        Self {
+            runner: Box::new(SystemJjRunner),
+            description_width: DEFAULT_DESCRIPTION_WIDTH,
+            truncate_position: TruncatePosition::End,
+        }
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        Self::new()
+            .with_description_width(
+                config
+                    .option::<usize>("description_width")
+                    .unwrap_or(DEFAULT_DESCRIPTION_WIDTH),
+            )
+            .with_truncate_position(config.option("truncate_position").unwrap_or_default())
+    }
+
+    /// 替换 jj 执行器
+    pub fn with_runner(mut self, runner: impl JjRunner + 'static) -> Self {
+        self.runner = Box::new(runner);
+        self
+    }
+
+    pub fn with_description_width(mut self, width: usize) -> Self {
+        self.description_width = width;
+        self
+    }
+
+    pub fn with_truncate_position(mut self, position: TruncatePosition) -> Self {
+        self.truncate_position = position;
+        self
+    }
+
+    /// 读取当前工作区 change 信息；不在 jj 仓库中时不执行任何命令
+    pub(crate) fn collect_preview(&self, cwd: &Path) -> Option<JjPreviewData> {
+        if !is_jj_repository(cwd) {
+            return None;
+        }
+
+        let working_dir = cwd.to_string_lossy();
+        let output = match self.runner.run(
+            &working_dir,
+            &["log", "--no-graph", "-r", "@", "-T", LOG_TEMPLATE],
+        ) {
+            Ok(output) if output.success => output.stdout,
+            _ => return None,
+        };
+        parse_log_line(&output)
+    }
+
+    fn format_preview(&self, preview: &JjPreviewData) -> SegmentData {
+        let mut secondary = preview.status.clone();
+        if self.description_width > 0 && !preview.description.is_empty() {
+            secondary.push(' ');
+            secondary.push_str(&truncate_display(
+                &preview.description,
+                self.description_width,
+                self.truncate_position,
+            ));
+        }
+
+        let description = if preview.description.is_empty() {
+            "(no description set)"
+        } else {
+            preview.description.as_str()
+        };
+
+        SegmentData::new(&preview.change_id)
+            .with_secondary(secondary)
+            .with_metadata("change_id", &preview.change_id)
+            .with_metadata("status", &preview.status)
+            .with_detail(format!("{}: {description}", preview.change_id))
+            .with_action(SegmentAction::RunCommand("jj status".to_string()))
+    }
+}
+
+/// cwd 或其任一上级目录包含 `.jj` 时视为 jj 仓库
+fn is_jj_repository(cwd: &Path) -> bool {
+    cwd.ancestors().any(|dir| dir.join(".jj").is_dir())
+}
+
+/// 解析 LOG_TEMPLATE 的输出
+fn parse_log_line(output: &str) -> Option<JjPreviewData> {
+    let line = output.lines().next()?;
+    let mut fields = line.splitn(3, '\t');
+    let change_id = fields.next()?.trim();
+    if change_id.is_empty() {
+        return None;
+    }
+    let status = match fields.next()? {
+        "conflict" => "⚠",
+        "dirty" => "●",
+        _ => "✓",
+    };
+    let description = fields.next().unwrap_or_default().trim();
+
+    Some(JjPreviewData {
+        change_id: change_id.to_string(),
+        status: status.to_string(),
+        description: description.to_string(),
+    })
+}
+
+impl Segment for JjSegment {
+    fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
+        // 如果有预览数据，使用预览数据
+        if let Some(preview) = &ctx.jj_preview {
+            if preview.change_id.is_empty() {
+                return None;
+            }
+            return Some(self.format_preview(preview));
+        }
+
+        let preview = self.collect_preview(ctx.cwd)?;
+        Some(self.format_preview(&preview))
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Jj
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// 返回固定输出的 mock 执行器
+    struct MockJjRunner {
+        stdout: &'static str,
+    }
+
+    impl JjRunner for MockJjRunner {
+        fn run(&self, _working_dir: &str, args: &[&str]) -> std::io::Result<GitOutput> {
+            Ok(GitOutput {
+                success: args.first() == Some(&"log"),
+                stdout: self.stdout.to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_collect_from_jj_log() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(repo.path().join(".jj")).expect("create .jj");
+        let nested = repo.path().join("src");
+        std::fs::create_dir(&nested).expect("create src");
+
+        let segment = JjSegment::new().with_runner(MockJjRunner {
+            stdout: "kxqzmtow\tdirty\tFix the parser for long inputs\n",
+        });
+        let ctx = StatusLineContext::new("", &nested);
+        let data = segment.collect(&ctx).expect("jj data");
+        assert_eq!(data.primary, "kxqzmtow");
+        assert_eq!(data.secondary, "● Fix the parser for long…");
+        assert_eq!(
+            data.detail.as_deref(),
+            Some("kxqzmtow: Fix the parser for long inputs")
+        );
+    }
+
+    #[test]
+    fn test_not_a_jj_repository() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let segment = JjSegment::new().with_runner(MockJjRunner {
+            stdout: "kxqzmtow\tempty\t\n",
+        });
+        let ctx = StatusLineContext::new("", dir.path());
+        assert!(segment.collect(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_parse_log_line() {
+        let preview = parse_log_line("zzmq\tempty\t\n").expect("preview");
+        assert_eq!(preview.status, "✓");
+        assert_eq!(preview.description, "");
+        assert_eq!(
+            parse_log_line("abc\tconflict\tmerge").map(|p| p.status),
+            Some("⚠".to_string())
+        );
+        assert_eq!(parse_log_line(""), None);
+    }
+
+    #[test]
+    fn test_truncate_position_option() {
+        let mut config = SegmentItemConfig::default_for(SegmentId::Jj);
+        config
+            .options
+            .insert("description_width".to_string(), serde_json::json!(10));
+        config
+            .options
+            .insert("truncate_position".to_string(), serde_json::json!("start"));
+        let ctx = StatusLineContext::new("", Path::new("/")).with_jj_preview(
+            "kxqzmtow",
+            "●",
+            "Fix the parser for long inputs",
+        );
+        let data = JjSegment::from_config(&config)
+            .collect(&ctx)
+            .expect("jj data");
+        assert_eq!(data.secondary, "● …ng inputs");
+    }
+}
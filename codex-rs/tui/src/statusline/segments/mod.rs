@@ -1,13 +1,67 @@
 // Segments 模块入口
 
+mod budget;
 mod context;
 mod directory;
+mod edits;
 mod git;
+mod host;
+mod jj;
 mod model;
+mod timezone;
 mod usage;
+mod warning;
 
+pub use budget::BudgetSegment;
 pub use context::ContextSegment;
 pub use directory::DirectorySegment;
+pub use edits::EditsSegment;
+pub use git::GitBaseStore;
+pub use git::GitOutput;
+pub use git::GitRunner;
 pub use git::GitSegment;
+pub use git::GitStateColors;
+pub use git::invalidate_git_cache;
+pub use host::HostSegment;
+pub use jj::JjRunner;
+pub use jj::JjSegment;
 pub use model::ModelSegment;
+pub use timezone::TimezoneSegment;
 pub use usage::UsageSegment;
+pub use warning::WarningSegment;
+
+use crate::statusline::CxLineConfig;
+use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
+use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentData;
+use crate::statusline::segment::SegmentId;
+
+/// 过滤上游传入的异常百分比：NaN、无穷和负数视为没有数据
+pub(crate) fn valid_percent(percent: f64) -> Option<f64> {
+    (percent.is_finite() && percent >= 0.0).then_some(percent)
+}
+
+/// 按 segment 配置构建对应的 segment 并收集数据
+/// `cxline` 为完整配置，用于解析全局选项（如命令缓存时长）
+pub fn collect_segment(
+    cxline: &CxLineConfig,
+    config: &SegmentItemConfig,
+    ctx: &StatusLineContext,
+) -> Option<SegmentData> {
+    match config.id {
+        SegmentId::Model => ModelSegment::from_config(config).collect(ctx),
+        SegmentId::Directory => DirectorySegment::from_config(config).collect(ctx),
+        SegmentId::Git => GitSegment::from_config(config)
+            .with_cache_ttl(cxline.command_cache_ttl_for(config))
+            .collect(ctx),
+        SegmentId::Context => ContextSegment::from_config(config).collect(ctx),
+        SegmentId::Usage => UsageSegment::from_config(config).collect(ctx),
+        SegmentId::Budget => BudgetSegment::from_config(config).collect(ctx),
+        SegmentId::Jj => JjSegment::from_config(config).collect(ctx),
+        SegmentId::Edits => EditsSegment::from_config(config).collect(ctx),
+        SegmentId::Timezone => TimezoneSegment::from_config(config).collect(ctx),
+        SegmentId::Host => HostSegment::from_config(config).collect(ctx),
+        SegmentId::Warning => WarningSegment::from_config(config).collect(ctx),
+    }
+}
@@ -1,36 +1,107 @@
 // Model Segment - 显示当前模型名称
 
 use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
 use crate::statusline::segment::Segment;
 use crate::statusline::segment::SegmentData;
 use crate::statusline::segment::SegmentId;
 use codex_protocol::openai_models::ReasoningEffort;
 
-pub struct ModelSegment;
+/// 模型名为空时的默认占位文本
+const DEFAULT_EMPTY_PLACEHOLDER: &str = "unknown";
+
+/// show_effort 模式下 reasoning effort 前的符号
+const EFFORT_GLYPH: &str = "✦";
+
+#[derive(Default)]
+pub struct ModelSegment {
+    /// 模型名为空时显示的占位文本，None 时隐藏 segment
+    empty_placeholder: Option<String>,
+    /// 在副标题行显示原始模型 ID（如 "gpt-5.2-codex-2025-01-15"）
+    model_id_subtitle: bool,
+    /// 以次要内容显示 reasoning effort（如 "✦ high"），代替模型名后的缩写后缀
+    show_effort: bool,
+}
+
+impl ModelSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        let segment = if config.option_bool("show_when_empty").unwrap_or(false) {
+            let placeholder = config
+                .option_str("empty_placeholder")
+                .unwrap_or(DEFAULT_EMPTY_PLACEHOLDER);
+            Self::new().with_empty_placeholder(placeholder)
+        } else {
+            Self::new()
+        };
+        segment
+            .with_model_id_subtitle(config.option_bool("model_id_subtitle").unwrap_or(false))
+            .with_show_effort(config.option_bool("show_effort").unwrap_or(false))
+    }
+
+    pub fn with_model_id_subtitle(mut self, enabled: bool) -> Self {
+        self.model_id_subtitle = enabled;
+        self
+    }
+
+    pub fn with_show_effort(mut self, enabled: bool) -> Self {
+        self.show_effort = enabled;
+        self
+    }
+
+    /// 模型名为空时显示占位文本而不是隐藏
+    pub fn with_empty_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.empty_placeholder = Some(placeholder.into());
+        self
+    }
+}
 
 impl Segment for ModelSegment {
     fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
-        let model_name = ctx.model_name;
+        let model_name = ctx.model_name.trim();
         if model_name.is_empty() {
-            return None;
+            let placeholder = self.empty_placeholder.as_deref()?;
+            return Some(SegmentData::new(placeholder).with_metadata("model_id", ""));
         }
 
         // 简化模型名称显示
         let display_name = simplify_model_name(model_name);
+        let alt_text = abbreviate_model_name(&display_name);
 
         // Append reasoning effort suffix if present
-        let display_name = if let Some(effort) = ctx.reasoning_effort {
-            let effort_suffix = reasoning_effort_suffix(effort);
-            if effort_suffix.is_empty() {
-                display_name
-            } else {
-                format!("{display_name} {effort_suffix}")
-            }
+        let effort_suffix = match ctx.reasoning_effort {
+            Some(effort) if !self.show_effort => reasoning_effort_suffix(effort),
+            _ => "",
+        };
+        let (display_name, alt_text) = if effort_suffix.is_empty() {
+            (display_name, alt_text)
         } else {
-            display_name
+            (
+                format!("{display_name} {effort_suffix}"),
+                alt_text.map(|alt_text| format!("{alt_text}{effort_suffix}")),
+            )
         };
 
-        Some(SegmentData::new(display_name).with_metadata("model_id", model_name))
+        let mut data = SegmentData::new(display_name).with_metadata("model_id", model_name);
+        if let Some(alt_text) = alt_text {
+            data = data.with_alt_text(alt_text);
+        }
+        if self.model_id_subtitle {
+            data = data.with_subtitle(model_name);
+        }
+        if self.show_effort
+            && let Some(effort) = ctx.reasoning_effort
+            && let Some(name) = reasoning_effort_name(effort)
+        {
+            data = data
+                .with_secondary(format!("{EFFORT_GLYPH} {name}"))
+                .with_metadata("reasoning_effort", name);
+        }
+        Some(data)
     }
 
     fn id(&self) -> SegmentId {
@@ -50,6 +121,18 @@ fn reasoning_effort_suffix(effort: ReasoningEffort) -> &'static str {
     }
 }
 
+/// reasoning effort 的完整名称，None 级别不显示
+fn reasoning_effort_name(effort: ReasoningEffort) -> Option<&'static str> {
+    match effort {
+        ReasoningEffort::None => None,
+        ReasoningEffort::Minimal => Some("minimal"),
+        ReasoningEffort::Low => Some("low"),
+        ReasoningEffort::Medium => Some("medium"),
+        ReasoningEffort::High => Some("high"),
+        ReasoningEffort::XHigh => Some("xhigh"),
+    }
+}
+
 /// 简化模型名称
 /// 例如：gpt-4o-2024-08-06 -> gpt-4o
 ///       claude-3-5-sonnet-20241022 -> claude-3.5-sonnet
@@ -89,9 +172,58 @@ fn simplify_model_name(name: &str) -> String {
     }
 }
 
+/// 窄屏下的模型名缩写：版本号加其后各词首字母
+/// 例如：GPT 5.1 Codex Max -> 5.1cm，GPT 5.2 -> 5.2；不比原名短时返回 None
+fn abbreviate_model_name(display_name: &str) -> Option<String> {
+    let words: Vec<&str> = display_name.split_whitespace().collect();
+    let version = words
+        .iter()
+        .position(|word| word.chars().any(|c| c.is_ascii_digit()))?;
+    let mut abbreviation = words[version].to_string();
+    abbreviation.extend(
+        words[version + 1..]
+            .iter()
+            .filter_map(|word| word.chars().next())
+            .flat_map(char::to_lowercase),
+    );
+    (abbreviation.chars().count() < display_name.chars().count()).then_some(abbreviation)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_empty_model_name_hidden_by_default() {
+        let segment = ModelSegment::from_config(&SegmentItemConfig::default_for(SegmentId::Model));
+        for name in ["", "   "] {
+            let ctx = StatusLineContext::new(name, Path::new("/"));
+            assert!(segment.collect(&ctx).is_none());
+        }
+    }
+
+    #[test]
+    fn test_empty_model_name_placeholder() {
+        let mut config = SegmentItemConfig::default_for(SegmentId::Model);
+        config
+            .options
+            .insert("show_when_empty".to_string(), serde_json::json!(true));
+        let ctx = StatusLineContext::new(" ", Path::new("/"));
+
+        let data = ModelSegment::from_config(&config)
+            .collect(&ctx)
+            .expect("placeholder data");
+        assert_eq!(data.primary, "unknown");
+
+        config
+            .options
+            .insert("empty_placeholder".to_string(), serde_json::json!("?"));
+        let data = ModelSegment::from_config(&config)
+            .collect(&ctx)
+            .expect("placeholder data");
+        assert_eq!(data.primary, "?");
+    }
 
     #[test]
     fn test_simplify_model_name() {
@@ -114,4 +246,74 @@ mod tests {
         // 测试无映射的模型
         assert_eq!(simplify_model_name("custom-model"), "custom-model");
     }
+
+    #[test]
+    fn test_no_click_action() {
+        let ctx = StatusLineContext::new("gpt-5.2-codex", Path::new("/"));
+        let data = ModelSegment::new().collect(&ctx).expect("model data");
+        assert_eq!(data.action, None);
+    }
+
+    #[test]
+    fn test_model_id_subtitle() {
+        let ctx = StatusLineContext::new("gpt-5.2-codex-2025-01-15", Path::new("/"));
+        let data = ModelSegment::new()
+            .with_model_id_subtitle(true)
+            .collect(&ctx)
+            .expect("model data");
+        assert_eq!(data.primary, "GPT 5.2 Codex");
+        assert_eq!(data.subtitle.as_deref(), Some("gpt-5.2-codex-2025-01-15"));
+    }
+
+    #[test]
+    fn test_show_effort_as_secondary() {
+        let segment = ModelSegment::new().with_show_effort(true);
+        let collect = |effort| {
+            let ctx = StatusLineContext::new("gpt-5.2-codex", Path::new("/"))
+                .with_reasoning_effort(effort);
+            segment.collect(&ctx).expect("model data")
+        };
+
+        for (effort, name) in [
+            (ReasoningEffort::High, "high"),
+            (ReasoningEffort::Medium, "medium"),
+            (ReasoningEffort::Low, "low"),
+        ] {
+            let data = collect(Some(effort));
+            assert_eq!(data.primary, "GPT 5.2 Codex");
+            assert_eq!(data.secondary, format!("✦ {name}"));
+            assert_eq!(
+                data.metadata.get("reasoning_effort").map(String::as_str),
+                Some(name)
+            );
+        }
+
+        let data = collect(None);
+        assert_eq!(data.secondary, "");
+        assert_eq!(data.metadata.get("reasoning_effort"), None);
+
+        // 默认仍以缩写后缀显示
+        let ctx = StatusLineContext::new("gpt-5.2-codex", Path::new("/"))
+            .with_reasoning_effort(Some(ReasoningEffort::High));
+        let data = ModelSegment::new().collect(&ctx).expect("model data");
+        assert_eq!(data.primary, "GPT 5.2 Codex ·hi");
+        assert_eq!(data.secondary, "");
+    }
+
+    #[test]
+    fn test_abbreviate_model_name() {
+        assert_eq!(
+            abbreviate_model_name("GPT 5.1 Codex Max").as_deref(),
+            Some("5.1cm")
+        );
+        assert_eq!(abbreviate_model_name("GPT 5.2").as_deref(), Some("5.2"));
+        assert_eq!(abbreviate_model_name("o3"), None);
+        assert_eq!(abbreviate_model_name("claude-3.5-sonnet"), None);
+
+        let ctx = StatusLineContext::new("gpt-5.1-codex-max", Path::new("/"))
+            .with_reasoning_effort(Some(ReasoningEffort::High));
+        let data = ModelSegment::new().collect(&ctx).expect("model data");
+        assert_eq!(data.primary, "GPT 5.1 Codex Max ·hi");
+        assert_eq!(data.alt_text.as_deref(), Some("5.1cm·hi"));
+    }
 }
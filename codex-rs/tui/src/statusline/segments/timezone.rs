@@ -0,0 +1,101 @@
+// Timezone Segment - 显示本地时区 UTC 偏移
+
+use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
+use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentData;
+use crate::statusline::segment::SegmentId;
+use chrono::Local;
+use chrono::Offset;
+
+#[derive(Default)]
+pub struct TimezoneSegment {
+    /// 同时显示 IANA 时区名（取自 TZ 环境变量，可用时）
+    show_zone_name: bool,
+}
+
+impl TimezoneSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        Self::new().with_zone_name(config.option_bool("show_zone_name").unwrap_or(false))
+    }
+
+    pub fn with_zone_name(mut self, enabled: bool) -> Self {
+        self.show_zone_name = enabled;
+        self
+    }
+}
+
+impl Segment for TimezoneSegment {
+    fn collect(&self, _ctx: &StatusLineContext) -> Option<SegmentData> {
+        let offset_seconds = Local::now().offset().fix().local_minus_utc();
+        let offset = format_utc_offset(offset_seconds);
+
+        let mut data =
+            SegmentData::new(&offset).with_metadata("offset_seconds", offset_seconds.to_string());
+        if self.show_zone_name
+            && let Some(zone) = iana_zone_name(std::env::var("TZ").ok().as_deref())
+        {
+            data = data
+                .with_secondary(zone)
+                .with_metadata("zone", zone.to_string());
+        }
+        Some(data)
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Timezone
+    }
+}
+
+/// 将 UTC 偏移（秒）格式化为紧凑形式，例如 "UTC+2"、"UTC-5:30"、"UTC"
+fn format_utc_offset(seconds: i32) -> String {
+    if seconds == 0 {
+        return "UTC".to_string();
+    }
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.unsigned_abs() / 60;
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    if minutes == 0 {
+        format!("UTC{sign}{hours}")
+    } else {
+        format!("UTC{sign}{hours}:{minutes:02}")
+    }
+}
+
+/// 从 TZ 取 IANA 时区名，例如 "Europe/Berlin"；POSIX 规则串（如 "CET-1"）不算
+fn iana_zone_name(tz: Option<&str>) -> Option<&str> {
+    let zone = tz?.trim().trim_start_matches(':');
+    zone.contains('/').then_some(zone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_utc_offset() {
+        assert_eq!(format_utc_offset(0), "UTC");
+        assert_eq!(format_utc_offset(2 * 3600), "UTC+2");
+        assert_eq!(format_utc_offset(-5 * 3600), "UTC-5");
+        assert_eq!(format_utc_offset(5 * 3600 + 30 * 60), "UTC+5:30");
+        assert_eq!(format_utc_offset(-(9 * 3600 + 30 * 60)), "UTC-9:30");
+        assert_eq!(format_utc_offset(12 * 3600 + 45 * 60), "UTC+12:45");
+    }
+
+    #[test]
+    fn test_iana_zone_name() {
+        assert_eq!(iana_zone_name(Some("Europe/Berlin")), Some("Europe/Berlin"));
+        assert_eq!(
+            iana_zone_name(Some(":America/New_York")),
+            Some("America/New_York")
+        );
+        assert_eq!(iana_zone_name(Some("CET-1")), None);
+        assert_eq!(iana_zone_name(None), None);
+    }
+}
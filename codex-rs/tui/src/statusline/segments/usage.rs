@@ -1,34 +1,123 @@
 // Usage Segment - 显示 Rate Limit 使用情况
 
+use super::valid_percent;
 use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
 use crate::statusline::segment::Segment;
 use crate::statusline::segment::SegmentData;
 use crate::statusline::segment::SegmentId;
+use crate::statusline::style::ramp_color;
+use chrono::DateTime;
+use chrono::Datelike;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use serde::Deserialize;
+use serde::Serialize;
 
-pub struct UsageSegment;
+/// 周限重置时间的显示格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetFormat {
+    /// 原样显示宿主提供的文本
+    #[default]
+    Raw,
+    /// 距离重置的时长，例如 "in 12m"
+    Relative,
+    /// 重置时刻，例如 "14:30"；不在今天时带星期，例如 "Tue 14:30"
+    Clock,
+}
+
+#[derive(Default)]
+pub struct UsageSegment {
+    /// 按周限使用率为图标和文本着色（绿 → 红）
+    color_by_level: bool,
+    /// 显示剩余额度而不是已用额度
+    display_remaining: bool,
+    reset_format: ResetFormat,
+}
+
+impl UsageSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(config: &SegmentItemConfig) -> Self {
+        Self::new()
+            .with_color_by_level(config.option_bool("color_by_level").unwrap_or(false))
+            .with_display_remaining(config.option_bool("display_remaining").unwrap_or(false))
+            .with_reset_format(config.option("reset_format").unwrap_or_default())
+    }
+
+    pub fn with_color_by_level(mut self, enabled: bool) -> Self {
+        self.color_by_level = enabled;
+        self
+    }
+
+    /// 以 "28% left" 形式显示剩余额度，圆圈图标按剩余比例填充
+    pub fn with_display_remaining(mut self, enabled: bool) -> Self {
+        self.display_remaining = enabled;
+        self
+    }
+
+    /// 无法解析重置时间时退回原样显示
+    pub fn with_reset_format(mut self, format: ResetFormat) -> Self {
+        self.reset_format = format;
+        self
+    }
+}
 
 impl Segment for UsageSegment {
     fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
-        // 5h limit 用于百分比显示
-        let hourly_percent = ctx.hourly_rate_limit_percent?;
+        // 5h limit 用于百分比显示，异常值按没有数据处理
+        let hourly_percent = ctx.hourly_rate_limit_percent.and_then(valid_percent)?;
         // Weekly limit 用于圆圈进度
-        let weekly_percent = ctx.weekly_rate_limit_percent.unwrap_or(hourly_percent);
+        let weekly_percent = ctx
+            .weekly_rate_limit_percent
+            .and_then(valid_percent)
+            .unwrap_or(hourly_percent);
 
-        // 格式化百分比 (显示 5h limit)
-        let display = format!("{hourly_percent:.0}%");
+        // 格式化百分比 (显示 5h limit)，圆圈按周限填充
+        let (display, circle_fraction) = if self.display_remaining {
+            let hourly_remaining = (100.0 - hourly_percent).max(0.0);
+            let weekly_remaining = (100.0 - weekly_percent).max(0.0);
+            (
+                format!("{hourly_remaining:.0}% left"),
+                weekly_remaining / 100.0,
+            )
+        } else {
+            (format!("{hourly_percent:.0}%"), weekly_percent / 100.0)
+        };
 
-        // 动态图标：根据周限使用率选择不同的圆形切片图标
-        let dynamic_icon = get_circle_icon(weekly_percent / 100.0);
+        // 动态图标：根据周限使用率（或剩余率）选择不同的圆形切片图标
+        let dynamic_icon = get_circle_icon(circle_fraction);
 
         let mut data = SegmentData::new(display)
             .with_metadata("hourly_percent", format!("{hourly_percent:.1}"))
             .with_metadata("weekly_percent", format!("{weekly_percent:.1}"))
             .with_metadata("dynamic_icon", dynamic_icon);
 
-        // 添加周限重置时间
+        // 颜色始终按使用率渐变：剩余越少越红
+        if self.color_by_level {
+            data = data.with_color_hint(ramp_color(weekly_percent / 100.0));
+        }
+
+        // 添加周限重置时间，metadata 保留原始文本；有精确时刻时按时刻计算
         if let Some(ref resets_at) = ctx.weekly_rate_limit_resets_at {
+            let now = Local::now();
+            let reset = ctx
+                .weekly_rate_limit_resets_at_timestamp
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+                .map(|reset| reset.with_timezone(&Local));
+            let secondary = match reset {
+                Some(reset) if self.reset_format != ResetFormat::Raw => {
+                    format_reset_at(&reset, self.reset_format, &now)
+                }
+                _ => format_reset(resets_at, self.reset_format, &now),
+            };
             data = data
-                .with_secondary(format!("· {resets_at}"))
+                .with_secondary(secondary)
                 .with_metadata("resets_at", resets_at);
         }
 
@@ -40,6 +129,82 @@ impl Segment for UsageSegment {
     }
 }
 
+/// 按格式显示重置时间，解析失败时原样返回
+fn format_reset<Tz: TimeZone>(raw: &str, format: ResetFormat, now: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let reset = match format {
+        ResetFormat::Raw => None,
+        ResetFormat::Relative | ResetFormat::Clock => parse_reset(raw, now),
+    };
+    match reset {
+        Some(reset) => format_reset_at(&reset, format, now),
+        None => raw.to_string(),
+    }
+}
+
+/// 按 Relative/Clock 格式显示已知的重置时刻
+fn format_reset_at<Tz: TimeZone>(
+    reset: &DateTime<Tz>,
+    format: ResetFormat,
+    now: &DateTime<Tz>,
+) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    if format == ResetFormat::Clock {
+        let pattern = if reset.date_naive() == now.date_naive() {
+            "%H:%M"
+        } else {
+            "%a %H:%M"
+        };
+        return reset.format(pattern).to_string();
+    }
+
+    let minutes = (reset.clone() - now.clone()).num_minutes();
+    if minutes <= 0 {
+        return "now".to_string();
+    }
+    match (minutes / 1440, minutes % 1440 / 60, minutes % 60) {
+        (0, 0, m) => format!("in {m}m"),
+        (0, h, 0) => format!("in {h}h"),
+        (0, h, m) => format!("in {h}h {m}m"),
+        (d, 0, _) => format!("in {d}d"),
+        (d, h, _) => format!("in {d}d {h}h"),
+    }
+}
+
+/// 解析重置时间，依次尝试 RFC 3339、Unix 秒和宿主使用的本地 "M-D-H" 格式
+/// "M-D-H" 没有年份，取不早于 now 一天的最近年份
+fn parse_reset<Tz: TimeZone>(raw: &str, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let raw = raw.trim();
+    let tz = now.timezone();
+    if let Ok(reset) = DateTime::parse_from_rfc3339(raw) {
+        return Some(reset.with_timezone(&tz));
+    }
+    if let Ok(seconds) = raw.parse::<i64>() {
+        return DateTime::from_timestamp(seconds, 0).map(|reset| reset.with_timezone(&tz));
+    }
+
+    let mut parts = raw.split('-').map(str::parse::<u32>);
+    let (Some(Ok(month)), Some(Ok(day)), Some(Ok(hour)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    let at_year = |year: i32| {
+        let naive = NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(hour, 0, 0)?;
+        tz.from_local_datetime(&naive).earliest()
+    };
+    let reset = at_year(now.year())?;
+    if reset < now.clone() - chrono::Duration::days(1) {
+        at_year(now.year() + 1)
+    } else {
+        Some(reset)
+    }
+}
+
 /// 根据使用率获取圆形切片图标
 /// 使用 Nerd Font Material Design Icons
 fn get_circle_icon(utilization: f64) -> String {
@@ -59,6 +224,30 @@ fn get_circle_icon(utilization: f64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::statusline::style::AnsiColor;
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+    use std::path::Path;
+
+    fn hint_for(percent: f64, segment: &UsageSegment) -> Option<AnsiColor> {
+        let ctx = StatusLineContext::new("", Path::new("/")).with_rate_limit(
+            Some(percent),
+            Some(percent),
+            None,
+        );
+        segment.collect(&ctx).expect("usage data").color_hint
+    }
+
+    #[test]
+    fn test_color_by_level() {
+        let segment = UsageSegment::new().with_color_by_level(true);
+        assert_eq!(hint_for(10.0, &segment), Some(AnsiColor::rgb(51, 255, 0)));
+        assert_eq!(hint_for(60.0, &segment), Some(AnsiColor::rgb(255, 204, 0)));
+        assert_eq!(hint_for(95.0, &segment), Some(AnsiColor::rgb(255, 26, 0)));
+
+        // 默认关闭，保持主题颜色
+        assert_eq!(hint_for(95.0, &UsageSegment::new()), None);
+    }
 
     #[test]
     fn test_get_circle_icon() {
@@ -67,4 +256,115 @@ mod tests {
         assert_eq!(get_circle_icon(0.5), "\u{f0aa1}");
         assert_eq!(get_circle_icon(1.0), "\u{f0aa5}");
     }
+
+    #[test]
+    fn test_display_remaining() {
+        let segment = UsageSegment::new()
+            .with_display_remaining(true)
+            .with_color_by_level(true);
+        let ctx = StatusLineContext::new("", Path::new("/")).with_rate_limit(
+            Some(72.0),
+            Some(72.0),
+            None,
+        );
+        let data = segment.collect(&ctx).expect("usage data");
+
+        assert_eq!(data.primary, "28% left");
+        assert_eq!(
+            data.metadata.get("dynamic_icon"),
+            Some(&get_circle_icon(0.28))
+        );
+        let Some(AnsiColor::Rgb { r, g, .. }) = data.color_hint else {
+            panic!("expected rgb hint, got {:?}", data.color_hint);
+        };
+        assert!(r == 255 && g < 160, "expected red-ish hint, got ({r}, {g})");
+    }
+
+    #[test]
+    fn test_invalid_percent_is_no_data() {
+        for bad in [f64::NAN, f64::INFINITY, -5.0] {
+            let ctx =
+                StatusLineContext::new("", Path::new("/")).with_rate_limit(Some(bad), None, None);
+            assert!(UsageSegment::new().collect(&ctx).is_none(), "{bad}");
+
+            // 周限异常时退回使用 5h 限额
+            let ctx = StatusLineContext::new("", Path::new("/")).with_rate_limit(
+                Some(40.0),
+                Some(bad),
+                None,
+            );
+            let data = UsageSegment::new().collect(&ctx).expect("usage data");
+            assert_eq!(data.primary, "40%");
+            assert_eq!(
+                data.metadata.get("weekly_percent").map(String::as_str),
+                Some("40.0")
+            );
+        }
+    }
+
+    #[test]
+    fn test_reset_format() {
+        let now = Utc
+            .with_ymd_and_hms(2026, 1, 28, 14, 18, 0)
+            .single()
+            .expect("fixed now");
+        let reset = "2026-01-28T14:30:00Z";
+
+        assert_eq!(format_reset(reset, ResetFormat::Raw, &now), reset);
+        assert_eq!(format_reset(reset, ResetFormat::Relative, &now), "in 12m");
+        assert_eq!(format_reset(reset, ResetFormat::Clock, &now), "14:30");
+
+        // Unix 秒与宿主的 "M-D-H" 格式
+        let later = (now + chrono::Duration::hours(50)).timestamp().to_string();
+        assert_eq!(
+            format_reset(&later, ResetFormat::Relative, &now),
+            "in 2d 2h"
+        );
+        assert_eq!(
+            format_reset("1-28-17", ResetFormat::Relative, &now),
+            "in 2h 42m"
+        );
+        assert_eq!(format_reset("2-3-9", ResetFormat::Clock, &now), "Tue 09:00");
+
+        // 跨年：12 月底时 1 月的重置属于下一年
+        let december = Utc
+            .with_ymd_and_hms(2026, 12, 30, 8, 0, 0)
+            .single()
+            .expect("fixed now");
+        assert_eq!(
+            format_reset("1-1-8", ResetFormat::Relative, &december),
+            "in 2d"
+        );
+
+        // 已过期和无法解析时的退回
+        assert_eq!(
+            format_reset("2026-01-28T14:00:00Z", ResetFormat::Relative, &now),
+            "now"
+        );
+        assert_eq!(format_reset("soon", ResetFormat::Clock, &now), "soon");
+    }
+
+    #[test]
+    fn test_reset_timestamp_overrides_hour_text() {
+        // 文本只精确到小时，按精确时刻计算
+        let reset = Local::now() + chrono::Duration::seconds(12 * 60 + 30);
+        let ctx = StatusLineContext::new("", Path::new("/"))
+            .with_rate_limit(Some(10.0), Some(20.0), Some("1-1-1".to_string()))
+            .with_rate_limit_reset_timestamp(Some(reset.timestamp()));
+
+        let relative = UsageSegment::new()
+            .with_reset_format(ResetFormat::Relative)
+            .collect(&ctx)
+            .expect("usage data");
+        assert_eq!(relative.secondary, "in 12m");
+
+        let clock = UsageSegment::new()
+            .with_reset_format(ResetFormat::Clock)
+            .collect(&ctx)
+            .expect("usage data");
+        assert_eq!(clock.secondary, reset.format("%H:%M").to_string());
+
+        let raw = UsageSegment::new().collect(&ctx).expect("usage data");
+        assert_eq!(raw.secondary, "1-1-1");
+    }
 }
@@ -0,0 +1,69 @@
+// Warning Segment - 配置加载失败时提示已回退到默认配置
+
+use crate::statusline::StatusLineContext;
+use crate::statusline::config::SegmentItemConfig;
+use crate::statusline::segment::Segment;
+use crate::statusline::segment::SegmentData;
+use crate::statusline::segment::SegmentId;
+use crate::statusline::style::ansi16;
+
+#[derive(Default)]
+pub struct WarningSegment;
+
+impl WarningSegment {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 根据 segment 配置的 options 构建
+    pub fn from_config(_config: &SegmentItemConfig) -> Self {
+        Self::new()
+    }
+}
+
+impl Segment for WarningSegment {
+    fn collect(&self, ctx: &StatusLineContext) -> Option<SegmentData> {
+        // 没有错误时隐藏
+        let error = ctx.config_error.as_deref()?;
+        Some(
+            SegmentData::new("⚠ config error")
+                .with_color_hint(ansi16::RED)
+                .with_detail(error)
+                .with_metadata("error", error),
+        )
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Warning
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statusline::LoadedConfig;
+    use pretty_assertions::assert_eq;
+    use std::path::Path;
+
+    #[test]
+    fn test_malformed_config_renders_warning() {
+        let loaded = LoadedConfig::from_toml("style = [not toml");
+        let error = loaded.last_error.expect("parse error");
+        assert!(error.starts_with("解析配置失败"), "{error}");
+
+        let ctx = StatusLineContext::new("", Path::new("/")).with_config_error(Some(error.clone()));
+        let data = WarningSegment::new().collect(&ctx).expect("warning data");
+        assert_eq!(data.primary, "⚠ config error");
+        assert_eq!(data.color_hint, Some(ansi16::RED));
+        assert_eq!(data.detail, Some(error));
+
+        let ctx = StatusLineContext::new("", Path::new("/"));
+        assert!(WarningSegment::new().collect(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_valid_config_has_no_error() {
+        let toml = toml::to_string(&crate::statusline::CxLineConfig::default()).expect("toml");
+        assert!(LoadedConfig::from_toml(&toml).last_error.is_none());
+    }
+}
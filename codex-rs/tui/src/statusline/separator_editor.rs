@@ -44,6 +44,21 @@ impl SeparatorEditor {
                 value: "\u{e0b0}",
                 description: "Powerline arrow",
             },
+            SeparatorPreset {
+                name: "Round",
+                value: "\u{e0b4}",
+                description: "Powerline rounded cap",
+            },
+            SeparatorPreset {
+                name: "Flame",
+                value: "\u{e0c0}",
+                description: "Powerline flame",
+            },
+            SeparatorPreset {
+                name: "Slant",
+                value: "\u{e0b8}",
+                description: "Powerline slant",
+            },
             SeparatorPreset {
                 name: "Space",
                 value: "  ",
@@ -115,6 +130,23 @@ impl SeparatorEditor {
         self.input.clone()
     }
 
+    /// 列出所有可选分隔符 (label, value)，当前输入不是预设时追加 "Custom"
+    pub fn all_options(&self) -> Vec<(String, String)> {
+        let presets = Self::presets();
+        let is_preset = presets.iter().any(|preset| preset.value == self.input);
+
+        let mut options: Vec<(String, String)> = presets
+            .into_iter()
+            .map(|preset| (preset.name.to_string(), preset.value.to_string()))
+            .collect();
+
+        if !is_preset && !self.input.is_empty() {
+            options.push(("Custom".to_string(), self.input.clone()));
+        }
+
+        options
+    }
+
     pub fn render(&self, area: Rect, buf: &mut Buffer) {
         if !self.is_open {
             return;
@@ -183,3 +215,31 @@ impl SeparatorEditor {
             .render(help_area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_options_custom_entry() {
+        let mut editor = SeparatorEditor::default();
+        editor.open(" >> ");
+
+        let options = editor.all_options();
+        assert_eq!(options.len(), SeparatorEditor::presets().len() + 1);
+        assert_eq!(
+            options.last(),
+            Some(&("Custom".to_string(), " >> ".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_all_options_preset_matches_label() {
+        let mut editor = SeparatorEditor::default();
+        editor.open(" • ");
+
+        let options = editor.all_options();
+        assert_eq!(options.len(), SeparatorEditor::presets().len());
+        assert!(options.contains(&("Dot".to_string(), " • ".to_string())));
+    }
+}
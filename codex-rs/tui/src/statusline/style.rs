@@ -16,6 +16,52 @@ pub enum StyleMode {
     NerdFont,
     /// Powerline 模式（带背景色和箭头分隔符）
     Powerline,
+    /// Powerline 模式，并在每个 pill 内的图标与文本之间加细分隔符
+    PowerlineSeparated,
+}
+
+/// Powerline 过渡字符形状
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerlineGlyph {
+    /// 经典箭头
+    #[default]
+    Arrow,
+    /// 圆角
+    Round,
+    /// 火焰
+    Flame,
+    /// 斜切
+    Slant,
+}
+
+impl PowerlineGlyph {
+    pub const ALL: [PowerlineGlyph; 4] = [Self::Arrow, Self::Round, Self::Flame, Self::Slant];
+
+    /// segment 之间的实心过渡字符
+    pub fn filled(self) -> &'static str {
+        match self {
+            Self::Arrow => separators::POWERLINE,
+            Self::Round => "\u{e0b4}",
+            Self::Flame => "\u{e0c0}",
+            Self::Slant => "\u{e0b8}",
+        }
+    }
+
+    /// 同一形状的空心（细线）字符，用于 pill 内的细分隔符
+    pub fn thin(self) -> &'static str {
+        match self {
+            Self::Arrow => separators::POWERLINE_THIN,
+            Self::Round => "\u{e0b5}",
+            Self::Flame => "\u{e0c1}",
+            Self::Slant => "\u{e0b9}",
+        }
+    }
+
+    /// 根据实心过渡字符反查形状（分隔符编辑器选中 Powerline 预设时使用）
+    pub fn from_filled(glyph: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|shape| shape.filled() == glyph)
+    }
 }
 
 /// ANSI 颜色（支持 16 色、256 色、RGB）
@@ -48,6 +94,45 @@ impl AnsiColor {
         Self::Rgb { r, g, b }
     }
 
+    /// 解析 `RRGGBB` 或 `#RRGGBB`，其余输入（含多字节字符）一律返回 None
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        // 先按字节校验，保证后续不会在字符中间切片
+        if digits.len() != 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let [_, r, g, b] = u32::from_str_radix(digits, 16).ok()?.to_be_bytes();
+        Some(Self::rgb(r, g, b))
+    }
+
+    /// 对应的 RGB 值，16/256 色按 xterm 默认调色板换算
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Self::Color16 { c16 } => ansi256_to_rgb(c16.min(15)),
+            Self::Color256 { c256 } => ansi256_to_rgb(c256),
+            Self::Rgb { r, g, b } => (r, g, b),
+        }
+    }
+
+    /// 是否为 24 位 RGB 颜色
+    pub fn is_rgb(&self) -> bool {
+        matches!(self, Self::Rgb { .. })
+    }
+
+    /// 最接近的 xterm 256 色索引（16 色索引本身就是 256 色的前 16 个）
+    pub fn to_256(&self) -> u8 {
+        match *self {
+            Self::Color16 { c16 } => c16,
+            Self::Color256 { c256 } => c256,
+            Self::Rgb { r, g, b } => rgb_to_ansi256(r, g, b),
+        }
+    }
+
+    /// 按终端颜色级别转换为 ratatui Color，超出终端能力的颜色映射到最接近的可用颜色
+    pub fn to_ratatui_color_for(&self, support: ColorSupport) -> Color {
+        self.downgrade(support).to_ratatui_color()
+    }
+
     /// 转换为 ratatui Color
     #[allow(clippy::disallowed_methods)] // 颜色系统需要支持 256 色和 RGB
     pub fn to_ratatui_color(&self) -> Color {
@@ -77,6 +162,172 @@ impl AnsiColor {
     }
 }
 
+/// 颜色字符串无法解析
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("无法解析颜色: {0:?}")]
+pub struct ParseColorError(pub String);
+
+impl TryFrom<&str> for AnsiColor {
+    type Error = ParseColorError;
+
+    /// 接受颜色名（"red"、"light_blue"）、十六进制（"#ff8800"）、
+    /// 带前缀的索引（"c16:5"、"c256:214"）和纯数字索引（0-15 为 16 色，其余为 256 色）
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let input = value.trim();
+        let error = || ParseColorError(value.to_string());
+
+        if let Some(code) = ansi16_index_from_name(input) {
+            return Ok(Self::c16(code));
+        }
+        if let Some(index) = input.strip_prefix("c256:") {
+            return index.parse().map(Self::c256).map_err(|_| error());
+        }
+        if let Some(index) = input.strip_prefix("c16:") {
+            return match index.parse::<u8>() {
+                Ok(code) if code < 16 => Ok(Self::c16(code)),
+                _ => Err(error()),
+            };
+        }
+        if let Ok(code) = input.parse::<u8>() {
+            return Ok(if code < 16 {
+                Self::c16(code)
+            } else {
+                Self::c256(code)
+            });
+        }
+        Self::from_hex(input).ok_or_else(error)
+    }
+}
+
+/// 终端颜色支持级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSupport {
+    /// 24 位真彩色
+    #[default]
+    TrueColor,
+    /// 256 色调色板
+    Ansi256,
+    /// 标准 16 色
+    Ansi16,
+}
+
+impl AnsiColor {
+    /// 降级为终端支持的颜色
+    pub fn downgrade(self, support: ColorSupport) -> Self {
+        match (support, self) {
+            (ColorSupport::TrueColor, _) => self,
+            (ColorSupport::Ansi256, Self::Rgb { r, g, b }) => Self::c256(rgb_to_ansi256(r, g, b)),
+            (ColorSupport::Ansi256, _) => self,
+            (ColorSupport::Ansi16, Self::Color16 { .. }) => self,
+            (ColorSupport::Ansi16, Self::Color256 { c256 }) => {
+                let (r, g, b) = ansi256_to_rgb(c256);
+                Self::c16(rgb_to_ansi16(r, g, b))
+            }
+            (ColorSupport::Ansi16, Self::Rgb { r, g, b }) => Self::c16(rgb_to_ansi16(r, g, b)),
+        }
+    }
+}
+
+/// xterm 6x6x6 色块每个分量的取值
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// xterm 默认的 16 色 RGB 值
+const ANSI16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// RGB 到 xterm 256 色的最近匹配
+/// 分别在 6x6x6 色块（16-231）和灰阶（232-255）中取最近值，再取二者中更接近的
+pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |v: u8| -> u8 {
+        match v {
+            0..48 => 0,
+            48..115 => 1,
+            _ => (v - 35) / 40,
+        }
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = (
+        CUBE_LEVELS[ri as usize],
+        CUBE_LEVELS[gi as usize],
+        CUBE_LEVELS[bi as usize],
+    );
+    let cube_code = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_index = if average > 238 {
+        23
+    } else {
+        (average.saturating_sub(3) / 10) as u8
+    };
+    let gray_level = 8 + 10 * gray_index;
+    let gray_code = 232 + gray_index;
+
+    if distance_sq((r, g, b), (gray_level, gray_level, gray_level)) < distance_sq((r, g, b), cube) {
+        gray_code
+    } else {
+        cube_code
+    }
+}
+
+/// xterm 256 色索引对应的 RGB 值
+pub fn ansi256_to_rgb(code: u8) -> (u8, u8, u8) {
+    match code {
+        0..16 => ANSI16_RGB[code as usize],
+        16..232 => {
+            let index = code - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (code - 232);
+            (level, level, level)
+        }
+    }
+}
+
+impl ColorSupport {
+    /// 将已转换的 ratatui Color 按 [`AnsiColor::downgrade`] 降级；命名的 16 色保持不变
+    pub fn downgrade_color(self, color: Color) -> Color {
+        let ansi = match color {
+            Color::Rgb(r, g, b) => AnsiColor::rgb(r, g, b),
+            Color::Indexed(code) => AnsiColor::c256(code),
+            _ => return color,
+        };
+        ansi.downgrade(self).to_ratatui_color()
+    }
+}
+
+/// RGB 到标准 16 色的最近匹配
+pub fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> u8 {
+    (0u8..16)
+        .min_by_key(|code| distance_sq((r, g, b), ANSI16_RGB[*code as usize]))
+        .unwrap_or(0)
+}
+
+fn distance_sq(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
 /// 预定义 16 色常量
 pub mod ansi16 {
     use super::AnsiColor;
@@ -120,7 +371,9 @@ impl IconConfig {
     pub fn get(&self, mode: StyleMode) -> &str {
         match mode {
             StyleMode::Plain => &self.plain,
-            StyleMode::NerdFont | StyleMode::Powerline => &self.nerd_font,
+            StyleMode::NerdFont | StyleMode::Powerline | StyleMode::PowerlineSeparated => {
+                &self.nerd_font
+            }
         }
     }
 }
@@ -137,6 +390,9 @@ pub struct ColorConfig {
     /// 背景颜色（主要用于 Powerline 模式）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub background: Option<AnsiColor>,
+    /// 徽标颜色（未配置时使用亮黄色）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub badge: Option<AnsiColor>,
 }
 
 impl ColorConfig {
@@ -145,6 +401,7 @@ impl ColorConfig {
             icon: Some(icon),
             text: Some(text),
             background: None,
+            badge: None,
         }
     }
 
@@ -163,12 +420,37 @@ impl ColorConfig {
         self.text.map(|c| c.to_ratatui_color())
     }
 
+    /// 获取图标实际使用的前景色
+    /// 回退顺序：icon → text → None（使用终端默认前景色）
+    pub fn resolved_icon_color(&self) -> Option<Color> {
+        self.icon_color().or_else(|| self.text_color())
+    }
+
+    /// 获取徽标的 ratatui Color
+    pub fn badge_color(&self) -> Color {
+        self.badge
+            .unwrap_or(ansi16::BRIGHT_YELLOW)
+            .to_ratatui_color()
+    }
+
     /// 获取背景的 ratatui Color
     pub fn background_color(&self) -> Option<Color> {
         self.background.map(|c| c.to_ratatui_color())
     }
 }
 
+/// 按比例在 绿 → 黄 → 红 之间渐变，fraction 取值 0.0..=1.0
+pub fn ramp_color(fraction: f64) -> AnsiColor {
+    let fraction = if fraction.is_nan() {
+        0.0
+    } else {
+        fraction.clamp(0.0, 1.0)
+    };
+    let red = (510.0 * fraction).round().min(255.0) as u8;
+    let green = (510.0 * (1.0 - fraction)).round().min(255.0) as u8;
+    AnsiColor::rgb(red, green, 0)
+}
+
 /// 文本样式配置
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TextStyleConfig {
@@ -179,25 +461,33 @@ pub struct TextStyleConfig {
 
 /// 颜色名称到 ratatui Color 的转换（兼容旧配置）
 pub fn color_from_name(name: &str) -> Color {
-    match name.to_lowercase().as_str() {
-        "black" => Color::Black,
-        "red" => Color::Red,
-        "green" => Color::Green,
-        "yellow" => Color::Yellow,
-        "blue" => Color::Blue,
-        "magenta" => Color::Magenta,
-        "cyan" => Color::Cyan,
-        "white" => Color::White,
-        "gray" | "grey" => Color::Gray,
-        "dark_gray" | "dark_grey" => Color::DarkGray,
-        "light_red" => Color::LightRed,
-        "light_green" => Color::LightGreen,
-        "light_yellow" => Color::LightYellow,
-        "light_blue" => Color::LightBlue,
-        "light_magenta" => Color::LightMagenta,
-        "light_cyan" => Color::LightCyan,
-        _ => Color::Reset,
-    }
+    ansi16_index_from_name(name)
+        .map(|code| AnsiColor::c16(code).to_ratatui_color())
+        .unwrap_or(Color::Reset)
+}
+
+/// 颜色名称对应的 16 色索引，与 [`AnsiColor::to_ratatui_color`] 的映射一致
+fn ansi16_index_from_name(name: &str) -> Option<u8> {
+    let code = match name.to_lowercase().as_str() {
+        "black" => 0,
+        "red" => 1,
+        "green" => 2,
+        "yellow" => 3,
+        "blue" => 4,
+        "magenta" => 5,
+        "cyan" => 6,
+        "white" => 7,
+        "dark_gray" | "dark_grey" => 8,
+        "light_red" => 9,
+        "light_green" => 10,
+        "light_yellow" => 11,
+        "light_blue" => 12,
+        "light_magenta" => 13,
+        "light_cyan" => 14,
+        "gray" | "grey" => 15,
+        _ => return None,
+    };
+    Some(code)
 }
 
 /// 默认 segment 图标
@@ -246,4 +536,181 @@ pub mod separators {
     pub const POWERLINE: &str = "\u{e0b0}";
     /// Powerline 细箭头
     pub const POWERLINE_THIN: &str = "\u{e0b1}";
+    /// 圆角左端
+    pub const ROUND_LEFT: &str = "\u{e0b6}";
+    /// 圆角右端
+    pub const ROUND_RIGHT: &str = "\u{e0b4}";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_powerline_glyph_round_trip() {
+        for glyph in PowerlineGlyph::ALL {
+            assert_eq!(PowerlineGlyph::from_filled(glyph.filled()), Some(glyph));
+            assert_ne!(glyph.filled(), glyph.thin());
+        }
+        assert_eq!(PowerlineGlyph::from_filled(" | "), None);
+    }
+
+    #[test]
+    fn test_to_ratatui_color_for_depth() {
+        let orange = AnsiColor::rgb(255, 135, 0);
+        assert_eq!(
+            orange.to_ratatui_color_for(ColorSupport::TrueColor),
+            orange.to_ratatui_color()
+        );
+        assert_eq!(
+            orange.to_ratatui_color_for(ColorSupport::Ansi256),
+            AnsiColor::c256(208).to_ratatui_color()
+        );
+        assert_eq!(
+            AnsiColor::rgb(250, 10, 10).to_ratatui_color_for(ColorSupport::Ansi16),
+            Color::LightRed
+        );
+        assert_eq!(
+            AnsiColor::c256(196).to_ratatui_color_for(ColorSupport::Ansi16),
+            Color::LightRed
+        );
+        assert_eq!(
+            ansi16::BLUE.to_ratatui_color_for(ColorSupport::Ansi16),
+            Color::Blue
+        );
+    }
+
+    #[test]
+    fn test_to_256_snaps_rgb() {
+        assert_eq!(AnsiColor::rgb(255, 255, 255).to_256(), 231);
+        assert_eq!(AnsiColor::rgb(0, 0, 0).to_256(), 16);
+        assert_eq!(AnsiColor::rgb(128, 128, 128).to_256(), 244);
+        assert_eq!(AnsiColor::c16(9).to_256(), 9);
+        assert_eq!(AnsiColor::c256(208).to_256(), 208);
+    }
+
+    #[test]
+    fn test_rgb_to_ansi256() {
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+        assert_eq!(rgb_to_ansi256(95, 135, 175), 67);
+        assert_eq!(rgb_to_ansi256(0, 255, 0), 46);
+        assert_eq!(rgb_to_ansi256(238, 238, 238), 255);
+
+        // 256 色表中的值应映射回自身
+        for code in 16..=255 {
+            let (r, g, b) = ansi256_to_rgb(code);
+            assert_eq!(rgb_to_ansi256(r, g, b), code, "code {code}");
+        }
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let orange = AnsiColor::rgb(255, 135, 0);
+        assert_eq!(orange.downgrade(ColorSupport::TrueColor), orange);
+        assert_eq!(
+            orange.downgrade(ColorSupport::Ansi256),
+            AnsiColor::c256(208)
+        );
+        assert_eq!(
+            AnsiColor::rgb(250, 10, 10).downgrade(ColorSupport::Ansi16),
+            ansi16::BRIGHT_RED
+        );
+        assert_eq!(
+            AnsiColor::c256(196).downgrade(ColorSupport::Ansi16),
+            ansi16::BRIGHT_RED
+        );
+        assert_eq!(ansi16::BLUE.downgrade(ColorSupport::Ansi16), ansi16::BLUE);
+    }
+
+    #[test]
+    fn test_try_from_str() {
+        let parse = |input: &str| AnsiColor::try_from(input);
+        assert_eq!(parse("red"), Ok(ansi16::RED));
+        assert_eq!(parse(" Light_Blue "), Ok(ansi16::BRIGHT_BLUE));
+        assert_eq!(parse("grey"), Ok(ansi16::BRIGHT_WHITE));
+        assert_eq!(parse("#ff8800"), Ok(AnsiColor::rgb(255, 136, 0)));
+        assert_eq!(parse("c256:214"), Ok(AnsiColor::c256(214)));
+        assert_eq!(parse("c16:5"), Ok(ansi16::MAGENTA));
+        assert_eq!(parse("214"), Ok(AnsiColor::c256(214)));
+        assert_eq!(parse("5"), Ok(ansi16::MAGENTA));
+
+        for invalid in ["", "crimson", "#ff88", "c256:300", "c16:16", "256", "-1"] {
+            assert_eq!(
+                parse(invalid),
+                Err(ParseColorError(invalid.to_string())),
+                "{invalid}"
+            );
+        }
+
+        // 名称表与旧的 color_from_name 保持一致
+        assert_eq!(color_from_name("white"), Color::White);
+        assert_eq!(color_from_name("gray"), Color::Gray);
+        assert_eq!(color_from_name("nope"), Color::Reset);
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(
+            AnsiColor::from_hex("FF8700"),
+            Some(AnsiColor::rgb(255, 135, 0))
+        );
+        assert_eq!(
+            AnsiColor::from_hex("#ff00ff"),
+            Some(AnsiColor::rgb(255, 0, 255))
+        );
+        for bad in [
+            "",
+            "#",
+            "ABCD",
+            "ABCDEF0",
+            "##ABCDEF",
+            "+12345",
+            "12 456",
+            "GG0000",
+            "1é234",
+            "ééé",
+            "中文中",
+        ] {
+            assert_eq!(AnsiColor::from_hex(bad), None, "{bad:?}");
+        }
+    }
+
+    /// 用固定种子的 xorshift 生成随机字符串，确认解析不会 panic，
+    /// 且只有合法的 6 位十六进制字符串才会得到颜色
+    #[test]
+    fn test_from_hex_fuzz() {
+        const POOL: &[char] = &[
+            '0', '7', '9', 'a', 'f', 'A', 'F', 'g', 'Z', '#', '+', '-', ' ', 'é', '中', '😀', '\0',
+        ];
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..20_000 {
+            let len = (next() % 9) as usize;
+            let input: String = (0..len)
+                .map(|_| POOL[(next() % POOL.len() as u64) as usize])
+                .collect();
+
+            let digits = input.strip_prefix('#').unwrap_or(&input);
+            let valid =
+                digits.chars().count() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit());
+            let parsed = AnsiColor::from_hex(&input);
+            assert_eq!(parsed.is_some(), valid, "{input:?}");
+            if let Some(AnsiColor::Rgb { r, g, b }) = parsed {
+                assert_eq!(
+                    format!("{r:02x}{g:02x}{b:02x}"),
+                    digits.to_ascii_lowercase()
+                );
+            }
+        }
+    }
 }
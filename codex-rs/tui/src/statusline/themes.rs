@@ -1,17 +1,26 @@
 // 主题预设系统
 
+use super::StatusLineContext;
+use super::build_statusline;
 use super::config::CxLineConfig;
 use super::config::SegmentItemConfig;
 use super::config::SegmentsConfig;
+use super::segment::SegmentId;
 use super::style::AnsiColor;
 use super::style::ColorConfig;
 use super::style::IconConfig;
 use super::style::StyleMode;
 use super::style::TextStyleConfig;
 use super::style::ansi16;
+use ratatui::text::Line;
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::SystemTime;
 
 /// 可用的预设主题名称
 pub const THEME_NAMES: &[&str] = &[
@@ -26,45 +35,127 @@ pub const THEME_NAMES: &[&str] = &[
     "powerline-tokyo-night",
 ];
 
+/// 内置主题文件首行的标记前缀，其后是文件正文的哈希
+/// 正文与哈希一致说明文件未被用户修改，可以随新版本更新
+const BUILTIN_MARKER: &str = "# cxline-builtin-theme: ";
+
+/// 已解析的主题文件缓存
+static THEME_CACHE: LazyLock<Mutex<ThemeCache>> = LazyLock::new(Default::default);
+
+/// 主题文件缓存：路径 -> (修改时间, 解析结果)
+#[derive(Default)]
+struct ThemeCache {
+    entries: HashMap<PathBuf, (SystemTime, CxLineConfig)>,
+}
+
+impl ThemeCache {
+    /// 文件修改时间未变时返回缓存结果，否则通过 read 重新读取并解析
+    fn load(
+        &mut self,
+        path: &Path,
+        read: impl Fn(&Path) -> std::io::Result<String>,
+    ) -> Option<CxLineConfig> {
+        let Ok(modified) = fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+            self.entries.remove(path);
+            return None;
+        };
+        if let Some((cached_at, config)) = self.entries.get(path)
+            && *cached_at == modified
+        {
+            return Some(config.clone());
+        }
+
+        let content = read(path).ok()?;
+        let config: CxLineConfig = toml::from_str(&content).ok()?;
+        self.entries
+            .insert(path.to_path_buf(), (modified, config.clone()));
+        Some(config)
+    }
+}
+
+/// 文件带有内置标记且正文哈希与标记一致
+fn is_unmodified_builtin(content: &str) -> bool {
+    let Some((first_line, body)) = content.split_once('\n') else {
+        return false;
+    };
+    first_line
+        .strip_prefix(BUILTIN_MARKER)
+        .is_some_and(|hash| hash == format!("{:016x}", fnv1a(body)))
+}
+
+/// 64 位 FNV-1a，跨版本稳定（标准库的 DefaultHasher 不保证）
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 /// 主题预设
 pub struct ThemePresets;
 
 impl ThemePresets {
     /// 获取主题目录路径
     pub fn themes_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".codex").join("cxline").join("themes"))
+        CxLineConfig::themes_dir()
     }
 
     /// 确保主题目录和预设文件存在
     pub fn ensure_themes_exist() {
         if let Some(themes_dir) = Self::themes_dir() {
-            if !themes_dir.exists() {
-                let _ = fs::create_dir_all(&themes_dir);
-            }
+            Self::ensure_themes_in(&themes_dir);
+        }
+    }
 
-            for theme_name in THEME_NAMES {
-                let theme_path = themes_dir.join(format!("{theme_name}.toml"));
-                if !theme_path.exists()
-                    && let Some(config) = Self::get_builtin(theme_name)
-                    && let Ok(content) = toml::to_string_pretty(&config)
-                {
+    /// 写入缺失的内置主题文件，并更新未被修改过的旧版内置主题（旧文件备份为 `.toml.bak`）
+    /// 没有标记或内容已被修改的文件视为用户主题，保持不变；可重复调用
+    fn ensure_themes_in(themes_dir: &Path) {
+        if !themes_dir.exists() {
+            let _ = fs::create_dir_all(themes_dir);
+        }
+
+        for theme_name in THEME_NAMES {
+            let theme_path = themes_dir.join(format!("{theme_name}.toml"));
+            let Some(content) = Self::builtin_file_content(theme_name) else {
+                continue;
+            };
+
+            let Ok(existing) = fs::read_to_string(&theme_path) else {
+                let _ = fs::write(&theme_path, content);
+                continue;
+            };
+            if existing != content && is_unmodified_builtin(&existing) {
+                let backup = themes_dir.join(format!("{theme_name}.toml.bak"));
+                if fs::write(&backup, &existing).is_ok() {
                     let _ = fs::write(&theme_path, content);
                 }
             }
         }
     }
 
-    /// 从文件加载主题
+    /// 内置主题文件内容：标记行 + TOML 正文
+    fn builtin_file_content(theme_name: &str) -> Option<String> {
+        let body = toml::to_string_pretty(&Self::get_builtin(theme_name)?).ok()?;
+        Some(format!("{BUILTIN_MARKER}{:016x}\n{body}", fnv1a(&body)))
+    }
+
+    /// 从文件加载主题（文件未修改时使用缓存）
     pub fn load_from_file(theme_name: &str) -> Option<CxLineConfig> {
         let themes_dir = Self::themes_dir()?;
         let theme_path = themes_dir.join(format!("{theme_name}.toml"));
 
-        if !theme_path.exists() {
-            return None;
-        }
+        THEME_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .load(&theme_path, |path| fs::read_to_string(path))
+    }
 
-        let content = fs::read_to_string(&theme_path).ok()?;
-        toml::from_str(&content).ok()
+    /// 清空主题文件缓存（用于测试或热重载）
+    pub fn clear_theme_cache() {
+        THEME_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .clear();
     }
 
     /// 获取主题（优先从文件加载，回退到内置预设）
@@ -84,12 +175,103 @@ impl ThemePresets {
         fs::create_dir_all(&themes_dir)?;
 
         let theme_path = themes_dir.join(format!("{theme_name}.toml"));
-        let content = toml::to_string_pretty(config)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        let content = config.to_toml_string()?;
 
         fs::write(&theme_path, content)
     }
 
+    /// 将配置导出为独立的主题文件，可放在任意路径分享给他人
+    pub fn export_to_path(config: &CxLineConfig, path: &Path) -> std::io::Result<()> {
+        fs::write(path, config.to_toml_string()?)
+    }
+
+    /// 从任意路径导入主题文件，校验通过后以其 `theme` 字段为名复制到主题目录
+    /// 同名主题已存在且内容不同时，先备份为 `.toml.bak` 再覆盖
+    pub fn import_from_path(path: &Path) -> std::io::Result<CxLineConfig> {
+        let themes_dir = Self::themes_dir()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "无法确定主题目录"))?;
+        Self::import_into(path, &themes_dir)
+    }
+
+    fn import_into(path: &Path, themes_dir: &Path) -> std::io::Result<CxLineConfig> {
+        let invalid =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+        let content = fs::read_to_string(path)?;
+        let config: CxLineConfig =
+            toml::from_str(&content).map_err(|e| invalid(format!("解析主题失败: {e}")))?;
+        if let Err(errors) = config.validate() {
+            let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            return Err(invalid(format!("主题校验失败: {}", messages.join("; "))));
+        }
+
+        // 主题名直接用作文件名，不允许为空或包含路径
+        let name = config.theme.as_str();
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(invalid(format!("无效的主题名: {name:?}")));
+        }
+
+        fs::create_dir_all(themes_dir)?;
+        let theme_path = themes_dir.join(format!("{name}.toml"));
+        if let Ok(existing) = fs::read_to_string(&theme_path)
+            && existing != content
+        {
+            fs::write(themes_dir.join(format!("{name}.toml.bak")), existing)?;
+        }
+        fs::write(theme_path, content)?;
+        Ok(config)
+    }
+
+    /// 用同一份上下文渲染每个主题的示例状态栏，返回 (主题名, 渲染结果)
+    pub fn render_gallery(ctx: &StatusLineContext<'_>) -> Vec<(String, Line<'static>)> {
+        THEME_NAMES
+            .iter()
+            .map(|name| {
+                let config = Self::get_theme(name);
+                let line = build_statusline(&config, ctx).render_line();
+                (name.to_string(), line)
+            })
+            .collect()
+    }
+
+    /// 校验所有内置主题能经 TOML 序列化后原样读回
+    /// 失败时返回 (主题名, 错误描述) 列表
+    pub fn validate_all() -> Result<(), Vec<(String, String)>> {
+        let errors: Vec<(String, String)> = THEME_NAMES
+            .iter()
+            .filter_map(|name| {
+                Self::validate_round_trip(name)
+                    .err()
+                    .map(|err| (name.to_string(), err))
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 单个内置主题的 TOML 往返校验
+    fn validate_round_trip(theme_name: &str) -> Result<(), String> {
+        let config = Self::get_builtin(theme_name).ok_or("不是内置主题")?;
+        let content = toml::to_string_pretty(&config).map_err(|e| format!("序列化失败: {e}"))?;
+        let parsed: CxLineConfig =
+            toml::from_str(&content).map_err(|e| format!("解析失败: {e}"))?;
+
+        if parsed.theme != theme_name {
+            return Err(format!("主题名不一致: {}", parsed.theme));
+        }
+
+        let expected = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+        let actual = serde_json::to_value(&parsed).map_err(|e| e.to_string())?;
+        if expected != actual {
+            return Err("往返后配置不一致".to_string());
+        }
+        Ok(())
+    }
+
     /// 获取内置预设主题
     pub fn get_builtin(theme_name: &str) -> Option<CxLineConfig> {
         match theme_name {
@@ -109,156 +291,196 @@ impl ThemePresets {
     /// Default 主题
     pub fn get_default() -> CxLineConfig {
         CxLineConfig {
-            enabled: true,
             theme: "default".to_string(),
             style: StyleMode::Plain,
             separator: " │ ".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_YELLOW, ansi16::BRIGHT_GREEN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_MAGENTA, ansi16::BRIGHT_MAGENTA),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(ansi16::BRIGHT_YELLOW, ansi16::BRIGHT_GREEN),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(ansi16::BRIGHT_MAGENTA, ansi16::BRIGHT_MAGENTA),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
+                        TextStyleConfig::default(),
+                    )
+                },
+                // 宿主目前不提供会话 token 预算，接入数据前默认关闭
+                SegmentItemConfig {
+                    enabled: false,
+                    ..SegmentItemConfig::new(
+                        SegmentId::Budget,
+                        IconConfig::new("💰", "\u{f0d6}"),
+                        ColorConfig::new(ansi16::BRIGHT_GREEN, ansi16::BRIGHT_GREEN),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig {
+                    enabled: false,
+                    ..SegmentItemConfig::new(
+                        SegmentId::Jj,
+                        IconConfig::new("🥋", "\u{e725}"),
+                        ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::new(
+                    SegmentId::Edits,
+                    IconConfig::new("✎", "\u{f044}"),
+                    ColorConfig::new(ansi16::BRIGHT_YELLOW, ansi16::BRIGHT_YELLOW),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    enabled: false,
+                    ..SegmentItemConfig::new(
+                        SegmentId::Timezone,
+                        IconConfig::new("🌐", "\u{f0ac}"),
+                        ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig {
+                    enabled: false,
+                    ..SegmentItemConfig::new(
+                        SegmentId::Host,
+                        IconConfig::new("🖥", "\u{f108}"),
+                        ColorConfig::new(ansi16::BRIGHT_MAGENTA, ansi16::BRIGHT_MAGENTA),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::new(
+                    SegmentId::Warning,
+                    IconConfig::new("", ""),
+                    ColorConfig::new(ansi16::RED, ansi16::RED),
+                    TextStyleConfig::default(),
+                ),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
     /// Cometix 主题
     pub fn get_cometix() -> CxLineConfig {
         CxLineConfig {
-            enabled: true,
             theme: "cometix".to_string(),
             style: StyleMode::NerdFont,
             separator: " │ ".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_YELLOW, ansi16::BRIGHT_GREEN),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_MAGENTA, ansi16::BRIGHT_MAGENTA),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(ansi16::BRIGHT_YELLOW, ansi16::BRIGHT_GREEN),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(ansi16::BRIGHT_MAGENTA, ansi16::BRIGHT_MAGENTA),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
     /// Minimal 主题
     pub fn get_minimal() -> CxLineConfig {
         CxLineConfig {
-            enabled: true,
             theme: "minimal".to_string(),
             style: StyleMode::Plain,
             separator: " │ ".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("✽", "\u{f2d0}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("◐", "\u{f024b}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_YELLOW, ansi16::BRIGHT_GREEN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("※", "\u{f02a2}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("◐", "\u{f49b}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_MAGENTA, ansi16::BRIGHT_MAGENTA),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("✽", "\u{f2d0}"),
+                    ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("◐", "\u{f024b}"),
+                    ColorConfig::new(ansi16::BRIGHT_YELLOW, ansi16::BRIGHT_GREEN),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("※", "\u{f02a2}"),
+                    ColorConfig::new(ansi16::BRIGHT_BLUE, ansi16::BRIGHT_BLUE),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("◐", "\u{f49b}"),
+                    ColorConfig::new(ansi16::BRIGHT_MAGENTA, ansi16::BRIGHT_MAGENTA),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
@@ -269,52 +491,51 @@ impl ThemePresets {
         let gruvbox_cyan = AnsiColor::c256(109);
 
         CxLineConfig {
-            enabled: true,
             theme: "gruvbox".to_string(),
             style: StyleMode::NerdFont,
             separator: " │ ".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(gruvbox_orange, gruvbox_orange),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(gruvbox_green, gruvbox_green),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(gruvbox_cyan, gruvbox_cyan),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(ansi16::MAGENTA, ansi16::MAGENTA),
-                    styles: TextStyleConfig { text_bold: true },
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(gruvbox_orange, gruvbox_orange),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(gruvbox_green, gruvbox_green),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(gruvbox_cyan, gruvbox_cyan),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(ansi16::MAGENTA, ansi16::MAGENTA),
+                    TextStyleConfig { text_bold: true },
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(ansi16::BRIGHT_CYAN, ansi16::BRIGHT_CYAN),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
@@ -328,52 +549,51 @@ impl ThemePresets {
         let bg_usage = AnsiColor::rgb(235, 203, 139);
 
         CxLineConfig {
-            enabled: true,
             theme: "nord".to_string(),
             style: StyleMode::Powerline,
             separator: "\u{e0b0}".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(nord_polar, nord_polar).with_background(bg_model),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(nord_polar, nord_polar).with_background(bg_dir),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(nord_polar, nord_polar).with_background(bg_git),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(nord_polar, nord_polar).with_background(bg_context),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(nord_polar, nord_polar).with_background(bg_usage),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(nord_polar, nord_polar).with_background(bg_model),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(nord_polar, nord_polar).with_background(bg_dir),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(nord_polar, nord_polar).with_background(bg_git),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(nord_polar, nord_polar).with_background(bg_context),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(nord_polar, nord_polar).with_background(bg_usage),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
@@ -389,52 +609,51 @@ impl ThemePresets {
         let bg_usage = AnsiColor::rgb(45, 50, 59);
 
         CxLineConfig {
-            enabled: true,
             theme: "powerline-dark".to_string(),
             style: StyleMode::Powerline,
             separator: "\u{e0b0}".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(white, white).with_background(bg_model),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(white, white).with_background(bg_dir),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(white, white).with_background(bg_git),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(light_gray, light_gray).with_background(bg_context),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(light_gray, light_gray).with_background(bg_usage),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(white, white).with_background(bg_model),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(white, white).with_background(bg_dir),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(white, white).with_background(bg_git),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(light_gray, light_gray).with_background(bg_context),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(light_gray, light_gray).with_background(bg_usage),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
@@ -450,52 +669,51 @@ impl ThemePresets {
         let bg_usage = AnsiColor::rgb(40, 167, 69);
 
         CxLineConfig {
-            enabled: true,
             theme: "powerline-light".to_string(),
             style: StyleMode::Powerline,
             separator: "\u{e0b0}".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(black, black).with_background(bg_model),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(white, white).with_background(bg_dir),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(white, white).with_background(bg_git),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(white, white).with_background(bg_context),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(white, white).with_background(bg_usage),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(black, black).with_background(bg_model),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(white, white).with_background(bg_dir),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(white, white).with_background(bg_git),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(white, white).with_background(bg_context),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(white, white).with_background(bg_usage),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
@@ -514,52 +732,51 @@ impl ThemePresets {
         let bg_usage = AnsiColor::rgb(35, 33, 54);
 
         CxLineConfig {
-            enabled: true,
             theme: "powerline-rose-pine".to_string(),
             style: StyleMode::Powerline,
             separator: "\u{e0b0}".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(rose, rose).with_background(bg_model),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(iris, iris).with_background(bg_dir),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(foam, foam).with_background(bg_git),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(subtle, subtle).with_background(bg_context),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(gold, gold).with_background(bg_usage),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(rose, rose).with_background(bg_model),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(iris, iris).with_background(bg_dir),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(foam, foam).with_background(bg_git),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(subtle, subtle).with_background(bg_context),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(gold, gold).with_background(bg_usage),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 
@@ -578,52 +795,219 @@ impl ThemePresets {
         let bg_usage = AnsiColor::rgb(36, 40, 59);
 
         CxLineConfig {
-            enabled: true,
             theme: "powerline-tokyo-night".to_string(),
             style: StyleMode::Powerline,
             separator: "\u{e0b0}".to_string(),
-            segments: SegmentsConfig {
-                model: SegmentItemConfig {
-                    id: super::segment::SegmentId::Model,
-                    enabled: true,
-                    icon: IconConfig::new("🤖", "\u{e26d}"),
-                    colors: ColorConfig::new(magenta, magenta).with_background(bg_model),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                directory: SegmentItemConfig {
-                    id: super::segment::SegmentId::Directory,
-                    enabled: true,
-                    icon: IconConfig::new("📁", "\u{f024b}"),
-                    colors: ColorConfig::new(blue, blue).with_background(bg_dir),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                git: SegmentItemConfig {
-                    id: super::segment::SegmentId::Git,
-                    enabled: true,
-                    icon: IconConfig::new("🌿", "\u{f02a2}"),
-                    colors: ColorConfig::new(green, green).with_background(bg_git),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                context: SegmentItemConfig {
-                    id: super::segment::SegmentId::Context,
-                    enabled: true,
-                    icon: IconConfig::new("⚡️", "\u{f49b}"),
-                    colors: ColorConfig::new(lavender, lavender).with_background(bg_context),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-                usage: SegmentItemConfig {
-                    id: super::segment::SegmentId::Usage,
-                    enabled: true,
-                    icon: IconConfig::new("📊", "\u{f0a9e}"),
-                    colors: ColorConfig::new(orange, orange).with_background(bg_usage),
-                    styles: TextStyleConfig::default(),
-                    options: HashMap::new(),
-                },
-            },
+            segments: SegmentsConfig::new(vec![
+                SegmentItemConfig::new(
+                    SegmentId::Model,
+                    IconConfig::new("🤖", "\u{e26d}"),
+                    ColorConfig::new(magenta, magenta).with_background(bg_model),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Directory,
+                    IconConfig::new("📁", "\u{f024b}"),
+                    ColorConfig::new(blue, blue).with_background(bg_dir),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Git,
+                    IconConfig::new("🌿", "\u{f02a2}"),
+                    ColorConfig::new(green, green).with_background(bg_git),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig::new(
+                    SegmentId::Context,
+                    IconConfig::new("⚡️", "\u{f49b}"),
+                    ColorConfig::new(lavender, lavender).with_background(bg_context),
+                    TextStyleConfig::default(),
+                ),
+                SegmentItemConfig {
+                    secondary_prefix: Some("· ".to_string()),
+                    ..SegmentItemConfig::new(
+                        SegmentId::Usage,
+                        IconConfig::new("📊", "\u{f0a9e}"),
+                        ColorConfig::new(orange, orange).with_background(bg_usage),
+                        TextStyleConfig::default(),
+                    )
+                },
+                SegmentItemConfig::default_for(SegmentId::Budget),
+                SegmentItemConfig::default_for(SegmentId::Jj),
+                SegmentItemConfig::default_for(SegmentId::Edits),
+                SegmentItemConfig::default_for(SegmentId::Timezone),
+                SegmentItemConfig::default_for(SegmentId::Host),
+                SegmentItemConfig::default_for(SegmentId::Warning),
+            ]),
+            ..CxLineConfig::default()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_ensure_themes_updates_stale_builtin_only() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = |name: &str| dir.path().join(name);
+        let read = |name: &str| fs::read_to_string(path(name)).expect("theme file");
+        let current = ThemePresets::builtin_file_content("nord").expect("nord");
+
+        // 旧版本写入、未被修改过的内置主题
+        let old_body = "style = \"plain\"\n";
+        let stale = format!("{BUILTIN_MARKER}{:016x}\n{old_body}", fnv1a(old_body));
+        fs::write(path("nord.toml"), &stale).expect("write stale");
+        // 同名但没有标记（用户另存的主题）和用户自建主题
+        fs::write(path("gruvbox.toml"), "style = \"plain\"\n").expect("write user");
+        fs::write(path("mine.toml"), "style = \"plain\"\n").expect("write custom");
+        // 带标记但正文被用户改过
+        let edited = format!(
+            "{BUILTIN_MARKER}{:016x}\nseparator = \"|\"\n",
+            fnv1a(old_body)
+        );
+        fs::write(path("minimal.toml"), &edited).expect("write edited");
+
+        ThemePresets::ensure_themes_in(dir.path());
+
+        assert_eq!(read("nord.toml"), current);
+        assert_eq!(read("nord.toml.bak"), stale);
+        assert_eq!(read("gruvbox.toml"), "style = \"plain\"\n");
+        assert_eq!(read("mine.toml"), "style = \"plain\"\n");
+        assert_eq!(read("minimal.toml"), edited);
+        assert!(!path("gruvbox.toml.bak").exists());
+        assert_eq!(
+            read("cometix.toml"),
+            ThemePresets::builtin_file_content("cometix").expect("cometix")
+        );
+
+        // 已是最新版本时不再备份
+        fs::remove_file(path("nord.toml.bak")).expect("remove backup");
+        ThemePresets::ensure_themes_in(dir.path());
+        assert!(!path("nord.toml.bak").exists());
+        assert!(
+            toml::from_str::<CxLineConfig>(&read("nord.toml")).is_ok(),
+            "标记行是 TOML 注释"
+        );
+    }
+
+    #[test]
+    fn test_builtin_themes_round_trip() {
+        assert_eq!(ThemePresets::validate_all(), Ok(()));
+        for name in THEME_NAMES {
+            assert!(ThemePresets::get_builtin(name).is_some(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_render_gallery() {
+        let ctx = StatusLineContext::new("gpt-5.2-codex", std::path::Path::new("/repo"))
+            .with_cwd_check(false)
+            .with_git_preview("main", "✓", 0, 0);
+        let gallery = ThemePresets::render_gallery(&ctx);
+
+        let names: Vec<&str> = gallery.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, THEME_NAMES);
+        for (name, line) in &gallery {
+            assert!(line.width() > 0, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_theme_cache_invalidated_by_mtime() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("minimal.toml");
+        let content = toml::to_string_pretty(&ThemePresets::get_minimal()).expect("serialize");
+        fs::write(&path, content).expect("write theme");
+
+        let reads = std::cell::Cell::new(0);
+        let read = |path: &Path| {
+            reads.set(reads.get() + 1);
+            fs::read_to_string(path)
+        };
+        let mut cache = ThemeCache::default();
+
+        let first = cache.load(&path, read).expect("theme");
+        let second = cache.load(&path, read).expect("cached theme");
+        assert_eq!(first.theme, second.theme);
+        assert_eq!(reads.get(), 1);
+
+        // 修改时间变化后重新读取
+        let later = SystemTime::now() + std::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(later))
+            .expect("touch theme");
+        cache.load(&path, read).expect("reloaded theme");
+        assert_eq!(reads.get(), 2);
+
+        // 文件删除后不再返回缓存
+        fs::remove_file(&path).expect("remove theme");
+        assert!(cache.load(&path, read).is_none());
+    }
+
+    #[test]
+    fn test_export_and_import_theme_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let themes_dir = dir.path().join("themes");
+        let shared = dir.path().join("shared.toml");
+
+        let mut config = ThemePresets::get_nord();
+        config.theme = "friend-nord".to_string();
+        config.tight_pills = true;
+        ThemePresets::export_to_path(&config, &shared).expect("export");
+
+        let imported = ThemePresets::import_into(&shared, &themes_dir).expect("import");
+        assert_eq!(imported.theme, "friend-nord");
+        assert!(imported.tight_pills);
+        let copied = fs::read_to_string(themes_dir.join("friend-nord.toml")).expect("copied");
+        assert_eq!(copied, fs::read_to_string(&shared).expect("shared"));
+
+        // 应用导入的主题时保留主题级字段
+        let mut current = ThemePresets::get_default();
+        current.apply_theme_config(imported);
+        assert_eq!(current.theme, "friend-nord");
+        assert!(current.tight_pills);
+        assert_eq!(current.style, config.style);
+        assert_eq!(current.order, config.order);
+
+        // 无法解析或主题名不能作为文件名时拒绝导入
+        fs::write(&shared, "style = [not toml").expect("write");
+        let err = ThemePresets::import_into(&shared, &themes_dir).expect_err("invalid toml");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        config.theme = "../escape".to_string();
+        ThemePresets::export_to_path(&config, &shared).expect("export");
+        assert!(ThemePresets::import_into(&shared, &themes_dir).is_err());
+        assert!(!dir.path().join("escape.toml").exists());
+    }
+
+    #[test]
+    fn test_import_backs_up_existing_theme() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let themes_dir = dir.path().join("themes");
+        let shared = dir.path().join("shared.toml");
+        fs::create_dir_all(&themes_dir).expect("create themes dir");
+        let customized = "# 用户自定义的 nord\n";
+        fs::write(themes_dir.join("nord.toml"), customized).expect("write custom");
+
+        let mut config = ThemePresets::get_nord();
+        config.tight_pills = true;
+        ThemePresets::export_to_path(&config, &shared).expect("export");
+        ThemePresets::import_into(&shared, &themes_dir).expect("import");
+
+        let read = |name: &str| fs::read_to_string(themes_dir.join(name)).expect(name);
+        assert_eq!(read("nord.toml.bak"), customized);
+        assert_eq!(
+            read("nord.toml"),
+            fs::read_to_string(&shared).expect("shared")
+        );
+
+        // 内容相同的重复导入不覆盖已有备份
+        ThemePresets::import_into(&shared, &themes_dir).expect("import again");
+        assert_eq!(read("nord.toml.bak"), customized);
+    }
+}
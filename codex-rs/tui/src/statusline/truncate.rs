@@ -0,0 +1,149 @@
+// 文本截断工具
+// 按显示宽度截断，不会拆分多字节字符或字素簇
+
+use serde::Deserialize;
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// 截断指示符
+pub const ELLIPSIS: &str = "…";
+
+/// 省略号位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncatePosition {
+    /// 保留开头，省略号在末尾："/home/user/…"
+    #[default]
+    End,
+    /// 保留结尾，省略号在开头："…jects/codex"
+    Start,
+    /// 保留首尾，省略号在中间："/home/…codex"
+    Middle,
+}
+
+/// 按显示宽度截断文本，结果（含省略号）不超过 max_width
+pub fn truncate_display(text: &str, max_width: usize, position: TruncatePosition) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+
+    let Some(budget) = max_width.checked_sub(ELLIPSIS.width()) else {
+        return String::new();
+    };
+
+    match position {
+        TruncatePosition::End => format!("{}{ELLIPSIS}", take_head(text, budget)),
+        TruncatePosition::Start => format!("{ELLIPSIS}{}", take_tail(text, budget)),
+        TruncatePosition::Middle => {
+            let head_width = budget.div_ceil(2);
+            let tail_width = budget - head_width;
+            format!(
+                "{}{ELLIPSIS}{}",
+                take_head(text, head_width),
+                take_tail(text, tail_width)
+            )
+        }
+    }
+}
+
+/// 取开头不超过 width 的部分
+fn take_head(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    let mut end = 0;
+    for (idx, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            break;
+        }
+        end = idx + grapheme.len();
+    }
+    &text[..end]
+}
+
+/// 取结尾不超过 width 的部分
+fn take_tail(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    let mut start = text.len();
+    for (idx, grapheme) in text.grapheme_indices(true).rev() {
+        used += grapheme.width();
+        if used > width {
+            break;
+        }
+        start = idx;
+    }
+    &text[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::statusline::Segment;
+    use crate::statusline::SegmentId;
+    use crate::statusline::StatusLineContext;
+    use crate::statusline::config::SegmentItemConfig;
+    use crate::statusline::segments::GitSegment;
+    use std::path::Path;
+
+    const PATH: &str = "/home/user/projects/codex";
+
+    #[test]
+    fn test_truncate_end() {
+        assert_eq!(
+            truncate_display(PATH, 12, TruncatePosition::End),
+            "/home/user/…"
+        );
+    }
+
+    #[test]
+    fn test_truncate_start() {
+        assert_eq!(
+            truncate_display(PATH, 12, TruncatePosition::Start),
+            "…jects/codex"
+        );
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        assert_eq!(
+            truncate_display(PATH, 12, TruncatePosition::Middle),
+            "/home/…codex"
+        );
+    }
+
+    #[test]
+    fn test_truncate_position_from_options() {
+        let ctx = StatusLineContext::new("", Path::new("/")).with_git_preview(
+            "feature/JIRA-1234-really-long-description",
+            "✓",
+            0,
+            0,
+        );
+        let branch_for = |position: &str| {
+            let mut config = SegmentItemConfig::default_for(SegmentId::Git);
+            config
+                .options
+                .insert("max_branch_len".to_string(), serde_json::json!(8));
+            config
+                .options
+                .insert("truncate_position".to_string(), serde_json::json!(position));
+            GitSegment::from_config(&config)
+                .collect(&ctx)
+                .map(|data| data.primary)
+        };
+
+        assert_eq!(branch_for("start").as_deref(), Some("…ription"));
+        assert_eq!(branch_for("end").as_deref(), Some("feature…"));
+        assert_eq!(branch_for("middle").as_deref(), Some("feat…ion"));
+    }
+
+    #[test]
+    fn test_truncate_fits_or_no_room() {
+        assert_eq!(truncate_display(PATH, 40, TruncatePosition::Middle), PATH);
+        assert_eq!(truncate_display(PATH, 0, TruncatePosition::End), "");
+        assert_eq!(
+            truncate_display("项目目录", 5, TruncatePosition::End),
+            "项目…"
+        );
+    }
+}